@@ -1,79 +1,632 @@
 mod trading;
 
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tauri::{Manager, State};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_notification::NotificationExt;
 use trading::engine::TradingEngine;
+use trading::ab::{self, AbResults, AbTest, AbVariantSpec};
+use trading::alerts::{self, AlertCondition, PriceAlert};
+use trading::backtest::{self, BacktestResult, SweepGrid};
+use trading::batch::CachedPrediction;
+use trading::archive;
+use trading::bundle;
+use trading::currency::PegStatus;
+use trading::conditional::{ConditionalOrder, EntryTrigger};
+use trading::db::{Database, HistoryPage, HistoryQuery};
+use trading::diagnostics::DiagnosticsReport;
+use trading::error::CommandError;
+use trading::embeddings::SimilarMarket;
+use trading::execution::{ExecutionQuality, ExecutionReport};
+use trading::market_maker::{self, MakerBook};
+use trading::negrisk::NegRiskOpportunity;
+use trading::risk::{ExposureGroup, MarketPosition};
+use trading::scoring::{CandidateScore, PackCalibration};
 use trading::models::*;
+use trading::guest::{self, GuestMode};
+use trading::health::HealthStatus;
+use trading::i18n::Locale;
+use trading::ledger::{CapitalFlow, LedgerEvent};
+use trading::fills::RestingOrder;
+use trading::gamma::ParseBenchmark;
+use trading::flatten::FlattenReport;
+use trading::lifecycle::OrderTransition;
+use trading::network::{self, Network};
+use trading::notifications::{Notification, Notifier};
+use trading::prompt_packs::{PromptPack, PromptPacks};
+use trading::reports::{self, PerformanceReport, ReportPeriod};
+use trading::reunderwrite::ThesisReview;
+use trading::simulation::{self, SimulationParams, SimulationResult};
+use trading::snapshot::{ActivityDelta, StatsCursor, StatsDelta};
+use trading::supervisor;
+use trading::wallet::{AllowanceStatus, ApprovalBatch};
 
 type EngineState = Arc<Mutex<TradingEngine>>;
+type AbState = Arc<Mutex<Option<AbTest>>>;
+
+// ─── Desktop Notifications ──────────────────────────────────────────
+
+/// Delivers engine notifications as native OS notifications
+struct DesktopNotifier {
+    app: AppHandle,
+}
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, notification: &Notification) {
+        let result = self.app
+            .notification()
+            .builder()
+            .title(&notification.title)
+            .body(&notification.body)
+            .show();
+        if let Err(e) = result {
+            log::warn!("Failed to show desktop notification: {}", e);
+        }
+    }
+}
 
 // ─── Tauri Commands ─────────────────────────────────────────────────
 
 #[tauri::command]
-async fn get_stats(engine: State<'_, EngineState>) -> Result<BotStats, String> {
+async fn get_stats(engine: State<'_, EngineState>) -> Result<BotStats, CommandError> {
     let eng = engine.lock().await;
     Ok(eng.get_stats())
 }
 
+/// Stats only when they changed since `cursor`, plus balance points added after it
+#[tauri::command]
+async fn get_stats_delta(engine: State<'_, EngineState>, cursor: Option<StatsCursor>) -> Result<StatsDelta, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_stats_delta(cursor.unwrap_or_default()))
+}
+
+#[tauri::command]
+async fn get_activity_log(engine: State<'_, EngineState>) -> Result<Arc<Vec<ActivityEntry>>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_activity_feed())
+}
+
+/// Feed entries added after `cursor`; poll with the returned cursor instead of
+/// refetching the whole log
 #[tauri::command]
-async fn get_activity_log(engine: State<'_, EngineState>) -> Result<Vec<ActivityEntry>, String> {
+async fn get_activity_since(engine: State<'_, EngineState>, cursor: u64) -> Result<ActivityDelta, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_activity_since(cursor))
+}
+
+#[tauri::command]
+async fn get_full_activity_log(engine: State<'_, EngineState>) -> Result<Arc<Vec<ActivityEntry>>, CommandError> {
     let eng = engine.lock().await;
     Ok(eng.get_activity_log())
 }
 
 #[tauri::command]
-async fn get_balance_history(engine: State<'_, EngineState>) -> Result<Vec<BalancePoint>, String> {
+async fn get_ledger(engine: State<'_, EngineState>) -> Result<Vec<LedgerEvent>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_ledger())
+}
+
+#[tauri::command]
+async fn record_capital_flow(engine: State<'_, EngineState>, amount: f64, note: Option<String>) -> Result<BotStats, CommandError> {
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    eng.record_capital_flow(amount, note.as_deref().unwrap_or(""))?;
+    Ok(eng.get_stats())
+}
+
+#[tauri::command]
+async fn detect_capital_flows(engine: State<'_, EngineState>) -> Result<u32, CommandError> {
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    eng.detect_capital_flows().await.map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn check_allowances(engine: State<'_, EngineState>) -> Result<AllowanceStatus, CommandError> {
+    let eng = engine.lock().await;
+    eng.check_allowances().await.map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn set_allowances(engine: State<'_, EngineState>) -> Result<ApprovalBatch, CommandError> {
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    eng.set_allowances().await.map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn get_capital_flows(engine: State<'_, EngineState>) -> Result<Vec<CapitalFlow>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_capital_flows())
+}
+
+/// One page of a history table (markets, predictions, orders, fills, resolutions,
+/// sessions or activity), filtered and sorted
+#[tauri::command]
+async fn query_history(engine: State<'_, EngineState>, query: HistoryQuery) -> Result<HistoryPage, CommandError> {
+    let eng = engine.lock().await;
+    eng.query_history(&query).map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn get_balance_history(engine: State<'_, EngineState>) -> Result<Arc<Vec<BalancePoint>>, CommandError> {
     let eng = engine.lock().await;
     Ok(eng.get_balance_history())
 }
 
 #[tauri::command]
-async fn save_config(engine: State<'_, EngineState>, config: BotConfig) -> Result<String, String> {
+async fn get_orders(engine: State<'_, EngineState>) -> Result<Arc<Vec<Order>>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_orders())
+}
+
+/// Latest USD price of the currency of record and whether it is off its peg
+#[tauri::command]
+async fn get_peg_status(engine: State<'_, EngineState>) -> Result<Option<PegStatus>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_peg_status())
+}
+
+/// Open orders aggregated into one position per market, with entry count and average price
+#[tauri::command]
+async fn get_market_positions(engine: State<'_, EngineState>) -> Result<Vec<MarketPosition>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_market_positions())
+}
+
+/// Open positions grouped with the markets their questions are similar to
+#[tauri::command]
+async fn get_exposure_groups(engine: State<'_, EngineState>) -> Result<Vec<ExposureGroup>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_exposure_groups())
+}
+
+#[tauri::command]
+async fn get_portfolio(engine: State<'_, EngineState>) -> Result<PortfolioSummary, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_portfolio())
+}
+
+#[tauri::command]
+async fn get_latest_commentary(engine: State<'_, EngineState>) -> Result<Option<PortfolioCommentary>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_latest_commentary())
+}
+
+#[tauri::command]
+async fn get_prompt_usage(engine: State<'_, EngineState>) -> Result<Vec<PromptUsage>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_prompt_usage())
+}
+
+#[tauri::command]
+async fn get_cache_tokens(engine: State<'_, EngineState>) -> Result<(u64, u64), CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_cache_tokens())
+}
+
+#[tauri::command]
+async fn save_config(engine: State<'_, EngineState>, config: BotConfig) -> Result<String, CommandError> {
     let mut eng = engine.lock().await;
-    eng.configure(config);
+    eng.ensure_writable()?;
+    eng.configure(config)?;
     Ok("Configuration saved successfully".to_string())
 }
 
 #[tauri::command]
-async fn get_config(engine: State<'_, EngineState>) -> Result<BotConfig, String> {
+async fn get_config(engine: State<'_, EngineState>) -> Result<BotConfig, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_config())
+}
+
+#[tauri::command]
+async fn get_network(engine: State<'_, EngineState>) -> Result<Network, CommandError> {
+    let eng = engine.lock().await;
+    Ok(network::resolve(&eng.config))
+}
+
+#[tauri::command]
+async fn get_health(engine: State<'_, EngineState>) -> Result<HealthStatus, CommandError> {
     let eng = engine.lock().await;
-    Ok(eng.config.clone())
+    Ok(eng.get_health())
 }
 
+/// Self-test of connectivity, credentials, clock, disk and RPC, meant to pass before going live
 #[tauri::command]
-async fn start_bot(engine: State<'_, EngineState>) -> Result<String, String> {
+async fn run_diagnostics(engine: State<'_, EngineState>) -> Result<DiagnosticsReport, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.run_diagnostics().await)
+}
+
+#[tauri::command]
+async fn benchmark_market_parsing(
+    engine: State<'_, EngineState>,
+    rounds: Option<u32>,
+) -> Result<ParseBenchmark, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.benchmark_market_parsing(rounds.unwrap_or(20)).await?)
+}
+
+#[tauri::command]
+async fn get_guest_mode(engine: State<'_, EngineState>) -> Result<GuestMode, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.guest)
+}
+
+#[tauri::command]
+async fn set_guest_mode(
+    engine: State<'_, EngineState>,
+    enabled: bool,
+    mask_balances: bool,
+    pin: Option<String>,
+) -> Result<GuestMode, CommandError> {
     let mut eng = engine.lock().await;
+    eng.set_guest_mode(enabled, mask_balances, pin.as_deref()).map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn get_locale(engine: State<'_, EngineState>) -> Result<Locale, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.locale)
+}
+
+/// Switch the language activity messages are rendered in; stored entries keep their keys
+#[tauri::command]
+async fn set_locale(engine: State<'_, EngineState>, locale: Locale) -> Result<Locale, CommandError> {
+    let mut eng = engine.lock().await;
+    eng.locale = locale;
+    Ok(eng.locale)
+}
+
+#[tauri::command]
+async fn start_bot(engine: State<'_, EngineState>) -> Result<String, CommandError> {
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
     eng.start();
     Ok("Bot started".to_string())
 }
 
 #[tauri::command]
-async fn stop_bot(engine: State<'_, EngineState>) -> Result<String, String> {
+async fn stop_bot(engine: State<'_, EngineState>) -> Result<String, CommandError> {
     let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
     eng.stop();
     Ok("Bot stopped".to_string())
 }
 
 #[tauri::command]
-async fn get_bot_status(engine: State<'_, EngineState>) -> Result<bool, String> {
+async fn get_bot_status(engine: State<'_, EngineState>) -> Result<bool, CommandError> {
     let eng = engine.lock().await;
     Ok(eng.is_running)
 }
 
 #[tauri::command]
-async fn run_cycle(engine: State<'_, EngineState>) -> Result<Vec<ActivityEntry>, String> {
+async fn run_cycle(engine: State<'_, EngineState>) -> Result<Vec<ActivityEntry>, CommandError> {
+    // The background loop holds the lock for a whole cycle; don't queue a second one behind it
+    let mut eng = engine.try_lock().map_err(|_| CommandError::busy())?;
+    eng.ensure_writable()?;
+    Ok(eng.run_cycle().await?)
+}
+
+#[tauri::command]
+async fn find_similar_markets(
+    engine: State<'_, EngineState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SimilarMarket>, CommandError> {
+    let eng = engine.lock().await;
+    eng.find_similar_markets(&query, limit.unwrap_or(10))
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Latest live or batch prediction per market, newest first
+#[tauri::command]
+async fn get_prediction_cache(engine: State<'_, EngineState>) -> Result<Vec<CachedPrediction>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_prediction_cache())
+}
+
+/// Quotes, inventory and PnL for each market-making market
+#[tauri::command]
+async fn get_market_maker(engine: State<'_, EngineState>) -> Result<Vec<MakerBook>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_market_maker())
+}
+
+/// Alert when an outcome's price is above, below or crosses `threshold`; works while
+/// the bot is stopped
+#[tauri::command]
+async fn create_price_alert(
+    engine: State<'_, EngineState>,
+    market_id: String,
+    condition: AlertCondition,
+    threshold: f64,
+    outcome: Option<String>,
+) -> Result<PriceAlert, CommandError> {
     let mut eng = engine.lock().await;
-    match eng.run_cycle().await {
-        Ok(activities) => Ok(activities),
-        Err(e) => Err(format!("Cycle error: {}", e)),
-    }
+    eng.ensure_writable()?;
+    eng.create_price_alert(&market_id, condition, threshold, outcome).await.map_err(CommandError::from)
 }
 
-// Demo mode: simulates trading activity for UI testing
 #[tauri::command]
-async fn run_demo_cycle(engine: State<'_, EngineState>) -> Result<BotStats, String> {
+async fn delete_price_alert(engine: State<'_, EngineState>, alert_id: String) -> Result<(), CommandError> {
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    eng.delete_price_alert(&alert_id).map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn get_price_alerts(engine: State<'_, EngineState>) -> Result<Vec<PriceAlert>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_price_alerts())
+}
+
+/// Park the market's latest signal until its price, liquidity or date trigger fires,
+/// then trade it through the usual risk limits; works while the bot is stopped
+#[tauri::command]
+async fn park_conditional_order(
+    engine: State<'_, EngineState>,
+    market_id: String,
+    trigger: EntryTrigger,
+    size: Option<f64>,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<ConditionalOrder, CommandError> {
     let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    eng.park_conditional_order(&market_id, trigger, size, expires_at).await.map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn cancel_conditional_order(engine: State<'_, EngineState>, order_id: String) -> Result<(), CommandError> {
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    eng.cancel_conditional_order(&order_id).map_err(CommandError::from)
+}
+
+/// Build and save the report for the last complete day or week now, replacing any
+/// file the scheduler wrote for it
+#[tauri::command]
+async fn generate_report(
+    app: AppHandle,
+    engine: State<'_, EngineState>,
+    period: ReportPeriod,
+) -> Result<PerformanceReport, CommandError> {
+    let dir = app.path().app_data_dir().map_err(anyhow::Error::from)?.join("reports");
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    let (from, to) = period.previous(chrono::Utc::now());
+    let mut report = eng.build_report(period, from, to);
+    let path = reports::write(&dir, &report)?;
+    report.path = Some(path);
+    Ok(report)
+}
+
+/// Zip logs, redacted config, version info and recent cycle reports for a bug report;
+/// returns the archive's path
+#[tauri::command]
+async fn export_diagnostics_bundle(
+    app: AppHandle,
+    engine: State<'_, EngineState>,
+    cycles: Option<usize>,
+) -> Result<PathBuf, CommandError> {
+    let dir = app.path().app_data_dir().map_err(anyhow::Error::from)?.join("bundles");
+    let files = engine.lock().await.diagnostics_bundle(cycles.unwrap_or(bundle::DEFAULT_BUNDLE_CYCLES))?;
+    Ok(bundle::write(&dir, &files, chrono::Utc::now())?)
+}
+
+/// Write the bot's persistent state, without credentials, to a versioned archive for
+/// another machine or a backup; returns the archive's path
+#[tauri::command]
+async fn export_state(app: AppHandle, engine: State<'_, EngineState>) -> Result<PathBuf, CommandError> {
+    let dir = app.path().app_data_dir().map_err(anyhow::Error::from)?.join("backups");
+    let state = {
+        let eng = engine.lock().await;
+        eng.ensure_writable()?;
+        eng.export_state()
+    };
+    Ok(archive::write(&dir, &state)?)
+}
+
+/// Replace the bot's state with an archive written by `export_state`
+#[tauri::command]
+async fn import_state(engine: State<'_, EngineState>, path: PathBuf) -> Result<(), CommandError> {
+    let state = archive::read(&path)?;
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    eng.import_state(state).map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn get_conditional_orders(engine: State<'_, EngineState>) -> Result<Vec<ConditionalOrder>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_conditional_orders())
+}
+
+#[tauri::command]
+async fn get_prompt_packs(engine: State<'_, EngineState>) -> Result<Vec<PromptPack>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_prompt_packs())
+}
+
+/// Re-read the prompt-pack files in the app data dir after editing them
+#[tauri::command]
+async fn reload_prompt_packs(engine: State<'_, EngineState>) -> Result<Vec<PromptPack>, CommandError> {
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    eng.reload_prompt_packs().map_err(CommandError::from)
+}
+
+/// Calibration per category for each prompt pack and the generic prompt
+#[tauri::command]
+async fn get_pack_calibration(engine: State<'_, EngineState>) -> Result<Vec<PackCalibration>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_pack_calibration())
+}
+
+/// Composite scores of the last cycle's candidates, best first, with the ones traded marked
+#[tauri::command]
+async fn get_candidate_ranking(engine: State<'_, EngineState>) -> Result<Vec<CandidateScore>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_candidate_ranking())
+}
+
+/// Neg-risk baskets bought this session, with the conversion call for No sets
+#[tauri::command]
+async fn get_neg_risk_baskets(engine: State<'_, EngineState>) -> Result<Vec<NegRiskOpportunity>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_neg_risk_baskets())
+}
+
+#[tauri::command]
+async fn get_execution_report(
+    engine: State<'_, EngineState>,
+    order_id: String,
+) -> Result<ExecutionReport, CommandError> {
+    let eng = engine.lock().await;
+    eng.get_execution_report(&order_id)
+        .ok_or_else(|| CommandError::not_found(format!("No execution report for order {}", order_id)))
+}
+
+#[tauri::command]
+async fn get_execution_quality(engine: State<'_, EngineState>) -> Result<ExecutionQuality, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_execution_quality())
+}
+
+#[tauri::command]
+async fn get_order_transitions(
+    engine: State<'_, EngineState>,
+    order_id: Option<String>,
+) -> Result<Vec<OrderTransition>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_order_transitions(order_id.as_deref()))
+}
+
+/// Emergency flatten: stop the bot, cancel all open orders and close every position
+#[tauri::command]
+async fn flatten_all(engine: State<'_, EngineState>) -> Result<FlattenReport, CommandError> {
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    Ok(eng.flatten_all().await)
+}
+
+#[tauri::command]
+async fn get_resting_orders(engine: State<'_, EngineState>) -> Result<Vec<RestingOrder>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_resting_orders())
+}
+
+#[tauri::command]
+async fn get_thesis_history(
+    engine: State<'_, EngineState>,
+    market_id: Option<String>,
+) -> Result<Vec<ThesisReview>, CommandError> {
+    let eng = engine.lock().await;
+    Ok(eng.get_thesis_history(market_id.as_deref()))
+}
+
+#[tauri::command]
+async fn amend_order(
+    engine: State<'_, EngineState>,
+    order_id: String,
+    new_price: f64,
+    new_size: f64,
+) -> Result<Order, CommandError> {
+    let mut eng = engine.lock().await;
+    eng.ensure_writable()?;
+    eng.amend_order(&order_id, new_price, new_size)
+        .await
+        .map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn import_trade_history(engine: State<'_, EngineState>) -> Result<ImportSummary, CommandError> {
+    let mut eng = engine.try_lock().map_err(|_| CommandError::busy())?;
+    eng.ensure_writable()?;
+    eng.import_trade_history().await.map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn run_parameter_sweep(
+    engine: State<'_, EngineState>,
+    grid: Option<SweepGrid>,
+) -> Result<Vec<BacktestResult>, CommandError> {
+    let (signals, initial_balance, max_bet) = {
+        let mut eng = engine.lock().await;
+        eng.ensure_writable()?;
+        eng.refresh_signal_resolutions().await;
+        (eng.signals.clone(), eng.config.initial_balance, eng.config.max_bet_size)
+    };
+    let grid = grid.unwrap_or_default();
+    let parallelism = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(4);
+
+    // Backtests are CPU-bound; run them off the async runtime without holding the engine lock
+    tokio::task::spawn_blocking(move || {
+        backtest::run_sweep(&signals, &grid, initial_balance, max_bet, parallelism)
+    })
+    .await
+    .map_err(CommandError::internal)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn simulate_bankroll(
+    engine: State<'_, EngineState>,
+    params: Option<SimulationParams>,
+) -> Result<SimulationResult, CommandError> {
+    let (measured, balance, max_bet, masked) = {
+        let eng = engine.lock().await;
+        (eng.measured_performance(), eng.stats.total_equity, eng.config.max_bet_size, eng.guest.masks_balances())
+    };
+    let params = params.unwrap_or_default();
+
+    let result = tokio::task::spawn_blocking(move || simulation::simulate(&params, &measured, balance, max_bet))
+        .await
+        .map_err(CommandError::internal)??;
+    Ok(if masked { guest::mask_simulation(result, balance) } else { result })
+}
+
+/// Start paper-trading variants side by side on a shared market feed, replacing any running test
+#[tauri::command]
+async fn start_ab_test(
+    engine: State<'_, EngineState>,
+    ab_state: State<'_, AbState>,
+    variants: Vec<AbVariantSpec>,
+) -> Result<AbResults, CommandError> {
+    engine.lock().await.ensure_writable()?;
+
+    let test = AbTest::new(variants)?;
+    let started_at = test.started_at();
+    let results = test.results();
+    *ab_state.lock().await = Some(test);
+
+    tauri::async_runtime::spawn(ab::run(ab_state.inner().clone(), started_at));
+    Ok(results)
+}
+
+#[tauri::command]
+async fn stop_ab_test(engine: State<'_, EngineState>, ab_state: State<'_, AbState>) -> Result<AbResults, CommandError> {
+    engine.lock().await.ensure_writable()?;
+
+    let mut state = ab_state.lock().await;
+    let test = state.as_mut().ok_or_else(|| CommandError::not_found("No A/B test has been started"))?;
+    test.stop();
+    Ok(test.results())
+}
+
+#[tauri::command]
+async fn get_ab_results(ab_state: State<'_, AbState>) -> Result<AbResults, CommandError> {
+    let state = ab_state.lock().await;
+    state.as_ref().map(|t| t.results()).ok_or_else(|| CommandError::not_found("No A/B test has been started"))
+}
+
+// Demo mode: simulates trading activity for UI testing
+#[tauri::command]
+async fn run_demo_cycle(engine: State<'_, EngineState>) -> Result<BotStats, CommandError> {
+    let mut eng = engine.try_lock().map_err(|_| CommandError::busy())?;
+    eng.ensure_writable()?;
 
     if !eng.is_running {
         eng.is_running = true;
@@ -94,9 +647,10 @@ async fn run_demo_cycle(engine: State<'_, EngineState>) -> Result<BotStats, Stri
     // Simulate market scanning
     let scan_count = 200 + (eng.stats.cycle as u64 * 7) % 900;
     eng.stats.markets_scanned += scan_count;
-    eng.add_activity(
+    eng.add_activity_at(
         &format!("Scanning {} feeds...", scan_count),
         ActivityType::Info,
+        LogLevel::Debug,
     );
 
     // Simulate finding edges and placing trades
@@ -143,15 +697,7 @@ async fn run_demo_cycle(engine: State<'_, EngineState>) -> Result<BotStats, Stri
             -order_size * (1.0 - edge) * 0.6
         };
 
-        eng.stats.current_balance += pnl;
-        eng.stats.total_trades += 1;
-        if pnl > 0.0 {
-            eng.stats.wins += 1;
-            if pnl > eng.stats.best_trade { eng.stats.best_trade = pnl; }
-        } else {
-            eng.stats.losses += 1;
-            if pnl < eng.stats.worst_trade { eng.stats.worst_trade = pnl; }
-        }
+        eng.record_demo_trade(&format!("demo-{}", idx), fair_value, order_size, pnl);
 
         eng.add_activity(
             &format!(
@@ -169,15 +715,17 @@ async fn run_demo_cycle(engine: State<'_, EngineState>) -> Result<BotStats, Stri
     }
 
     // Evaluate remaining markets
-    eng.add_activity(
+    eng.add_activity_at(
         &format!("Evaluating {} markets...", 400 + (eng.stats.cycle as u64 % 600)),
         ActivityType::Info,
+        LogLevel::Debug,
     );
 
     // Monitoring orderbooks
-    eng.add_activity(
+    eng.add_activity_at(
         &format!("Monitoring {} orderbooks...", 200 + (eng.stats.cycle as u64 % 700)),
         ActivityType::Info,
+        LogLevel::Debug,
     );
 
     // API cost simulation
@@ -193,21 +741,14 @@ async fn run_demo_cycle(engine: State<'_, EngineState>) -> Result<BotStats, Stri
 
     // Update balance history
     eng.balance_history.push(BalancePoint {
-        timestamp: chrono::Utc::now().format("%H:%M:%S").to_string(),
+        timestamp: chrono::Utc::now(),
+        local_time: String::new(),
         balance: eng.stats.current_balance,
         label: format!("{}m", eng.balance_history.len() * 2),
     });
 
-    // Update derived stats
-    eng.stats.total_pnl = eng.stats.current_balance - eng.stats.initial_balance;
-    eng.stats.total_pnl_pct = format!(
-        "{}${:.1}k",
-        if eng.stats.total_pnl >= 0.0 { "+" } else { "" },
-        eng.stats.total_pnl / 1000.0
-    );
-
+    // Balance, PnL and trade counts come from the ledger; these are filled in for show
     if eng.stats.total_trades > 0 {
-        eng.stats.win_rate = (eng.stats.wins as f64 / eng.stats.total_trades as f64) * 100.0;
         eng.stats.avg_bet = eng.stats.current_balance / eng.stats.total_trades as f64 * 0.3;
     }
 
@@ -226,21 +767,116 @@ async fn run_demo_cycle(engine: State<'_, EngineState>) -> Result<BotStats, Stri
 #[cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 fn main() {
     let engine: EngineState = Arc::new(Mutex::new(TradingEngine::new()));
+    let ab_state: AbState = Arc::new(Mutex::new(None));
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(engine)
+        .manage(ab_state)
+        .setup(|app| {
+            let engine = app.state::<EngineState>().inner().clone();
+            let notifier = DesktopNotifier { app: app.handle().clone() };
+            let data_dir = app.path().app_data_dir().ok();
+            let history = data_dir.as_ref()
+                .ok_or_else(|| anyhow::anyhow!("No app data directory"))
+                .and_then(|dir| Database::open(dir.join("history")));
+            let prompt_packs = data_dir.as_ref()
+                .ok_or_else(|| anyhow::anyhow!("No app data directory"))
+                .and_then(|dir| PromptPacks::load(&dir.join("prompt_packs")));
+            tauri::async_runtime::block_on(async {
+                let mut eng = engine.lock().await;
+                eng.notifications.register(Box::new(notifier));
+                match history {
+                    Ok(db) => eng.attach_database(db),
+                    Err(e) => log::warn!("History database unavailable: {:#}", e),
+                }
+                match prompt_packs {
+                    Ok(packs) => eng.attach_prompt_packs(packs),
+                    Err(e) => log::warn!("Prompt packs unavailable, using built-in packs: {:#}", e),
+                }
+            });
+
+            // Price alerts and the stablecoin peg are watched whether or not the bot is trading
+            tauri::async_runtime::spawn(alerts::monitor(engine.clone()));
+            // Market-making quotes refresh on their own interval, between scan cycles
+            tauri::async_runtime::spawn(market_maker::quoter(engine.clone()));
+            // Daily and weekly reports are written once their period ends
+            if let Some(dir) = data_dir {
+                tauri::async_runtime::spawn(reports::scheduler(engine.clone(), dir.join("reports")));
+            }
+            // Scan cycles run in the background while the bot is started
+            tauri::async_runtime::spawn(supervisor::supervise(engine));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             get_stats,
+            get_stats_delta,
             get_activity_log,
+            get_activity_since,
+            get_full_activity_log,
             get_balance_history,
+            get_ledger,
+            record_capital_flow,
+            detect_capital_flows,
+            check_allowances,
+            set_allowances,
+            get_capital_flows,
+            query_history,
+            get_orders,
+            get_portfolio,
+            get_market_positions,
+            get_exposure_groups,
+            get_peg_status,
+            get_latest_commentary,
+            get_prompt_usage,
+            get_cache_tokens,
             save_config,
             get_config,
+            get_network,
             start_bot,
             stop_bot,
             get_bot_status,
+            get_health,
+            run_diagnostics,
+            benchmark_market_parsing,
+            get_guest_mode,
+            set_guest_mode,
+            get_locale,
+            set_locale,
             run_cycle,
             run_demo_cycle,
+            import_trade_history,
+            find_similar_markets,
+            get_execution_report,
+            get_execution_quality,
+            get_order_transitions,
+            get_resting_orders,
+            get_thesis_history,
+            flatten_all,
+            get_prediction_cache,
+            get_market_maker,
+            get_candidate_ranking,
+            get_prompt_packs,
+            reload_prompt_packs,
+            get_pack_calibration,
+            create_price_alert,
+            delete_price_alert,
+            get_price_alerts,
+            park_conditional_order,
+            cancel_conditional_order,
+            get_conditional_orders,
+            generate_report,
+            export_diagnostics_bundle,
+            export_state,
+            import_state,
+            get_neg_risk_baskets,
+            amend_order,
+            run_parameter_sweep,
+            simulate_bankroll,
+            start_ab_test,
+            stop_ab_test,
+            get_ab_results,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use super::config::ConfigError;
+use super::engine::TradingEngine;
+use super::models::*;
+use super::polymarket::PolymarketClient;
+
+/// One side of an A/B test: a named config run in paper mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbVariantSpec {
+    pub name: String,
+    pub config: BotConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbVariantResult {
+    pub name: String,
+    pub min_edge_threshold: f64,
+    pub claude_model: String,
+    pub stats: BotStats,
+    pub signals: u32,
+    pub orders: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbResults {
+    pub started_at: DateTime<Utc>,
+    pub cycles: u32,
+    pub is_running: bool,
+    pub variants: Vec<AbVariantResult>,
+    /// Variant with the highest total PnL so far, once any trade has settled
+    pub leader: Option<String>,
+}
+
+/// Several paper engines with different configs fed the same market scan each cycle
+pub struct AbTest {
+    feed: PolymarketClient,
+    variants: Vec<(String, TradingEngine)>,
+    started_at: DateTime<Utc>,
+    cycles: u32,
+    scan_interval_secs: u32,
+    pub is_running: bool,
+}
+
+impl AbTest {
+    pub fn new(specs: Vec<AbVariantSpec>) -> std::result::Result<Self, Vec<ConfigError>> {
+        let error = |field: &str, message: String| vec![ConfigError { field: field.to_string(), message }];
+        if specs.len() < 2 {
+            return Err(error("variants", "At least two variants are required".to_string()));
+        }
+        let mut names = HashSet::new();
+        if let Some(dup) = specs.iter().find(|s| !names.insert(s.name.as_str())) {
+            return Err(error("variants", format!("Duplicate variant name \"{}\"", dup.name)));
+        }
+
+        let feed = PolymarketClient::new(&specs[0].config)
+            .map_err(|e| error("proxy_url", e.to_string()))?;
+        let scan_interval_secs = specs.iter().map(|s| s.config.scan_interval_secs).max().unwrap_or(60);
+
+        let mut variants = Vec::with_capacity(specs.len());
+        for spec in specs {
+            // Paper trading only: orders are simulated and never watch-only
+            let config = BotConfig { watch_only: false, auto_trading: true, ..spec.config };
+            let mut engine = TradingEngine::new();
+            engine.configure(config).map_err(|errors| {
+                errors
+                    .into_iter()
+                    .map(|e| ConfigError { field: format!("{}.{}", spec.name, e.field), message: e.message })
+                    .collect::<Vec<_>>()
+            })?;
+            engine.start();
+            variants.push((spec.name, engine));
+        }
+
+        Ok(Self {
+            feed,
+            variants,
+            started_at: Utc::now(),
+            cycles: 0,
+            scan_interval_secs,
+            is_running: true,
+        })
+    }
+
+    /// Fetch markets once and run every variant against the same snapshot
+    pub async fn run_cycle(&mut self) -> Result<()> {
+        let markets = self.feed.get_markets(100, 0).await?;
+        for (_, engine) in &mut self.variants {
+            engine.run_cycle_on(markets.clone()).await?;
+        }
+        self.cycles += 1;
+        Ok(())
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    pub fn stop(&mut self) {
+        self.is_running = false;
+        for (_, engine) in &mut self.variants {
+            engine.stop();
+        }
+    }
+
+    pub fn results(&self) -> AbResults {
+        let variants: Vec<AbVariantResult> = self.variants
+            .iter()
+            .map(|(name, engine)| AbVariantResult {
+                name: name.clone(),
+                min_edge_threshold: engine.config.min_edge_threshold,
+                claude_model: engine.config.claude_model.clone(),
+                stats: engine.get_stats(),
+                signals: engine.signals.len() as u32,
+                orders: engine.orders.iter().filter(|o| o.parent_id.is_none()).count() as u32,
+            })
+            .collect();
+
+        let leader = variants
+            .iter()
+            .filter(|v| v.stats.total_trades > 0)
+            .max_by(|a, b| a.stats.total_pnl.total_cmp(&b.stats.total_pnl))
+            .map(|v| v.name.clone());
+
+        AbResults {
+            started_at: self.started_at,
+            cycles: self.cycles,
+            is_running: self.is_running,
+            variants,
+            leader,
+        }
+    }
+}
+
+/// Cycle the test started at `started_at` until it is stopped or replaced
+pub async fn run(test: Arc<Mutex<Option<AbTest>>>, started_at: DateTime<Utc>) {
+    loop {
+        let interval = {
+            let mut guard = test.lock().await;
+            let Some(ab) = guard.as_mut().filter(|t| t.is_running && t.started_at == started_at) else {
+                return;
+            };
+            if let Err(e) = ab.run_cycle().await {
+                log::warn!("A/B cycle failed: {}", e);
+            }
+            ab.scan_interval_secs.max(1)
+        };
+        tokio::time::sleep(Duration::from_secs(interval as u64)).await;
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use super::engine::TradingEngine;
+
+/// How often alert prices and conditional triggers are polled, whether or not the bot is trading
+pub const ALERT_CHECK_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertCondition {
+    /// Price at or above the threshold
+    Above,
+    /// Price at or below the threshold
+    Below,
+    /// Price moved from one side of the threshold to the other since the last check
+    Crosses,
+}
+
+impl AlertCondition {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            AlertCondition::Above => "≥",
+            AlertCondition::Below => "≤",
+            AlertCondition::Crosses => "↔",
+        }
+    }
+
+    /// Whether `price` meets the condition against `threshold`, given the price seen at the previous check
+    pub fn holds(self, price: f64, threshold: f64, last_price: Option<f64>) -> bool {
+        match self {
+            AlertCondition::Above => price >= threshold,
+            AlertCondition::Below => price <= threshold,
+            AlertCondition::Crosses => last_price.is_some_and(|last| {
+                (last < threshold && price >= threshold) || (last > threshold && price <= threshold)
+            }),
+        }
+    }
+}
+
+/// One-shot price alert on an outcome of a market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceAlert {
+    pub id: String,
+    pub market_id: String,
+    pub market_name: String,
+    pub outcome: String,
+    pub condition: AlertCondition,
+    pub threshold: f64,
+    pub created_at: DateTime<Utc>,
+    pub last_price: Option<f64>,
+    pub last_checked: Option<DateTime<Utc>>,
+    /// Set when the alert fires; triggered alerts are no longer checked
+    pub triggered_at: Option<DateTime<Utc>>,
+    pub triggered_price: Option<f64>,
+}
+
+impl PriceAlert {
+    pub fn is_active(&self) -> bool {
+        self.triggered_at.is_none()
+    }
+
+    /// Whether `price` satisfies the condition, given the price seen at the previous check
+    pub fn fires_at(&self, price: f64) -> bool {
+        self.condition.holds(price, self.threshold, self.last_price)
+    }
+}
+
+/// Poll alert prices and conditional order triggers every `ALERT_CHECK_SECS`, and the
+/// stablecoin peg at its own slower rate, for the lifetime of the app
+pub async fn monitor(engine: Arc<Mutex<TradingEngine>>) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(ALERT_CHECK_SECS)).await;
+        let mut eng = engine.lock().await;
+        eng.check_price_alerts().await;
+        eng.check_conditional_orders().await;
+        eng.check_peg().await;
+    }
+}
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::alerts::PriceAlert;
+use super::backtest::Signal;
+use super::batch::CachedPrediction;
+use super::conditional::ConditionalOrder;
+use super::db::StoredRow;
+use super::guest;
+use super::ledger::Ledger;
+use super::migrations;
+use super::models::*;
+
+/// Layout version written into every archive; see [`migrations::ARCHIVE_MIGRATIONS`]
+pub const STATE_VERSION: u32 = migrations::ARCHIVE_VERSION;
+
+/// The bot's persistent state, for moving it to another machine or restoring a backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateArchive {
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: DateTime<Utc>,
+    /// Credentials are blanked; see [`without_secrets`]
+    pub config: BotConfig,
+    pub stats: BotStats,
+    pub ledger: Ledger,
+    pub orders: Vec<Order>,
+    pub balance_history: Vec<BalancePoint>,
+    /// Latest prediction per market
+    pub predictions: Vec<CachedPrediction>,
+    /// Every recorded AI assessment; calibration and backtests are computed from these
+    pub signals: Vec<Signal>,
+    pub price_alerts: Vec<PriceAlert>,
+    pub conditional_orders: Vec<ConditionalOrder>,
+    /// History database rows: markets, predictions, orders, fills, resolutions, sessions and activity
+    pub history: Vec<StoredRow>,
+    /// Schema version of the `history` rows
+    #[serde(default)]
+    pub row_version: u32,
+}
+
+/// Config with every credential emptied, so an archive can be stored or shared safely
+pub fn without_secrets(config: &BotConfig) -> BotConfig {
+    let mut config = config.clone();
+    for secret in guest::secrets_mut(&mut config) {
+        secret.clear();
+    }
+    config
+}
+
+/// An imported config carrying the credentials already configured on this machine
+pub fn with_secrets_of(imported: BotConfig, current: &BotConfig) -> BotConfig {
+    let mut config = imported;
+    let mut current = current.clone();
+    for (secret, kept) in guest::secrets_mut(&mut config).into_iter().zip(guest::secrets_mut(&mut current)) {
+        *secret = std::mem::take(kept);
+    }
+    config
+}
+
+/// Write the archive as `state-<timestamp>.json` in `dir`; returns its path
+pub fn write(dir: &Path, archive: &StateArchive) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("Creating {}", dir.display()))?;
+    let path = dir.join(format!("state-{}.json", archive.exported_at.format("%Y%m%d-%H%M%S")));
+    fs::write(&path, serde_json::to_vec_pretty(archive)?).with_context(|| format!("Writing {}", path.display()))?;
+    Ok(path)
+}
+
+/// Read an archive, upgrading one written by an older version before the rest is parsed
+pub fn read(path: &Path) -> Result<StateArchive> {
+    let contents = fs::read(path).with_context(|| format!("Reading {}", path.display()))?;
+    let mut value: serde_json::Value = serde_json::from_slice(&contents).context("Not a JSON file")?;
+    let Some(version) = value.get("version").and_then(|v| v.as_u64()) else {
+        bail!("{} is not a bot state archive", path.display());
+    };
+    migrations::upgrade_archive(version.try_into().unwrap_or(u32::MAX), &mut value)?;
+    let mut archive: StateArchive = serde_json::from_value(value).context("Invalid state archive")?;
+    // Rows carried in the archive were written at the archive's row version or older
+    for stored in &mut archive.history {
+        migrations::upgrade_row(stored.table, archive.row_version, &mut stored.row)?;
+    }
+    archive.row_version = migrations::ROW_VERSION;
+    Ok(archive)
+}
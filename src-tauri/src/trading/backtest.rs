@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::models::*;
+
+/// Upper bound on grid size so a sweep cannot stall the app
+pub const MAX_SWEEP_COMBINATIONS: usize = 1000;
+
+/// An AI assessment recorded during a scan, replayed by the backtester once its market settles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Signal {
+    pub timestamp: DateTime<Utc>,
+    pub market_id: String,
+    pub market_name: String,
+    pub category: Option<String>,
+    pub outcome: String,
+    /// Market price of `outcome` when the signal was recorded
+    pub price: f64,
+    pub fair_price: f64,
+    pub edge: f64,
+    pub confidence: f64,
+    pub liquidity: f64,
+    pub resolution: Option<MarketResolution>,
+    #[serde(default)]
+    pub prompt_pack: Option<String>,
+}
+
+impl Signal {
+    /// Value of one share of `outcome` once settled; invalid markets refund the price
+    pub fn payout(&self) -> Option<f64> {
+        let resolution = self.resolution.as_ref()?;
+        match resolution.status {
+            // Invalid markets refund the stake
+            ResolutionStatus::Invalid => Some(self.price),
+            _ => resolution.payout_for(&self.outcome),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestParams {
+    pub min_edge: f64,
+    /// Fraction of the full Kelly stake to bet
+    pub kelly_fraction: f64,
+    pub min_confidence: f64,
+    pub min_liquidity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestResult {
+    pub params: BacktestParams,
+    pub trades: u32,
+    pub wins: u32,
+    pub final_balance: f64,
+    pub total_return: f64,
+    pub max_drawdown: f64,
+    /// Mean over standard deviation of per-trade returns; 0 with fewer than two trades
+    pub risk_adjusted_return: f64,
+}
+
+/// Values to grid over; every combination is backtested
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SweepGrid {
+    pub edge_thresholds: Vec<f64>,
+    pub kelly_fractions: Vec<f64>,
+    pub min_confidences: Vec<f64>,
+    pub min_liquidities: Vec<f64>,
+}
+
+impl Default for SweepGrid {
+    fn default() -> Self {
+        Self {
+            edge_thresholds: vec![0.05, 0.08, 0.10, 0.15, 0.20],
+            kelly_fractions: vec![0.1, 0.25, 0.5],
+            min_confidences: vec![0.0, 0.6, 0.75],
+            min_liquidities: vec![0.0, 1000.0, 10000.0],
+        }
+    }
+}
+
+impl SweepGrid {
+    pub fn combinations(&self) -> Vec<BacktestParams> {
+        let mut combos = Vec::new();
+        for &min_edge in &self.edge_thresholds {
+            for &kelly_fraction in &self.kelly_fractions {
+                for &min_confidence in &self.min_confidences {
+                    for &min_liquidity in &self.min_liquidities {
+                        combos.push(BacktestParams { min_edge, kelly_fraction, min_confidence, min_liquidity });
+                    }
+                }
+            }
+        }
+        combos
+    }
+}
+
+/// Replay settled signals in time order, taking at most one position per market
+pub fn run(signals: &[Signal], params: &BacktestParams, initial_balance: f64, max_bet: f64) -> BacktestResult {
+    let mut ordered: Vec<&Signal> = signals.iter().filter(|s| s.payout().is_some()).collect();
+    ordered.sort_by_key(|s| s.timestamp);
+
+    let mut balance = initial_balance;
+    let mut peak = initial_balance;
+    let mut max_drawdown: f64 = 0.0;
+    let mut returns = Vec::new();
+    let mut wins = 0;
+    let mut traded = HashSet::new();
+
+    for signal in ordered {
+        if signal.edge < params.min_edge
+            || signal.confidence < params.min_confidence
+            || signal.liquidity < params.min_liquidity
+            || signal.price <= 0.0
+            || signal.price >= 1.0
+            || traded.contains(&signal.market_id)
+        {
+            continue;
+        }
+
+        let full_kelly = (signal.edge / (1.0 - signal.price)).clamp(0.0, 1.0);
+        let stake = (balance * full_kelly * params.kelly_fraction).min(max_bet).min(balance);
+        if stake < 1.0 {
+            continue;
+        }
+        traded.insert(signal.market_id.clone());
+
+        let payout = signal.payout().unwrap_or(0.0);
+        let pnl = stake / signal.price * payout - stake;
+        returns.push(pnl / balance);
+        if pnl > 0.0 {
+            wins += 1;
+        }
+
+        balance += pnl;
+        peak = peak.max(balance);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((peak - balance) / peak);
+        }
+    }
+
+    BacktestResult {
+        params: params.clone(),
+        trades: returns.len() as u32,
+        wins,
+        final_balance: balance,
+        total_return: if initial_balance > 0.0 { balance / initial_balance - 1.0 } else { 0.0 },
+        max_drawdown,
+        risk_adjusted_return: sharpe(&returns),
+    }
+}
+
+/// Backtest every grid combination on up to `parallelism` threads, best risk-adjusted return first
+pub fn run_sweep(
+    signals: &[Signal],
+    grid: &SweepGrid,
+    initial_balance: f64,
+    max_bet: f64,
+    parallelism: usize,
+) -> Result<Vec<BacktestResult>> {
+    let combos = grid.combinations();
+    if combos.is_empty() {
+        bail!("Sweep grid is empty");
+    }
+    if combos.len() > MAX_SWEEP_COMBINATIONS {
+        bail!("Sweep grid has {} combinations, over the limit of {}", combos.len(), MAX_SWEEP_COMBINATIONS);
+    }
+
+    let chunk_size = combos.len().div_ceil(parallelism.max(1));
+    let mut results: Vec<BacktestResult> = std::thread::scope(|scope| {
+        let handles: Vec<_> = combos
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk.iter().map(|p| run(signals, p, initial_balance, max_bet)).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap_or_default()).collect()
+    });
+
+    results.sort_by(|a, b| {
+        b.risk_adjusted_return
+            .total_cmp(&a.risk_adjusted_return)
+            .then(b.total_return.total_cmp(&a.total_return))
+    });
+    Ok(results)
+}
+
+fn sharpe(returns: &[f64]) -> f64 {
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let std = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    if std > 0.0 { mean / std } else { 0.0 }
+}
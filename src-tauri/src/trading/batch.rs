@@ -0,0 +1,66 @@
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use super::models::*;
+use super::time;
+
+/// Requests per submitted batch
+pub const MAX_BATCH_REQUESTS: usize = 100;
+/// Batches take up to a day; markets resolving sooner than this are left to the live path
+const URGENT_RESOLUTION_HOURS: i64 = 48;
+/// The API expires batches unfinished after a day; one still pending past this is abandoned
+const BATCH_EXPIRY_HOURS: i64 = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PredictionSource {
+    Live,
+    Batch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedPrediction {
+    pub prediction: AIPrediction,
+    pub analyzed_at: DateTime<Utc>,
+    pub source: PredictionSource,
+}
+
+/// A submitted batch awaiting results, with the markets behind each custom id
+#[derive(Debug, Clone)]
+pub struct PendingBatch {
+    pub id: String,
+    pub submitted_at: DateTime<Utc>,
+    pub markets: HashMap<String, Market>,
+}
+
+impl PendingBatch {
+    pub fn expired(&self, now: DateTime<Utc>) -> bool {
+        now - self.submitted_at > Duration::hours(BATCH_EXPIRY_HOURS)
+    }
+}
+
+/// Markets resolving within the urgency window can't wait for a batch
+pub fn is_urgent(market: &Market, now: DateTime<Utc>) -> bool {
+    market.end_date
+        .as_deref()
+        .and_then(time::parse_timestamp)
+        .is_some_and(|end| end - now < Duration::hours(URGENT_RESOLUTION_HOURS))
+}
+
+/// Markets to defer to the next batch: not analyzed live this cycle, not urgent, and
+/// missing from the cache or older than `max_age_hours` there
+pub fn select_deferred<'m>(
+    markets: &'m [Market],
+    analyzed_live: &HashSet<&str>,
+    cache: &HashMap<String, CachedPrediction>,
+    max_age_hours: u32,
+    now: DateTime<Utc>,
+) -> Vec<&'m Market> {
+    let stale_before = now - Duration::hours(max_age_hours as i64);
+    markets
+        .iter()
+        .filter(|m| !analyzed_live.contains(m.id.as_str()))
+        .filter(|m| !is_urgent(m, now))
+        .filter(|m| cache.get(&m.id).is_none_or(|c| c.analyzed_at < stale_before))
+        .take(MAX_BATCH_REQUESTS)
+        .collect()
+}
@@ -0,0 +1,105 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+
+/// Cycle reports included when the caller does not ask for a number
+pub const DEFAULT_BUNDLE_CYCLES: usize = 10;
+
+/// One file inside a diagnostics bundle
+pub struct BundleFile {
+    pub name: String,
+    pub contents: Vec<u8>,
+}
+
+impl BundleFile {
+    pub fn json(name: &str, value: &impl serde::Serialize) -> Result<Self> {
+        Ok(Self { name: name.to_string(), contents: serde_json::to_vec_pretty(value)? })
+    }
+}
+
+/// Write the files as `diagnostics-<timestamp>.zip` in `dir`; returns the archive's path
+pub fn write(dir: &Path, files: &[BundleFile], at: DateTime<Utc>) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("Creating {}", dir.display()))?;
+    let path = dir.join(format!("diagnostics-{}.zip", at.format("%Y%m%d-%H%M%S")));
+    fs::write(&path, zip(files, at)).with_context(|| format!("Writing {}", path.display()))?;
+    Ok(path)
+}
+
+/// Uncompressed ("stored") zip archive: the bundle is small text, and every OS opens it
+/// without extra tooling
+pub fn zip(files: &[BundleFile], at: DateTime<Utc>) -> Vec<u8> {
+    let (time, date) = dos_datetime(at);
+    let mut out = Vec::new();
+    let mut directory = Vec::new();
+
+    for file in files {
+        let offset = out.len() as u32;
+        let crc = crc32(&file.contents);
+        let size = file.contents.len() as u32;
+        let name = file.name.as_bytes();
+
+        // Local file header: version 2.0, UTF-8 names, no compression
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        for field in [20u16, 0x0800, 0, time, date] {
+            out.extend_from_slice(&field.to_le_bytes());
+        }
+        for field in [crc, size, size] {
+            out.extend_from_slice(&field.to_le_bytes());
+        }
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(name);
+        out.extend_from_slice(&file.contents);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        for field in [20u16, 20, 0x0800, 0, time, date] {
+            directory.extend_from_slice(&field.to_le_bytes());
+        }
+        for field in [crc, size, size] {
+            directory.extend_from_slice(&field.to_le_bytes());
+        }
+        // Name length, extra, comment, disk, internal attributes
+        for field in [name.len() as u16, 0, 0, 0, 0] {
+            directory.extend_from_slice(&field.to_le_bytes());
+        }
+        // External attributes, local header offset
+        for field in [0u32, offset] {
+            directory.extend_from_slice(&field.to_le_bytes());
+        }
+        directory.extend_from_slice(name);
+    }
+
+    let directory_offset = out.len() as u32;
+    let directory_size = directory.len() as u32;
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    let count = files.len() as u16;
+    for field in [0u16, 0, count, count] {
+        out.extend_from_slice(&field.to_le_bytes());
+    }
+    for field in [directory_size, directory_offset] {
+        out.extend_from_slice(&field.to_le_bytes());
+    }
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out
+}
+
+/// MS-DOS time and date fields; zip timestamps have two-second resolution and start in 1980
+fn dos_datetime(at: DateTime<Utc>) -> (u16, u16) {
+    let time = ((at.hour() << 11) | (at.minute() << 5) | (at.second() / 2)) as u16;
+    let date = ((((at.year() - 1980).max(0) as u32) << 9) | (at.month() << 5) | at.day()) as u16;
+    (time, date)
+}
+
+/// CRC-32 (IEEE), as zip requires
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
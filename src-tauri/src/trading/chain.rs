@@ -0,0 +1,169 @@
+use anyhow::{bail, Context, Result};
+use reqwest::Client;
+use serde_json::Value;
+use super::http;
+use super::models::BotConfig;
+use super::network::{self, Network};
+
+/// keccak256("Transfer(address,address,uint256)")
+const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+/// USDC and USDC.e on Polygon use 6 decimals
+pub const COLLATERAL_DECIMALS: u32 = 6;
+
+/// An ERC-20 transfer of the collateral token involving the watched wallet
+#[derive(Debug, Clone)]
+pub struct TokenTransfer {
+    pub tx_hash: String,
+    pub log_index: u64,
+    pub block_number: u64,
+    pub from: String,
+    pub to: String,
+    pub amount: f64,
+    pub block_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Minimal Polygon JSON-RPC client
+pub struct ChainClient {
+    client: Client,
+    network: Network,
+}
+
+impl ChainClient {
+    pub fn new(config: &BotConfig) -> Result<Self> {
+        Ok(Self {
+            client: http::build_client(30, &config.proxy_url)?,
+            network: network::resolve(config),
+        })
+    }
+
+    /// Client for the wallet RPC that signs `eth_sendTransaction`, when one is configured
+    pub fn signer(config: &BotConfig) -> Result<Option<Self>> {
+        if config.signer_rpc_url.trim().is_empty() {
+            return Ok(None);
+        }
+        let mut network = network::resolve(config);
+        network.rpc_url = config.signer_rpc_url.trim().to_string();
+        Ok(Some(Self { client: http::build_client(120, &config.proxy_url)?, network }))
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let body = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let resp: Value = self.client.post(&self.network.rpc_url).json(&body).send().await?.json().await?;
+        if let Some(err) = resp.get("error") {
+            bail!("RPC {} failed: {}", method, err);
+        }
+        resp.get("result").cloned().context("RPC response missing result")
+    }
+
+    pub async fn block_number(&self) -> Result<u64> {
+        let result = self.call("eth_blockNumber", serde_json::json!([])).await?;
+        parse_hex_u64(result.as_str().unwrap_or_default()).context("Invalid block number")
+    }
+
+    /// Read-only contract call at the latest block; returns the raw 0x-prefixed result
+    pub async fn eth_call(&self, to: &str, data: &str) -> Result<String> {
+        let result = self.call("eth_call", serde_json::json!([{ "to": to, "data": data }, "latest"])).await?;
+        result.as_str().map(|s| s.to_string()).context("Invalid eth_call result")
+    }
+
+    pub async fn estimate_gas(&self, from: &str, to: &str, data: &str) -> Result<u64> {
+        let result = self.call("eth_estimateGas", serde_json::json!([{ "from": from, "to": to, "data": data }])).await?;
+        parse_hex_u64(result.as_str().unwrap_or_default()).context("Invalid gas estimate")
+    }
+
+    pub async fn gas_price(&self) -> Result<u128> {
+        let result = self.call("eth_gasPrice", serde_json::json!([])).await?;
+        u128::from_str_radix(result.as_str().unwrap_or_default().trim_start_matches("0x"), 16).context("Invalid gas price")
+    }
+
+    /// Next nonce for `address`, counting transactions still in the mempool
+    pub async fn transaction_count(&self, address: &str) -> Result<u64> {
+        let result = self.call("eth_getTransactionCount", serde_json::json!([address, "pending"])).await?;
+        parse_hex_u64(result.as_str().unwrap_or_default()).context("Invalid nonce")
+    }
+
+    /// Have the RPC's wallet sign and broadcast a transaction; returns its hash
+    pub async fn send_transaction(&self, tx: Value) -> Result<String> {
+        let result = self.call("eth_sendTransaction", serde_json::json!([tx])).await?;
+        result.as_str().map(|s| s.to_string()).context("Invalid transaction hash")
+    }
+
+    /// Have the RPC's wallet sign EIP-712 typed data as `address`; returns the signature
+    pub async fn sign_typed_data(&self, address: &str, typed_data: &Value) -> Result<String> {
+        let result = self.call("eth_signTypedData_v4", serde_json::json!([address, typed_data.to_string()])).await?;
+        result.as_str().map(|s| s.to_string()).context("Invalid signature")
+    }
+
+    /// Collateral transfers into or out of `wallet` within the block range (inclusive)
+    pub async fn collateral_transfers(&self, wallet: &str, from_block: u64, to_block: u64) -> Result<Vec<TokenTransfer>> {
+        let wallet_topic = address_topic(wallet);
+        let mut transfers = Vec::new();
+
+        // Topic positions: [signature, from, to]
+        for topics in [
+            serde_json::json!([TRANSFER_TOPIC, Value::Null, wallet_topic]),
+            serde_json::json!([TRANSFER_TOPIC, wallet_topic, Value::Null]),
+        ] {
+            let filter = serde_json::json!([{
+                "address": self.network.collateral_token,
+                "fromBlock": format!("0x{:x}", from_block),
+                "toBlock": format!("0x{:x}", to_block),
+                "topics": topics,
+            }]);
+            let logs = self.call("eth_getLogs", filter).await?;
+            for log in logs.as_array().into_iter().flatten() {
+                if let Some(transfer) = parse_transfer(log) {
+                    transfers.push(transfer);
+                }
+            }
+        }
+
+        transfers.sort_by_key(|t| (t.block_number, t.log_index));
+        Ok(transfers)
+    }
+}
+
+fn parse_transfer(log: &Value) -> Option<TokenTransfer> {
+    let topics = log.get("topics")?.as_array()?;
+    let from = topic_address(topics.get(1)?.as_str()?);
+    let to = topic_address(topics.get(2)?.as_str()?);
+    let raw = u128::from_str_radix(log.get("data")?.as_str()?.trim_start_matches("0x"), 16).ok()?;
+
+    Some(TokenTransfer {
+        tx_hash: log.get("transactionHash")?.as_str()?.to_string(),
+        log_index: parse_hex_u64(log.get("logIndex")?.as_str()?)?,
+        block_number: parse_hex_u64(log.get("blockNumber")?.as_str()?)?,
+        from,
+        to,
+        amount: raw as f64 / 10f64.powi(COLLATERAL_DECIMALS as i32),
+        // Not part of the spec, but returned by most Polygon providers
+        block_timestamp: log.get("blockTimestamp")
+            .and_then(|t| t.as_str())
+            .and_then(parse_hex_u64)
+            .and_then(|t| chrono::DateTime::from_timestamp(t as i64, 0)),
+    })
+}
+
+pub fn parse_hex_u64(raw: &str) -> Option<u64> {
+    u64::from_str_radix(raw.trim_start_matches("0x"), 16).ok()
+}
+
+/// Approximate value of a hex word too wide for an integer type, e.g. a uint256 allowance
+pub fn parse_hex_f64(raw: &str) -> Option<f64> {
+    let hex = raw.trim_start_matches("0x");
+    hex.chars().try_fold(0.0, |acc, c| c.to_digit(16).map(|d| acc * 16.0 + d as f64))
+}
+
+/// Left-pad an address to a 32-byte log topic
+pub fn address_topic(address: &str) -> String {
+    format!("0x{:0>64}", address.trim_start_matches("0x").to_lowercase())
+}
+
+fn topic_address(topic: &str) -> String {
+    let hex = topic.trim_start_matches("0x");
+    format!("0x{}", &hex[hex.len().saturating_sub(40)..])
+}
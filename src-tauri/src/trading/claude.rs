@@ -1,89 +1,309 @@
-use anyhow::Result;
+use std::collections::HashMap;
+use anyhow::{Context, Result};
 use reqwest::Client;
+use chrono::Utc;
+use serde_json::Value;
 use super::models::*;
+use super::features::MarketFeatures;
+use super::prompt::{self, PromptBuilder};
+use super::prompt_packs::PromptPacks;
+use super::http;
+use super::ratelimit::RateLimitStatus;
 
-const CLAUDE_API_URL: &str = "https://api.anthropic.com/v1/messages";
+pub const CLAUDE_API_URL: &str = "https://api.anthropic.com/v1/messages";
+/// Output budget of one market analysis
+pub const ANALYSIS_MAX_TOKENS: u32 = 1024;
+/// Shortest prefix Anthropic will cache; shorter blocks marked cacheable are billed normally
+pub const MIN_CACHEABLE_TOKENS: u32 = 1024;
+
+/// Processing state of a submitted message batch
+#[derive(Debug, Clone)]
+pub struct BatchStatus {
+    pub ended: bool,
+    /// Set once the batch has ended
+    pub results_url: Option<String>,
+}
 
 pub struct ClaudeClient {
     client: Client,
+    api_url: String,
     api_key: String,
     model: String,
     total_input_tokens: u64,
     total_output_tokens: u64,
+    total_cache_write_tokens: u64,
+    total_cache_read_tokens: u64,
+    /// Batch API usage, billed at half the live rate
+    total_batch_input_tokens: u64,
+    total_batch_output_tokens: u64,
+    total_batch_cache_write_tokens: u64,
+    total_batch_cache_read_tokens: u64,
+    prompt_builder: PromptBuilder,
+    prompt_usage: Vec<PromptUsage>,
+    rate_limits: RateLimitStatus,
 }
 
 impl ClaudeClient {
-    pub fn new(api_key: &str, model: &str) -> Self {
-        Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(60))
-                .build()
-                .expect("Failed to build HTTP client"),
-            api_key: api_key.to_string(),
-            model: model.to_string(),
+    pub fn new(config: &BotConfig) -> Result<Self> {
+        Ok(Self {
+            client: http::build_client(60, &config.proxy_url)?,
+            api_url: http::base_url(&config.claude_api_url, CLAUDE_API_URL),
+            api_key: config.claude_api_key.clone(),
+            model: config.claude_model.clone(),
             total_input_tokens: 0,
             total_output_tokens: 0,
-        }
+            total_cache_write_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_batch_input_tokens: 0,
+            total_batch_output_tokens: 0,
+            total_batch_cache_write_tokens: 0,
+            total_batch_cache_read_tokens: 0,
+            prompt_builder: PromptBuilder::new(config.max_prompt_tokens, config.prompt_caching),
+            prompt_usage: Vec::new(),
+            rate_limits: RateLimitStatus::default(),
+        })
+    }
+
+    pub fn set_prompt_packs(&mut self, packs: PromptPacks) {
+        self.prompt_builder.set_packs(packs);
+    }
+
+    /// Quota left as of the latest response
+    pub fn rate_limits(&self) -> &RateLimitStatus {
+        &self.rate_limits
     }
 
     /// Analyze a market using Claude AI to determine edge & probability
-    pub async fn analyze_market(&mut self, market: &Market) -> Result<AIPrediction> {
-        let system_prompt = r#"You are an expert prediction market analyst and quantitative trader. 
-Your task is to analyze prediction markets and determine:
-1. The TRUE probability of each outcome based on available information
-2. Whether there is an EDGE (difference between fair price and market price)
-3. Your confidence level in the prediction
-4. Recommended position size based on Kelly Criterion
-
-Respond in strict JSON format:
-{
-    "predicted_outcome": "Yes" or "No",
-    "fair_price": 0.XX,
-    "confidence": 0.XX,
-    "edge": 0.XX,
-    "reasoning": "Brief explanation",
-    "recommended_size_pct": 0.XX
-}
+    pub async fn analyze_market(
+        &mut self,
+        market: &Market,
+        features: Option<&MarketFeatures>,
+    ) -> Result<AIPrediction> {
+        let prompt = self.prompt_builder.build_market_prompt(market, features)?;
+        let (text, usage) = self.send(prompt.cached_prefix, prompt.system, prompt.user, ANALYSIS_MAX_TOKENS).await?;
 
-Only recommend trades where edge > 0.05 (5%). Be conservative with sizing.
-Consider base rates, current events, and market efficiency."#;
+        self.prompt_usage.push(PromptUsage {
+            market_id: market.id.clone(),
+            market_name: market.question.clone(),
+            estimated_tokens: prompt.estimated_tokens,
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_write_tokens: usage.cache_creation_input_tokens,
+            cache_read_tokens: usage.cache_read_input_tokens,
+            truncated: prompt.truncated,
+            timestamp: Utc::now(),
+        });
 
-        let market_info = format!(
-            "Market: {}\nOutcomes: {:?}\nCurrent Prices: {:?}\nVolume: ${:.0}\nLiquidity: ${:.0}\nEnd Date: {}",
-            market.question,
-            market.outcomes,
-            market.outcome_prices,
-            market.volume,
-            market.liquidity,
-            market.end_date.as_deref().unwrap_or("Not set")
-        );
+        // Keep last 500 entries
+        if self.prompt_usage.len() > 500 {
+            self.prompt_usage = self.prompt_usage.split_off(self.prompt_usage.len() - 500);
+        }
+
+        // Parse JSON from Claude response
+        let prediction = self.parse_prediction(&text, market)?;
+        Ok(prediction)
+    }
+
+    /// Ask Claude for a narrative analyst report on the current portfolio
+    pub async fn generate_commentary(
+        &mut self,
+        positions: &[Position],
+        open_orders: &[Order],
+        markets: &[Market],
+    ) -> Result<PortfolioCommentary> {
+        let user = prompt::build_commentary_prompt(positions, open_orders, markets);
+        let (text, usage) = self.send(None, prompt::COMMENTARY_SYSTEM_PROMPT.to_string(), user, 1500).await?;
+
+        Ok(PortfolioCommentary {
+            timestamp: Utc::now(),
+            commentary: text.trim().to_string(),
+            positions_count: (positions.len() + open_orders.len()) as u32,
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+        })
+    }
 
-        let request = ClaudeRequest {
+    /// Submit market analyses to the Message Batches API, returning the batch id.
+    /// Each entry is (custom id, market, features); results come back keyed by custom id.
+    pub async fn submit_batch(&mut self, entries: &[(String, &Market, Option<&MarketFeatures>)]) -> Result<String> {
+        let mut requests = Vec::with_capacity(entries.len());
+        for (custom_id, market, features) in entries {
+            let prompt = self.prompt_builder.build_market_prompt(market, *features)?;
+            requests.push(serde_json::json!({
+                "custom_id": custom_id,
+                "params": self.build_request(prompt.cached_prefix, prompt.system, prompt.user, 1024),
+            }));
+        }
+
+        let resp = self.client
+            .post(self.batches_url())
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&serde_json::json!({ "requests": requests }))
+            .send()
+            .await?;
+        let resp = http::check(resp).await?;
+
+        let body: Value = resp.json().await?;
+
+        body.get("id")
+            .and_then(|id| id.as_str())
+            .map(|id| id.to_string())
+            .with_context(|| format!("Batch submission failed: {}", body))
+    }
+
+    pub async fn batch_status(&self, batch_id: &str) -> Result<BatchStatus> {
+        let resp = self.client
+            .get(format!("{}/{}", self.batches_url(), batch_id))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .send()
+            .await?;
+        let resp = http::check(resp).await?;
+        let body: Value = resp.json().await?;
+
+        Ok(BatchStatus {
+            ended: body.get("processing_status").and_then(|s| s.as_str()) == Some("ended"),
+            results_url: body.get("results_url").and_then(|u| u.as_str()).map(|u| u.to_string()),
+        })
+    }
+
+    /// Download a finished batch and parse the successful analyses. Errored or expired
+    /// requests are skipped; `markets` maps custom ids back to the analyzed markets.
+    pub async fn batch_results(
+        &mut self,
+        results_url: &str,
+        markets: &HashMap<String, Market>,
+    ) -> Result<(Vec<(String, AIPrediction)>, u32)> {
+        let resp = self.client
+            .get(results_url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .send()
+            .await?;
+        let body = http::check(resp).await?.text().await?;
+
+        // Entries that failed or cannot be parsed are counted and skipped, not fatal to the batch
+        let mut predictions = Vec::new();
+        let mut skipped = 0;
+        for line in body.lines().filter(|l| !l.trim().is_empty()) {
+            let Ok(entry) = serde_json::from_str::<Value>(line) else {
+                skipped += 1;
+                continue;
+            };
+            let Some(custom_id) = entry.get("custom_id").and_then(|id| id.as_str()) else {
+                skipped += 1;
+                continue;
+            };
+            let Some(market) = markets.get(custom_id) else { continue };
+            let result = &entry["result"];
+            if result.get("type").and_then(|t| t.as_str()) != Some("succeeded") {
+                skipped += 1;
+                continue;
+            }
+            let Ok(message) = serde_json::from_value::<ClaudeResponse>(result["message"].clone()) else {
+                skipped += 1;
+                continue;
+            };
+
+            if let Some(usage) = &message.usage {
+                self.total_batch_input_tokens += usage.input_tokens as u64;
+                self.total_batch_output_tokens += usage.output_tokens as u64;
+                self.total_batch_cache_write_tokens += usage.cache_creation_input_tokens as u64;
+                self.total_batch_cache_read_tokens += usage.cache_read_input_tokens as u64;
+            }
+            let text = message.content.first().and_then(|c| c.text.clone()).unwrap_or_default();
+            match self.parse_prediction(&text, market) {
+                Ok(prediction) => predictions.push((custom_id.to_string(), prediction)),
+                Err(_) => skipped += 1,
+            }
+        }
+        Ok((predictions, skipped))
+    }
+
+    /// Validate the API key by listing models, which spends no tokens
+    pub async fn check_credentials(&self) -> Result<()> {
+        let url = match self.api_url.trim_end_matches('/').strip_suffix("/messages") {
+            Some(base) => format!("{}/models", base),
+            None => format!("{}/models", self.api_url.trim_end_matches('/')),
+        };
+        let resp = self.client
+            .get(url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .send()
+            .await?;
+        http::check(resp).await?;
+        Ok(())
+    }
+
+    fn batches_url(&self) -> String {
+        format!("{}/batches", self.api_url.trim_end_matches('/'))
+    }
+
+    /// The cached prefix goes first and is marked as the cacheable part when it is long enough;
+    /// the rest of the system prompt follows it uncached
+    fn build_request(&self, cached_prefix: Option<String>, system: String, user: String, max_tokens: u32) -> ClaudeRequest {
+        let mut blocks = Vec::new();
+        if let Some(prefix) = cached_prefix {
+            blocks.push(ClaudeSystemBlock {
+                block_type: "text".to_string(),
+                cache_control: (prompt::estimate_tokens(&prefix) >= MIN_CACHEABLE_TOKENS).then(CacheControl::ephemeral),
+                text: prefix,
+            });
+        }
+        // Empty text blocks are rejected
+        if !system.is_empty() {
+            blocks.push(ClaudeSystemBlock { block_type: "text".to_string(), cache_control: None, text: system });
+        }
+        ClaudeRequest {
             model: self.model.clone(),
-            max_tokens: 1024,
+            max_tokens,
             messages: vec![ClaudeMessage {
                 role: "user".to_string(),
-                content: format!("Analyze this prediction market and provide your assessment:\n\n{}", market_info),
+                content: user,
             }],
-            system: Some(system_prompt.to_string()),
-        };
+            system: Some(blocks),
+        }
+    }
+
+    /// Send a single-turn request and return the response text with its token usage
+    async fn send(
+        &mut self,
+        cached_prefix: Option<String>,
+        system: String,
+        user: String,
+        max_tokens: u32,
+    ) -> Result<(String, ClaudeUsage)> {
+        let request = self.build_request(cached_prefix, system, user, max_tokens);
 
         let resp = self.client
-            .post(CLAUDE_API_URL)
+            .post(&self.api_url)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
             .header("content-type", "application/json")
             .json(&request)
             .send()
             .await?;
+        // Throttled responses carry the headers too, with `retry-after`
+        self.rate_limits.update(resp.headers(), Utc::now());
+        let resp = http::check(resp).await?;
+
 
         let claude_resp: ClaudeResponse = resp.json().await?;
 
         // Track token usage
-        if let Some(usage) = &claude_resp.usage {
-            self.total_input_tokens += usage.input_tokens as u64;
-            self.total_output_tokens += usage.output_tokens as u64;
-        }
+        let usage = claude_resp.usage.clone().unwrap_or(ClaudeUsage {
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        });
+        self.total_input_tokens += usage.input_tokens as u64;
+        self.total_output_tokens += usage.output_tokens as u64;
+        self.total_cache_write_tokens += usage.cache_creation_input_tokens as u64;
+        self.total_cache_read_tokens += usage.cache_read_input_tokens as u64;
 
         let text = claude_resp.content
             .first()
@@ -91,9 +311,7 @@ Consider base rates, current events, and market efficiency."#;
             .map(|t| t.to_string())
             .unwrap_or_default();
 
-        // Parse JSON from Claude response
-        let prediction = self.parse_prediction(&text, market)?;
-        Ok(prediction)
+        Ok((text, usage))
     }
 
     fn parse_prediction(&self, text: &str, market: &Market) -> Result<AIPrediction> {
@@ -149,15 +367,35 @@ Consider base rates, current events, and market efficiency."#;
                 .to_string(),
             recommended_size: recommended_size_pct,
             fair_price,
+            prompt_pack: self.prompt_builder.pack_for(market),
         })
     }
 
     /// Estimate API cost based on token usage
     pub fn estimate_cost(&self) -> f64 {
-        // Claude Sonnet pricing: $3/M input, $15/M output
+        // Claude Sonnet pricing: $3/M input, $15/M output,
+        // cache writes at 1.25x and cache reads at 0.1x the input rate
         let input_cost = (self.total_input_tokens as f64 / 1_000_000.0) * 3.0;
         let output_cost = (self.total_output_tokens as f64 / 1_000_000.0) * 15.0;
-        input_cost + output_cost
+        let cache_write_cost = (self.total_cache_write_tokens as f64 / 1_000_000.0) * 3.75;
+        let cache_read_cost = (self.total_cache_read_tokens as f64 / 1_000_000.0) * 0.30;
+        let batch_cost = (self.total_batch_input_tokens as f64 / 1_000_000.0) * 1.5
+            + (self.total_batch_output_tokens as f64 / 1_000_000.0) * 7.5
+            + (self.total_batch_cache_write_tokens as f64 / 1_000_000.0) * 1.875
+            + (self.total_batch_cache_read_tokens as f64 / 1_000_000.0) * 0.15;
+        input_cost + output_cost + cache_write_cost + cache_read_cost + batch_cost
+    }
+
+    /// Total (cache write, cache read) input tokens, live and batch
+    pub fn get_cache_tokens(&self) -> (u64, u64) {
+        (
+            self.total_cache_write_tokens + self.total_batch_cache_write_tokens,
+            self.total_cache_read_tokens + self.total_batch_cache_read_tokens,
+        )
+    }
+
+    pub fn get_prompt_usage(&self) -> Vec<PromptUsage> {
+        self.prompt_usage.clone()
     }
 
     pub fn get_total_tokens(&self) -> (u64, u64) {
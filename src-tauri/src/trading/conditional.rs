@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::alerts::AlertCondition;
+use super::models::*;
+
+/// What has to happen before a parked signal is traded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum EntryTrigger {
+    /// The signal's outcome price meets `condition` against `threshold`, as for price alerts
+    Price { condition: AlertCondition, threshold: f64 },
+    /// Market liquidity reaches at least this many dollars
+    Liquidity { min_liquidity: f64 },
+    /// Not before this time
+    Date { after: DateTime<Utc> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConditionalStatus {
+    Parked,
+    /// Sent through the risk and execution gates; `order_id` is set when they produced an order
+    Triggered { order_id: Option<String> },
+    Expired,
+    Cancelled,
+}
+
+/// An analyzed signal parked until its trigger fires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalOrder {
+    pub id: String,
+    pub market_id: String,
+    pub market_name: String,
+    pub outcome: String,
+    pub trigger: EntryTrigger,
+    /// Stake in dollars; `None` sizes from the signal when it triggers
+    pub size: Option<f64>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub status: ConditionalStatus,
+    pub last_price: Option<f64>,
+    pub last_checked: Option<DateTime<Utc>>,
+    /// When the order left `Parked`
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl ConditionalOrder {
+    pub fn is_parked(&self) -> bool {
+        self.status == ConditionalStatus::Parked
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|t| now >= t)
+    }
+
+    /// Whether the trigger holds for the market as fetched now
+    pub fn is_triggered(&self, market: &Market, price: Option<f64>, now: DateTime<Utc>) -> bool {
+        match self.trigger {
+            EntryTrigger::Price { condition, threshold } => {
+                price.is_some_and(|p| condition.holds(p, threshold, self.last_price))
+            }
+            EntryTrigger::Liquidity { min_liquidity } => market.liquidity >= min_liquidity,
+            EntryTrigger::Date { after } => now >= after,
+        }
+    }
+
+    pub fn close(&mut self, status: ConditionalStatus, now: DateTime<Utc>) {
+        self.status = status;
+        self.closed_at = Some(now);
+    }
+}
+
+/// Short human form of the trigger for activity messages
+pub fn describe_trigger(trigger: &EntryTrigger) -> String {
+    match trigger {
+        EntryTrigger::Price { condition, threshold } => format!("price {} {:.3}", condition.symbol(), threshold),
+        EntryTrigger::Liquidity { min_liquidity } => format!("liquidity ≥ ${:.0}", min_liquidity),
+        EntryTrigger::Date { after } => format!("after {}", after.format("%Y-%m-%d %H:%M UTC")),
+    }
+}
@@ -0,0 +1,278 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use super::embeddings::EmbeddingProvider;
+use super::models::BotConfig;
+
+/// A validation failure tied to a specific config field, so the UI can highlight the input
+#[derive(Debug, Clone, Serialize, Deserialize, thiserror::Error)]
+#[error("{field}: {message}")]
+pub struct ConfigError {
+    pub field: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into() }
+    }
+}
+
+/// Minimum scan interval when orders are placed automatically
+pub const MIN_LIVE_SCAN_INTERVAL_SECS: u32 = 10;
+
+/// Validate a config, collecting every field-level error rather than stopping at the first
+pub fn validate(config: &BotConfig) -> Result<(), Vec<ConfigError>> {
+    let mut errors = Vec::new();
+
+    if config.initial_balance.is_nan() || config.initial_balance <= 0.0 {
+        errors.push(ConfigError::new("initial_balance", "Must be greater than 0"));
+    }
+    if config.max_bet_size.is_nan() || config.max_bet_size <= 0.0 {
+        errors.push(ConfigError::new("max_bet_size", "Must be greater than 0"));
+    } else if config.max_bet_size > config.initial_balance {
+        errors.push(ConfigError::new("max_bet_size", "Cannot exceed the initial balance"));
+    }
+    if !(0.0..=1.0).contains(&config.min_edge_threshold) {
+        errors.push(ConfigError::new("min_edge_threshold", "Must be between 0 and 1"));
+    }
+    if config.max_concurrent_orders == 0 {
+        errors.push(ConfigError::new("max_concurrent_orders", "Must be at least 1"));
+    }
+    if config.scan_interval_secs == 0 {
+        errors.push(ConfigError::new("scan_interval_secs", "Must be at least 1 second"));
+    } else if config.auto_trading && config.scan_interval_secs < MIN_LIVE_SCAN_INTERVAL_SECS {
+        errors.push(ConfigError::new(
+            "scan_interval_secs",
+            format!("Must be at least {}s when auto trading", MIN_LIVE_SCAN_INTERVAL_SECS),
+        ));
+    }
+    if config.max_prompt_tokens < 200 {
+        errors.push(ConfigError::new("max_prompt_tokens", "Must be at least 200"));
+    }
+    if config.sampling.markets_per_cycle == 0 {
+        errors.push(ConfigError::new("sampling.markets_per_cycle", "Must be at least 1"));
+    }
+    if config.max_order_slices == 0 {
+        errors.push(ConfigError::new("max_order_slices", "Must be at least 1"));
+    }
+    if config.paper_fills.queue_model && config.paper_fills.max_rest_secs == 0 {
+        errors.push(ConfigError::new("paper_fills.max_rest_secs", "Must be positive while the queue model is on"));
+    }
+
+    if config.auto_trading && config.claude_api_key.trim().is_empty() {
+        errors.push(ConfigError::new("claude_api_key", "Required for auto trading"));
+    }
+    if config.embedding_provider == EmbeddingProvider::Voyage && config.voyage_api_key.trim().is_empty() {
+        errors.push(ConfigError::new("voyage_api_key", "Required for Voyage embeddings"));
+    }
+    if !(0.0..=1.0).contains(&config.duplicate_similarity) {
+        errors.push(ConfigError::new("duplicate_similarity", "Must be between 0 and 1"));
+    }
+    if !(-1.0..=1.0).contains(&config.max_offer_pressure) {
+        errors.push(ConfigError::new("max_offer_pressure", "Must be between -1 and 1"));
+    }
+    if !(0.0..=1.0).contains(&config.min_entry_price) {
+        errors.push(ConfigError::new("min_entry_price", "Must be between 0 and 1"));
+    }
+    if !(0.0..=1.0).contains(&config.max_entry_price) {
+        errors.push(ConfigError::new("max_entry_price", "Must be between 0 and 1"));
+    } else if config.max_entry_price <= config.min_entry_price {
+        errors.push(ConfigError::new("max_entry_price", "Must be above min_entry_price"));
+    }
+    for (i, rule) in config.side_guardrails.iter().enumerate() {
+        if rule.category.trim().is_empty() {
+            errors.push(ConfigError::new(&format!("side_guardrails.{}.category", i), "Required; use * for every category"));
+        }
+        if !(0.0..=1.0).contains(&rule.min_price) || !(0.0..=1.0).contains(&rule.max_price) {
+            errors.push(ConfigError::new(&format!("side_guardrails.{}", i), "Prices must be between 0 and 1"));
+        } else if rule.max_price <= rule.min_price {
+            errors.push(ConfigError::new(&format!("side_guardrails.{}.max_price", i), "Must be above min_price"));
+        }
+    }
+    if config.circuit_breaker.failure_threshold > 0 && config.circuit_breaker.cooldown_secs == 0 {
+        errors.push(ConfigError::new("circuit_breaker.cooldown_secs", "Must be positive while the breaker is enabled"));
+    }
+    if !(config.currency.depeg_threshold > 0.0 && config.currency.depeg_threshold < 1.0) {
+        errors.push(ConfigError::new("currency.depeg_threshold", "Must be between 0 and 1"));
+    }
+    if config.batch_max_age_hours == 0 {
+        errors.push(ConfigError::new("batch_max_age_hours", "Must be at least 1"));
+    }
+    if config.watch_only && config.wallet_address.trim().is_empty() {
+        errors.push(ConfigError::new("wallet_address", "Required in watch-only mode"));
+    }
+
+    if !config.proxy_url.trim().is_empty() && reqwest::Proxy::all(config.proxy_url.trim()).is_err() {
+        errors.push(ConfigError::new("proxy_url", "Not a valid proxy URL"));
+    }
+    for (field, url) in [
+        ("polymarket_clob_url", &config.polymarket_clob_url),
+        ("polymarket_gamma_url", &config.polymarket_gamma_url),
+        ("polymarket_data_url", &config.polymarket_data_url),
+        ("claude_api_url", &config.claude_api_url),
+        ("rpc_url", &config.rpc_url),
+        ("signer_rpc_url", &config.signer_rpc_url),
+    ] {
+        if !url.trim().is_empty() && reqwest::Url::parse(url.trim()).is_err() {
+            errors.push(ConfigError::new(field, "Not a valid URL"));
+        }
+    }
+
+    if config.display_timezone.trim().parse::<chrono_tz::Tz>().is_err() {
+        errors.push(ConfigError::new("display_timezone", "Unknown timezone"));
+    }
+
+    for (category, cap) in &config.risk.category_caps {
+        if !(0.0..=1.0).contains(cap) {
+            errors.push(ConfigError::new(
+                &format!("risk.category_caps.{}", category),
+                "Must be between 0 and 1",
+            ));
+        }
+    }
+    if config.risk.cash_reserve < 0.0 {
+        errors.push(ConfigError::new("risk.cash_reserve", "Cannot be negative"));
+    }
+    if config.risk.max_market_exposure < 0.0 {
+        errors.push(ConfigError::new("risk.max_market_exposure", "Cannot be negative"));
+    }
+    if !(0.0..1.0).contains(&config.risk.min_add_on_improvement) {
+        errors.push(ConfigError::new("risk.min_add_on_improvement", "Must be between 0 and 1"));
+    }
+    if !(0.0..=1.0).contains(&config.risk.max_worst_case_loss) {
+        errors.push(ConfigError::new("risk.max_worst_case_loss", "Must be between 0 and 1"));
+    }
+    if !(0.0..=1.0).contains(&config.risk.max_correlated_exposure) {
+        errors.push(ConfigError::new("risk.max_correlated_exposure", "Must be between 0 and 1"));
+    }
+    if !(0.0..=1.0).contains(&config.risk.correlation_similarity) {
+        errors.push(ConfigError::new("risk.correlation_similarity", "Must be between 0 and 1"));
+    }
+
+    if config.exits.window_hours == 0 {
+        errors.push(ConfigError::new("exits.window_hours", "Must be at least 1"));
+    }
+    if !(config.exits.reduce_fraction > 0.0 && config.exits.reduce_fraction < 1.0) {
+        errors.push(ConfigError::new("exits.reduce_fraction", "Must be between 0 and 1"));
+    }
+
+    let reunderwrite = &config.reunderwrite;
+    if reunderwrite.interval_hours == 0 {
+        errors.push(ConfigError::new("reunderwrite.interval_hours", "Must be at least 1"));
+    }
+    if reunderwrite.max_per_cycle == 0 {
+        errors.push(ConfigError::new("reunderwrite.max_per_cycle", "Must be at least 1"));
+    }
+    if !(reunderwrite.exit_drift > 0.0 && reunderwrite.exit_drift < 1.0) {
+        errors.push(ConfigError::new("reunderwrite.exit_drift", "Must be between 0 and 1"));
+    }
+    if !(reunderwrite.add_edge > 0.0 && reunderwrite.add_edge < 1.0) {
+        errors.push(ConfigError::new("reunderwrite.add_edge", "Must be between 0 and 1"));
+    }
+
+    let scoring = &config.scoring;
+    let weights = [
+        scoring.edge_weight,
+        scoring.confidence_weight,
+        scoring.liquidity_weight,
+        scoring.time_weight,
+        scoring.calibration_weight,
+    ];
+    if weights.iter().any(|w| *w < 0.0) || scoring.total_weight() <= 0.0 {
+        errors.push(ConfigError::new("scoring", "Weights must be non-negative and not all zero"));
+    }
+    if scoring.edge_scale <= 0.0 {
+        errors.push(ConfigError::new("scoring.edge_scale", "Must be positive"));
+    }
+    if scoring.top_k == 0 {
+        errors.push(ConfigError::new("scoring.top_k", "Must be at least 1"));
+    }
+
+    if config.neg_risk.max_stake <= 0.0 {
+        errors.push(ConfigError::new("neg_risk.max_stake", "Must be positive"));
+    }
+    if config.neg_risk.min_edge < 0.0 {
+        errors.push(ConfigError::new("neg_risk.min_edge", "Must not be negative"));
+    }
+
+    let mm = &config.market_maker;
+    if !(mm.half_spread > 0.0 && mm.half_spread < 0.5) {
+        errors.push(ConfigError::new("market_maker.half_spread", "Must be between 0 and 0.5"));
+    }
+    if mm.quote_size <= 0.0 {
+        errors.push(ConfigError::new("market_maker.quote_size", "Must be positive"));
+    }
+    if mm.max_inventory < mm.quote_size {
+        errors.push(ConfigError::new("market_maker.max_inventory", "Must be at least the quote size"));
+    }
+    if mm.refresh_secs == 0 {
+        errors.push(ConfigError::new("market_maker.refresh_secs", "Must be at least 1"));
+    }
+    if mm.enabled && mm.markets.is_empty() {
+        errors.push(ConfigError::new("market_maker.markets", "Choose at least one market to quote"));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parse a number typed in any common locale: "0.3", "0,3", "1,234.5", "1.234,5", "1 234,5".
+/// A lone comma before exactly three digits groups thousands ("10,000"), unless the whole
+/// part is zero ("0,125").
+pub fn parse_number(raw: &str) -> Option<f64> {
+    let cleaned: String = raw.trim().chars().filter(|c| !c.is_whitespace() && *c != '_').collect();
+    if cleaned.is_empty() {
+        return None;
+    }
+
+    // Whichever separator appears last is the decimal one; the other groups thousands
+    let normalized = match (cleaned.rfind('.'), cleaned.rfind(',')) {
+        (Some(dot), Some(comma)) if comma > dot => cleaned.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => cleaned.replace(',', ""),
+        (None, Some(comma)) if cleaned.matches(',').count() == 1 => {
+            let (whole, fraction) = (&cleaned[..comma], &cleaned[comma + 1..]);
+            let grouped = fraction.len() == 3 && !matches!(whole.trim_start_matches(['-', '+']), "" | "0");
+            if grouped { cleaned.replace(',', "") } else { cleaned.replace(',', ".") }
+        }
+        (None, Some(_)) => cleaned.replace(',', ""),
+        _ => cleaned,
+    };
+    normalized.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(f64),
+    String(String),
+}
+
+/// Serde deserializer for `f64` config fields that also accepts locale-formatted strings
+pub fn lenient_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match NumberOrString::deserialize(deserializer)? {
+        NumberOrString::Number(n) => Ok(n),
+        NumberOrString::String(s) => parse_number(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid number: {}", s))),
+    }
+}
+
+/// Serde deserializer for `u32` config fields that also accepts numeric strings
+pub fn lenient_u32<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let n = match NumberOrString::deserialize(deserializer)? {
+        NumberOrString::Number(n) => n,
+        NumberOrString::String(s) => parse_number(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid number: {}", s)))?,
+    };
+    if n < 0.0 || n.fract() != 0.0 || n > u32::MAX as f64 {
+        return Err(serde::de::Error::custom(format!("expected a whole number, got {}", n)));
+    }
+    Ok(n as u32)
+}
@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::config;
+use super::http;
+use super::models::*;
+
+pub const PRICE_API_URL: &str = "https://api.coingecko.com/api/v3";
+/// Minimum time between peg checks; the alert monitor ticks more often than this
+pub const PEG_CHECK_SECS: i64 = 300;
+/// Age past which the last peg status, and any pause it set, rests on an outdated price
+pub const PEG_STALE_SECS: i64 = 1800;
+
+/// Collateral every balance, stake and PnL is denominated in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Currency {
+    /// USDC on Polygon, Polymarket's collateral
+    #[default]
+    Usdc,
+}
+
+impl Currency {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Currency::Usdc => "USDC",
+        }
+    }
+
+    /// Id of the token on the price API
+    fn price_id(self) -> &'static str {
+        match self {
+            Currency::Usdc => "usd-coin",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurrencyConfig {
+    pub currency: Currency,
+    /// Deviation from $1 beyond which the stablecoin counts as depegged
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub depeg_threshold: f64,
+    /// Stop opening positions while depegged
+    pub pause_on_depeg: bool,
+    /// Overrides the price API base URL
+    pub price_api_url: String,
+}
+
+impl Default for CurrencyConfig {
+    fn default() -> Self {
+        Self {
+            currency: Currency::Usdc,
+            depeg_threshold: 0.01,
+            pause_on_depeg: false,
+            price_api_url: String::new(),
+        }
+    }
+}
+
+/// Latest USD price of the currency of record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PegStatus {
+    pub currency: Currency,
+    pub usd_price: f64,
+    /// `usd_price - 1`
+    pub deviation: f64,
+    pub depegged: bool,
+    /// New entries are blocked until the peg recovers
+    pub paused: bool,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl PegStatus {
+    pub fn new(config: &CurrencyConfig, usd_price: f64, checked_at: DateTime<Utc>) -> Self {
+        let deviation = usd_price - 1.0;
+        let depegged = deviation.abs() > config.depeg_threshold;
+        Self {
+            currency: config.currency,
+            usd_price,
+            deviation,
+            depegged,
+            paused: depegged && config.pause_on_depeg,
+            checked_at,
+        }
+    }
+
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        (now - self.checked_at).num_seconds() > PEG_STALE_SECS
+    }
+}
+
+pub struct PriceFeed {
+    client: Client,
+    api_url: String,
+}
+
+impl PriceFeed {
+    pub fn new(config: &BotConfig) -> Result<Self> {
+        Ok(Self {
+            client: http::build_client(15, &config.proxy_url)?,
+            api_url: http::base_url(&config.currency.price_api_url, PRICE_API_URL),
+        })
+    }
+
+    pub async fn usd_price(&self, currency: Currency) -> Result<f64> {
+        let resp = self.client
+            .get(format!("{}/simple/price", self.api_url))
+            .query(&[("ids", currency.price_id()), ("vs_currencies", "usd")])
+            .send()
+            .await?;
+        let body: Value = http::check(resp).await?.json().await?;
+        body.get(currency.price_id())
+            .and_then(|p| p.get("usd"))
+            .and_then(|p| p.as_f64())
+            .with_context(|| format!("No USD price for {} in response", currency.symbol()))
+    }
+}
@@ -0,0 +1,467 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::batch::CachedPrediction;
+use super::ledger::LedgerEvent;
+use super::migrations::{self, ROW_VERSION};
+use super::time;
+
+/// Rewrite a table once it holds this many superseded records and more dead than live ones
+const COMPACT_MIN_DEAD: usize = 1000;
+/// Activity is the only table that grows without bound; the oldest entries are dropped
+const MAX_ACTIVITY_ROWS: usize = 20_000;
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Table {
+    Markets,
+    Predictions,
+    Orders,
+    Fills,
+    Resolutions,
+    Sessions,
+    Activity,
+    /// Re-underwriting reviews of held positions
+    Reviews,
+    /// Ledger events kept in neither fills nor resolutions: order placements and capital flows
+    Ledger,
+}
+
+impl Table {
+    pub const ALL: [Table; 9] = [
+        Table::Markets,
+        Table::Predictions,
+        Table::Orders,
+        Table::Fills,
+        Table::Resolutions,
+        Table::Sessions,
+        Table::Activity,
+        Table::Reviews,
+        Table::Ledger,
+    ];
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Table::Markets => "markets.jsonl",
+            Table::Predictions => "predictions.jsonl",
+            Table::Orders => "orders.jsonl",
+            Table::Fills => "fills.jsonl",
+            Table::Resolutions => "resolutions.jsonl",
+            Table::Sessions => "sessions.jsonl",
+            Table::Activity => "activity.jsonl",
+            Table::Reviews => "reviews.jsonl",
+            Table::Ledger => "ledger.jsonl",
+        }
+    }
+
+    /// Row field behind date filters and the default sort; markets fall back to write time
+    fn time_field(&self) -> Option<&'static str> {
+        match self {
+            Table::Markets => None,
+            Table::Predictions => Some("analyzed_at"),
+            Table::Orders => Some("created_at"),
+            Table::Fills | Table::Resolutions | Table::Activity | Table::Ledger => Some("timestamp"),
+            Table::Sessions => Some("started_at"),
+            Table::Reviews => Some("reviewed_at"),
+        }
+    }
+}
+
+/// One stored version of a row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Record {
+    key: String,
+    written_at: DateTime<Utc>,
+    /// Schema version of `row`; records written before versioning have none and read as 0
+    #[serde(default)]
+    version: u32,
+    row: Value,
+}
+
+impl Record {
+    fn time(&self, table: Table) -> DateTime<Utc> {
+        table.time_field()
+            .and_then(|field| self.row.get(field))
+            .and_then(|v| v.as_str())
+            .and_then(time::parse_timestamp)
+            .unwrap_or(self.written_at)
+    }
+}
+
+#[derive(Debug, Default)]
+struct TableData {
+    records: Vec<Record>,
+    index: HashMap<String, usize>,
+    /// Superseded records still in the file
+    dead: usize,
+}
+
+/// Embedded history store: one append-only JSON-lines file per table under `dir`,
+/// loaded into memory on open. The last record written for a key wins.
+#[derive(Debug)]
+pub struct Database {
+    dir: PathBuf,
+    tables: HashMap<Table, TableData>,
+}
+
+impl Database {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).with_context(|| format!("Cannot create {}", dir.display()))?;
+
+        let mut tables = HashMap::new();
+        let mut migrated = Vec::new();
+        for table in Table::ALL {
+            let path = dir.join(table.file_name());
+            let mut data = TableData::default();
+            let mut oldest = ROW_VERSION;
+            if path.exists() {
+                let contents = fs::read(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+                // After the last newline is either a line a crash cut short or a whole record
+                // whose newline was never written
+                let complete = contents.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+                let (lines, tail) = contents.split_at(complete);
+                let mut tail_kept = false;
+                for (line, is_tail) in lines.split(|&b| b == b'\n').map(|l| (l, false)).chain([(tail, true)]) {
+                    // Unreadable lines are skipped rather than failing the open
+                    let Ok(mut record) = serde_json::from_str::<Record>(&String::from_utf8_lossy(line)) else { continue };
+                    // A row that cannot be upgraded fails the open, leaving the file untouched
+                    migrations::upgrade_row(table, record.version, &mut record.row)?;
+                    oldest = oldest.min(record.version);
+                    record.version = ROW_VERSION;
+                    data.insert(record);
+                    tail_kept |= is_tail;
+                }
+                // Left unterminated, the tail would run into the next append
+                if !tail.is_empty() {
+                    let mut file = OpenOptions::new().append(true).open(&path)
+                        .with_context(|| format!("Cannot repair {}", path.display()))?;
+                    if tail_kept {
+                        writeln!(file)?;
+                    } else {
+                        file.set_len(complete as u64)?;
+                    }
+                }
+            }
+            tables.insert(table, data);
+            if oldest < ROW_VERSION {
+                migrated.push((table, oldest));
+            }
+        }
+
+        let mut db = Self { dir, tables };
+        // Persist upgrades, keeping each original file until the user removes it
+        for (table, oldest) in migrated {
+            let path = db.dir.join(table.file_name());
+            let backup = db.dir.join(format!("{}.v{}.bak", table.file_name(), oldest));
+            fs::copy(&path, &backup).with_context(|| format!("Cannot back up {}", path.display()))?;
+            db.compact(table)?;
+        }
+        Ok(db)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Create and remove a probe file in the database directory
+    pub fn check_writable(&self) -> Result<()> {
+        let probe = self.dir.join(".write-probe");
+        fs::write(&probe, b"ok").with_context(|| format!("Cannot write to {}", self.dir.display()))?;
+        fs::remove_file(&probe)?;
+        Ok(())
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.tables.values().map(|t| t.index.len()).sum()
+    }
+
+    /// Insert or replace the row stored under `key`. Returns false without writing
+    /// when the stored row is already identical.
+    pub fn upsert<T: Serialize>(&mut self, table: Table, key: &str, row: &T) -> Result<bool> {
+        let row = serde_json::to_value(row)?;
+        let data = self.tables.entry(table).or_default();
+        if data.index.get(key).is_some_and(|&i| data.records[i].row == row) {
+            return Ok(false);
+        }
+
+        let record = Record { key: key.to_string(), written_at: Utc::now(), version: ROW_VERSION, row };
+        let path = self.dir.join(table.file_name());
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)
+            .with_context(|| format!("Cannot open {}", path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        data.insert(record);
+
+        let superseded = data.dead >= COMPACT_MIN_DEAD && data.dead > data.index.len();
+        let overgrown = table == Table::Activity && data.index.len() > MAX_ACTIVITY_ROWS + COMPACT_MIN_DEAD;
+        if superseded || overgrown {
+            self.compact(table)?;
+        }
+        Ok(true)
+    }
+
+    /// Every live row of every table, oldest write first within each table
+    pub fn export_rows(&self) -> Vec<StoredRow> {
+        let mut rows = Vec::new();
+        for table in Table::ALL {
+            rows.extend(self.live(table).into_iter().map(|r| StoredRow { table, key: r.key.clone(), row: r.row.clone() }));
+        }
+        rows
+    }
+
+    /// Current rows of a table, oldest written first
+    pub fn rows(&self, table: Table) -> Vec<&Value> {
+        self.live(table).into_iter().map(|r| &r.row).collect()
+    }
+
+    fn live(&self, table: Table) -> Vec<&Record> {
+        let Some(data) = self.tables.get(&table) else { return Vec::new() };
+        let mut live: Vec<&Record> = data.index.values().map(|&i| &data.records[i]).collect();
+        live.sort_by_key(|r| r.written_at);
+        live
+    }
+
+    /// Rewrite a table's file with only its live rows
+    fn compact(&mut self, table: Table) -> Result<()> {
+        let data = self.tables.entry(table).or_default();
+        let mut live: Vec<Record> = data.index.values().map(|&i| data.records[i].clone()).collect();
+        live.sort_by_key(|r| r.written_at);
+        if table == Table::Activity && live.len() > MAX_ACTIVITY_ROWS {
+            live.drain(..live.len() - MAX_ACTIVITY_ROWS);
+        }
+
+        let path = self.dir.join(table.file_name());
+        let tmp = path.with_extension("jsonl.tmp");
+        {
+            let mut out = BufWriter::new(File::create(&tmp)?);
+            for record in &live {
+                writeln!(out, "{}", serde_json::to_string(record)?)?;
+            }
+            out.flush()?;
+        }
+        fs::rename(&tmp, &path)?;
+
+        let mut compacted = TableData::default();
+        for record in live {
+            compacted.insert(record);
+        }
+        *data = compacted;
+        Ok(())
+    }
+
+    pub fn query(&self, query: &HistoryQuery) -> HistoryPage {
+        let table = query.table;
+        let mut rows: Vec<&Record> = self.tables
+            .get(&table)
+            .map(|data| data.index.values().map(|&i| &data.records[i]).collect())
+            .unwrap_or_default();
+        rows.retain(|r| query.filter.matches(table, r));
+
+        let descending = query.sort.descending;
+        match query.sort.field.as_deref() {
+            Some(field) if Some(field) != table.time_field() => rows.sort_by(|a, b| {
+                let ord = compare_values(a.row.get(field), b.row.get(field));
+                if descending { ord.reverse() } else { ord }
+            }),
+            _ => rows.sort_by(|a, b| {
+                let ord = a.time(table).cmp(&b.time(table));
+                if descending { ord.reverse() } else { ord }
+            }),
+        }
+
+        let size = query.page.size.clamp(1, MAX_PAGE_SIZE);
+        let total = rows.len();
+        HistoryPage {
+            table,
+            total,
+            page: query.page.number,
+            page_size: size,
+            rows: rows
+                .into_iter()
+                .skip(query.page.number as usize * size as usize)
+                .take(size as usize)
+                .map(|r| r.row.clone())
+                .collect(),
+        }
+    }
+}
+
+impl TableData {
+    fn insert(&mut self, record: Record) {
+        if let Some(&i) = self.index.get(&record.key) {
+            self.records[i] = record;
+            self.dead += 1;
+        } else {
+            self.index.insert(record.key.clone(), self.records.len());
+            self.records.push(record);
+        }
+    }
+}
+
+/// Numbers compare numerically, strings as timestamps when both parse; missing values sort last
+fn compare_values(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a, b) {
+        (Some(Value::Number(a)), Some(Value::Number(b))) => {
+            a.as_f64().unwrap_or(0.0).total_cmp(&b.as_f64().unwrap_or(0.0))
+        }
+        (Some(Value::String(a)), Some(Value::String(b))) => {
+            match (time::parse_timestamp(a), time::parse_timestamp(b)) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                _ => a.cmp(b),
+            }
+        }
+        (Some(Value::Bool(a)), Some(Value::Bool(b))) => a.cmp(b),
+        (None | Some(Value::Null), None | Some(Value::Null)) => Ordering::Equal,
+        (None | Some(Value::Null), _) => Ordering::Greater,
+        (_, None | Some(Value::Null)) => Ordering::Less,
+        _ => Ordering::Equal,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    /// `market_id` of the row, or `id` for the markets table
+    pub market_id: Option<String>,
+    /// Exact matches on top-level row fields, e.g. {"status": "Resolved"}
+    pub fields: BTreeMap<String, Value>,
+    /// Case-insensitive text anywhere in the row
+    pub search: Option<String>,
+    #[serde(deserialize_with = "time::deserialize_option")]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(deserialize_with = "time::deserialize_option")]
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl HistoryFilter {
+    fn matches(&self, table: Table, record: &Record) -> bool {
+        let row = &record.row;
+        if let Some(ref market_id) = self.market_id {
+            let key = if table == Table::Markets { "id" } else { "market_id" };
+            if row.get(key).and_then(|v| v.as_str()) != Some(market_id.as_str()) {
+                return false;
+            }
+        }
+        if !self.fields.iter().all(|(field, value)| row.get(field) == Some(value)) {
+            return false;
+        }
+        let at = record.time(table);
+        if self.from.is_some_and(|from| at < from) || self.to.is_some_and(|to| at > to) {
+            return false;
+        }
+        match self.search.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => row.to_string().to_lowercase().contains(&text.to_lowercase()),
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySort {
+    /// Top-level row field; defaults to the table's timestamp
+    pub field: Option<String>,
+    pub descending: bool,
+}
+
+impl Default for HistorySort {
+    fn default() -> Self {
+        Self { field: None, descending: true }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryPageRequest {
+    /// Zero-based
+    pub number: u32,
+    pub size: u32,
+}
+
+impl Default for HistoryPageRequest {
+    fn default() -> Self {
+        Self { number: 0, size: DEFAULT_PAGE_SIZE }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryQuery {
+    pub table: Table,
+    #[serde(default)]
+    pub filter: HistoryFilter,
+    #[serde(default)]
+    pub sort: HistorySort,
+    #[serde(default)]
+    pub page: HistoryPageRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub table: Table,
+    /// Rows matching the filter across all pages
+    pub total: usize,
+    pub page: u32,
+    pub page_size: u32,
+    pub rows: Vec<Value>,
+}
+
+/// Ledger event with its kind's fields lifted to the top level, so `market_id` and
+/// `order_id` filter like any other row
+pub fn ledger_row(event: &LedgerEvent) -> Value {
+    let mut row = serde_json::json!({ "id": event.id, "timestamp": event.timestamp });
+    if let Ok(Value::Object(kind)) = serde_json::to_value(&event.kind) {
+        for (name, fields) in kind {
+            row["kind"] = Value::String(name);
+            if let Value::Object(fields) = fields {
+                row.as_object_mut().expect("object").extend(fields);
+            }
+        }
+    }
+    row
+}
+
+/// Inverse of [`ledger_row`]
+pub fn ledger_event(row: &Value) -> Option<LedgerEvent> {
+    let mut fields = row.as_object()?.clone();
+    let id = fields.remove("id")?.as_str()?.to_string();
+    let timestamp = serde_json::from_value(fields.remove("timestamp")?).ok()?;
+    let kind = fields.remove("kind")?.as_str()?.to_string();
+    let kind = serde_json::from_value(serde_json::json!({ kind: fields })).ok()?;
+    Some(LedgerEvent { id, timestamp, kind })
+}
+
+pub fn prediction_row(cached: &CachedPrediction) -> Value {
+    let mut row = serde_json::to_value(&cached.prediction).unwrap_or_default();
+    if let Value::Object(ref mut fields) = row {
+        fields.insert("analyzed_at".to_string(), serde_json::json!(cached.analyzed_at));
+        fields.insert("source".to_string(), serde_json::json!(cached.source));
+    }
+    row
+}
+
+/// A live row with its table and key, as carried in a state archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredRow {
+    pub table: Table,
+    pub key: String,
+    pub row: Value,
+}
+
+/// One start-to-stop run of the bot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    pub stopped_at: Option<DateTime<Utc>>,
+    pub cycles: u32,
+    pub start_balance: f64,
+    pub end_balance: f64,
+}
@@ -0,0 +1,133 @@
+use std::time::Instant;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use super::sampling::SamplingStrategy;
+
+/// Clock offset from the CLOB beyond which signed orders risk rejection
+const SKEW_WARN_SECS: i64 = 2;
+const SKEW_FAIL_SECS: i64 = 10;
+/// Cycle reports kept in memory for diagnostics bundles
+pub const MAX_CYCLE_REPORTS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    /// Not applicable to the current configuration, e.g. no credentials entered
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub latency_ms: Option<u64>,
+}
+
+impl DiagnosticCheck {
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status, detail: detail.into(), latency_ms: None }
+    }
+
+    pub fn passed(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Pass, detail)
+    }
+
+    pub fn warned(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Warn, detail)
+    }
+
+    pub fn failed(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Fail, detail)
+    }
+
+    pub fn skipped(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Skipped, detail)
+    }
+
+    /// Pass with `detail` on success, fail with the error otherwise
+    pub fn from_result(name: &str, started: Instant, result: anyhow::Result<String>) -> Self {
+        let latency_ms = Some(started.elapsed().as_millis() as u64);
+        match result {
+            Ok(detail) => Self { name: name.to_string(), status: CheckStatus::Pass, detail, latency_ms },
+            Err(e) => Self { name: name.to_string(), status: CheckStatus::Fail, detail: format!("{:#}", e), latency_ms },
+        }
+    }
+}
+
+/// Per-check results of `run_diagnostics`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    pub checks: Vec<DiagnosticCheck>,
+    /// No check failed; warnings and skipped checks are allowed
+    pub passed: bool,
+    /// Passed, and every check live trading depends on ran
+    pub ready_for_live: bool,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// What one scan cycle did, kept for bug reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleReport {
+    pub cycle: u32,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub orders_placed: u32,
+    pub api_cost: f64,
+    pub balance: f64,
+    /// Activity entries the cycle logged, and how many were errors
+    pub entries: u32,
+    pub errors: u32,
+    /// Counters flushed at the end of the cycle, e.g. ("tally.price_band", 3)
+    pub tallies: Vec<(String, u32)>,
+    /// Set when the cycle aborted
+    pub error: Option<String>,
+    /// Strategy that picked the markets sent for analysis, and their ids
+    #[serde(default)]
+    pub sampling: Option<SamplingStrategy>,
+    #[serde(default)]
+    pub sampled_markets: Vec<String>,
+    /// Markets parsed from the scan's listing, and the parse throughput; `None` when the
+    /// cycle's markets came from a feed
+    #[serde(default)]
+    pub markets_parsed: u32,
+    #[serde(default)]
+    pub markets_per_sec: Option<f64>,
+}
+
+/// Checks that must pass (not merely be skipped) before live trading
+pub const LIVE_REQUIREMENTS: [&str; 4] = ["CLOB", "CLOB credentials", "Clock skew", "Anthropic credentials"];
+
+impl DiagnosticsReport {
+    pub fn new(checks: Vec<DiagnosticCheck>) -> Self {
+        let passed = checks.iter().all(|c| c.status != CheckStatus::Fail);
+        let ready_for_live = passed
+            && LIVE_REQUIREMENTS.iter().all(|name| {
+                checks.iter().any(|c| c.name == *name && matches!(c.status, CheckStatus::Pass | CheckStatus::Warn))
+            });
+        Self { checks, passed, ready_for_live, checked_at: Utc::now() }
+    }
+}
+
+/// Judge the local clock against a server timestamp taken halfway through the round trip
+pub fn clock_skew_check(server_time: DateTime<Utc>, sent_at: DateTime<Utc>, received_at: DateTime<Utc>) -> DiagnosticCheck {
+    let midpoint = sent_at + (received_at - sent_at) / 2;
+    let skew = midpoint - server_time;
+    let abs = skew.num_seconds().abs();
+    let status = if abs >= SKEW_FAIL_SECS {
+        CheckStatus::Fail
+    } else if abs >= SKEW_WARN_SECS {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    };
+    let direction = if skew >= Duration::zero() { "ahead of" } else { "behind" };
+    DiagnosticCheck {
+        name: "Clock skew".to_string(),
+        status,
+        detail: format!("Local clock {}s {} the CLOB", abs, direction),
+        latency_ms: Some((received_at - sent_at).num_milliseconds().max(0) as u64),
+    }
+}
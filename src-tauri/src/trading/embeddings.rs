@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::http;
+use super::models::*;
+
+const LOCAL_DIMENSIONS: usize = 384;
+/// Vectors kept in the similarity index; the least recently seen markets are evicted past it
+pub const MAX_STORED_EMBEDDINGS: usize = 5000;
+const VOYAGE_API_URL: &str = "https://api.voyageai.com/v1/embeddings";
+const VOYAGE_MODEL: &str = "voyage-3-lite";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmbeddingProvider {
+    /// Hashed word and bigram features computed in-process, no API calls
+    #[default]
+    Local,
+    /// Voyage AI embeddings API (Anthropic's recommended embeddings provider)
+    Voyage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarMarket {
+    pub market_id: String,
+    pub question: String,
+    pub score: f32,
+}
+
+pub struct Embedder {
+    provider: EmbeddingProvider,
+    client: Client,
+    api_key: String,
+}
+
+impl Embedder {
+    pub fn new(config: &BotConfig) -> Result<Self> {
+        Ok(Self {
+            provider: config.embedding_provider,
+            client: http::build_client(30, &config.proxy_url)?,
+            api_key: config.voyage_api_key.clone(),
+        })
+    }
+
+    /// Embed a batch of texts into unit-length vectors
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        match self.provider {
+            EmbeddingProvider::Local => Ok(texts.iter().map(|t| local_embedding(t)).collect()),
+            EmbeddingProvider::Voyage => self.embed_voyage(texts).await,
+        }
+    }
+
+    async fn embed_voyage(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let resp = self.client
+            .post(VOYAGE_API_URL)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&serde_json::json!({ "input": texts, "model": VOYAGE_MODEL }))
+            .send()
+            .await?;
+        let resp = http::check(resp).await?;
+        let body: Value = resp.json().await?;
+
+        let data = body.get("data")
+            .and_then(|d| d.as_array())
+            .context("Embedding response missing data")?;
+        let mut vectors = vec![Vec::new(); texts.len()];
+        for item in data {
+            let index = item.get("index").and_then(|i| i.as_u64()).unwrap_or(0) as usize;
+            let embedding: Vec<f32> = item.get("embedding")
+                .and_then(|e| serde_json::from_value(e.clone()).ok())
+                .unwrap_or_default();
+            if let Some(slot) = vectors.get_mut(index) {
+                *slot = normalize(embedding);
+            }
+        }
+        Ok(vectors)
+    }
+}
+
+struct StoredEmbedding {
+    question: String,
+    vector: Vec<f32>,
+    /// Sequence number of the last scan or position that referenced the market
+    seen: u64,
+}
+
+/// Market question vectors keyed by market id, bounded to `MAX_STORED_EMBEDDINGS`
+#[derive(Default)]
+pub struct EmbeddingStore {
+    entries: HashMap<String, StoredEmbedding>,
+    clock: u64,
+}
+
+impl EmbeddingStore {
+    pub fn contains(&self, market_id: &str) -> bool {
+        self.entries.contains_key(market_id)
+    }
+
+    /// Mark a market as still in use so eviction passes it over
+    pub fn touch(&mut self, market_id: &str) {
+        self.clock += 1;
+        if let Some(entry) = self.entries.get_mut(market_id) {
+            entry.seen = self.clock;
+        }
+    }
+
+    pub fn insert(&mut self, market_id: &str, question: &str, vector: Vec<f32>) {
+        self.clock += 1;
+        let entry = StoredEmbedding { question: question.to_string(), vector, seen: self.clock };
+        self.entries.insert(market_id.to_string(), entry);
+        if self.entries.len() > MAX_STORED_EMBEDDINGS {
+            self.evict();
+        }
+    }
+
+    /// Drop the least recently seen tenth so eviction does not run on every insert
+    fn evict(&mut self) {
+        let keep = MAX_STORED_EMBEDDINGS - MAX_STORED_EMBEDDINGS / 10;
+        let mut seen: Vec<u64> = self.entries.values().map(|e| e.seen).collect();
+        seen.sort_unstable_by(|a, b| b.cmp(a));
+        let cutoff = seen[keep - 1];
+        self.entries.retain(|_, e| e.seen >= cutoff);
+    }
+
+    pub fn get(&self, market_id: &str) -> Option<&Vec<f32>> {
+        self.entries.get(market_id).map(|e| &e.vector)
+    }
+
+    /// Whether both markets are indexed and at least `threshold` similar
+    pub fn similar(&self, a: &str, b: &str, threshold: f32) -> bool {
+        a == b || match (self.get(a), self.get(b)) {
+            (Some(x), Some(y)) => cosine(x, y) >= threshold,
+            _ => false,
+        }
+    }
+
+    /// The `limit` stored markets closest to `vector`, excluding `exclude_id`
+    pub fn most_similar(&self, vector: &[f32], exclude_id: Option<&str>, limit: usize) -> Vec<SimilarMarket> {
+        let mut results: Vec<SimilarMarket> = self.entries
+            .iter()
+            .filter(|(id, _)| Some(id.as_str()) != exclude_id)
+            .map(|(id, e)| SimilarMarket {
+                market_id: id.clone(),
+                question: e.question.clone(),
+                score: cosine(vector, &e.vector),
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(limit);
+        results
+    }
+}
+
+/// Cosine similarity of two unit vectors
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Feature-hashed bag of words and word bigrams, L2-normalized
+pub fn local_embedding(text: &str) -> Vec<f32> {
+    let words: Vec<String> = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_string())
+        .collect();
+
+    let mut vector = vec![0.0f32; LOCAL_DIMENSIONS];
+    let mut add = |feature: &str, weight: f32| {
+        let hash = fnv1a(feature.as_bytes());
+        // Use one hash bit as the sign to reduce collision bias
+        let sign = if hash & 1 == 0 { 1.0 } else { -1.0 };
+        vector[(hash >> 1) as usize % LOCAL_DIMENSIONS] += sign * weight;
+    };
+
+    for word in &words {
+        add(word, 1.0);
+    }
+    for pair in words.windows(2) {
+        add(&format!("{} {}", pair[0], pair[1]), 0.5);
+    }
+
+    normalize(vector)
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+    vector
+}
+
+/// 64-bit FNV-1a, stable across builds unlike the std hasher
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
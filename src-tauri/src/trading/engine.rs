@@ -1,3 +1,4 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use chrono::Utc;
@@ -6,16 +7,129 @@ use anyhow::Result;
 
 use super::models::*;
 use super::polymarket::PolymarketClient;
-use super::claude::ClaudeClient;
+use super::chain::ChainClient;
+use super::claude::{ClaudeClient, ANALYSIS_MAX_TOKENS};
+use super::currency::{self, Currency, PegStatus, PriceFeed};
+use super::alerts::{AlertCondition, PriceAlert};
+use super::archive::{self, StateArchive, STATE_VERSION};
+use super::backtest::Signal;
+use super::conditional::{self, ConditionalOrder, ConditionalStatus, EntryTrigger};
+use super::batch::{self, CachedPrediction, PendingBatch, PredictionSource};
+use super::db::{self, Database, HistoryPage, HistoryQuery, SessionRecord, Table};
+use super::bundle::BundleFile;
+use super::diagnostics::{self, CycleReport, DiagnosticCheck, DiagnosticsReport};
+use super::config::{self, ConfigError};
+use super::error::{GuestModeBlocked, NotConfigured, NotFound};
+use super::embeddings::{self, Embedder, EmbeddingStore, SimilarMarket};
+use super::execution::{self, ExecutionQuality, ExecutionReport, ScheduledSlice};
+use super::exits::{self, ExitAction, ExitDecision};
+use super::market_maker::{self, MakerBook, MakerEvent, MarketMaker};
+use super::negrisk::{self, BasketKind, NegRiskEvent, NegRiskOpportunity};
+use super::features::MarketFeatures;
+use super::gamma::{self, ParseBenchmark, ParseStats};
+use super::fills::{self, RestingOrder};
+use super::flatten::{self, FlattenAction, FlattenReport};
+use super::microstructure::MicrostructureSignals;
+use super::guest::{self, GuestMode};
+use super::i18n::{self, Locale, Message};
+use super::health::{self, BreakerEvent, CallTracker, CircuitBreakerConfig, HealthStatus};
+use super::lifecycle::{self, IllegalTransition, OrderTransition};
+use super::migrations;
+use super::ledger::{CapitalFlow, FlowSource, Ledger, LedgerEvent, LedgerEventKind};
+use super::network::Environment;
+use super::notifications::{NotificationKind, NotificationManager};
+use super::prompt_packs::{PromptPack, PromptPacks};
+use super::reports::{self, CategoryCalibration, NotableEvent, PerformanceReport, ReportPeriod, ReportTrade};
+use super::reunderwrite::{self, Recommendation, ReunderwriteConfig, ThesisReview};
+use super::risk::{ExposureGroup, MarketPosition, PositionManager, SizeDecision};
+use super::snapshot::{self, ActivityDelta, Snapshot, StatsCursor, StatsDelta, ViewKey};
+use super::sampling::{Sampler, SamplingStrategy};
+use super::scoring::{self, CandidateScore, PackCalibration};
+use super::simulation::MeasuredPerformance;
+use super::time;
+use super::wallet::{self, AllowanceStatus, ApprovalBatch};
 
 pub struct TradingEngine {
     pub polymarket: Option<PolymarketClient>,
     pub claude: Option<ClaudeClient>,
+    embedder: Option<Embedder>,
+    chain: Option<ChainClient>,
+    price_feed: Option<PriceFeed>,
+    /// Last stablecoin price check
+    peg: Option<PegStatus>,
+    /// Last block scanned for wallet deposits/withdrawals
+    last_transfer_block: Option<u64>,
+    pub embeddings: EmbeddingStore,
+    /// Category-specific prompt guidance, re-applied whenever the Claude client is rebuilt
+    prompt_packs: PromptPacks,
     pub config: BotConfig,
     pub stats: BotStats,
     pub orders: Vec<Order>,
+    /// Source of truth for balance and win/loss counters
+    pub ledger: Ledger,
     pub activity_log: Vec<ActivityEntry>,
+    activity_seq: u64,
+    /// Rendered views handed to pollers until the underlying collection changes
+    activity_view: Snapshot<ActivityEntry>,
+    feed_view: Snapshot<ActivityEntry>,
+    balance_view: Snapshot<BalancePoint>,
+    orders_view: Snapshot<Order>,
+    tallies: Vec<(String, u32)>,
     pub balance_history: Vec<BalancePoint>,
+    pub portfolio: PortfolioSummary,
+    alerted_resolutions: HashSet<String>,
+    /// When each unsettled signal market was last looked up, so lookups rotate through them
+    signal_checked_at: HashMap<String, chrono::DateTime<Utc>>,
+    scheduled_slices: Vec<ScheduledSlice>,
+    /// Paper orders waiting on the simulated book under `paper_fills.queue_model`
+    resting_orders: Vec<RestingOrder>,
+    execution_reports: HashMap<String, ExecutionReport>,
+    /// Recent order status changes, oldest first
+    order_transitions: VecDeque<OrderTransition>,
+    pub latest_commentary: Option<PortfolioCommentary>,
+    /// Every AI assessment, kept for backtesting
+    pub signals: Vec<Signal>,
+    /// Latest prediction per market id, from live or batch analysis
+    prediction_cache: HashMap<String, CachedPrediction>,
+    pending_batch: Option<PendingBatch>,
+    market_maker: MarketMaker,
+    price_alerts: Vec<PriceAlert>,
+    conditional_orders: Vec<ConditionalOrder>,
+    /// Re-underwriting reviews per position key, oldest first
+    thesis_history: HashMap<String, Vec<ThesisReview>>,
+    sampler: Sampler,
+    /// Strategy and market ids sampled for analysis in the running cycle
+    cycle_sample: Option<(SamplingStrategy, Vec<String>)>,
+    /// How the running cycle's market listing parsed
+    cycle_parse: Option<ParseStats>,
+    /// Last seen price per held market id and outcome, for mark-to-market equity
+    marks: HashMap<(String, String), f64>,
+    /// Scores of the last cycle's analyzed candidates, best first
+    candidate_ranking: Vec<CandidateScore>,
+    /// Recent neg-risk baskets, newest last
+    neg_risk_baskets: Vec<NegRiskOpportunity>,
+    /// Events already holding a basket
+    neg_risk_events: HashSet<String>,
+    pub notifications: NotificationManager,
+    /// Durable history of markets, predictions, orders, fills, resolutions and activity
+    db: Option<Database>,
+    db_error: Option<String>,
+    session: Option<SessionRecord>,
+    last_commentary_attempt: Option<chrono::DateTime<Utc>>,
+    /// Cumulative AI cost sampled at the end of each cycle, for per-period reports
+    cost_marks: Vec<(chrono::DateTime<Utc>, f64)>,
+    polymarket_calls: CallTracker,
+    claude_calls: CallTracker,
+    peg_calls: CallTracker,
+    /// Last peg check, successful or not, for throttling
+    peg_attempted_at: Option<chrono::DateTime<Utc>>,
+    last_cycle: Option<chrono::DateTime<Utc>>,
+    /// Most recent cycles, oldest first
+    cycle_reports: VecDeque<CycleReport>,
+    pub guest: GuestMode,
+    /// Digest of the PIN set when guest mode was turned on
+    guest_pin: Option<String>,
+    pub locale: Locale,
     pub is_running: bool,
     pub start_time: Option<chrono::DateTime<Utc>>,
 }
@@ -28,12 +142,24 @@ impl TradingEngine {
         Self {
             polymarket: None,
             claude: None,
+            embedder: None,
+            chain: None,
+            price_feed: None,
+            peg: None,
+            last_transfer_block: None,
+            embeddings: EmbeddingStore::default(),
+            prompt_packs: PromptPacks::builtin(),
             config,
             stats: BotStats {
                 current_balance: initial_balance,
+                total_equity: initial_balance,
+                mark_to_market_equity: initial_balance,
+                model_equity: initial_balance,
+                free_capital: initial_balance,
                 initial_balance,
                 total_pnl: 0.0,
-                total_pnl_pct: "+0%".to_string(),
+                total_pnl_pct: "+0.0%".to_string(),
+                net_deposits: 0.0,
                 api_costs: 0.0,
                 win_rate: 0.0,
                 wins: 0,
@@ -50,237 +176,3353 @@ impl TradingEngine {
                 uptime: "00:00:00".to_string(),
                 cycle: 0,
                 pid: std::process::id(),
+                currency: Currency::default().symbol().to_string(),
             },
             orders: Vec::new(),
+            ledger: Ledger::default(),
             activity_log: Vec::new(),
+            activity_seq: 0,
+            activity_view: Snapshot::default(),
+            feed_view: Snapshot::default(),
+            balance_view: Snapshot::default(),
+            orders_view: Snapshot::default(),
+            tallies: Vec::new(),
             balance_history: vec![BalancePoint {
-                timestamp: Utc::now().format("%H:%M:%S").to_string(),
+                timestamp: Utc::now(),
+                local_time: String::new(),
                 balance: initial_balance,
                 label: "0h".to_string(),
             }],
+            portfolio: PortfolioSummary::default(),
+            alerted_resolutions: HashSet::new(),
+            signal_checked_at: HashMap::new(),
+            scheduled_slices: Vec::new(),
+            resting_orders: Vec::new(),
+            execution_reports: HashMap::new(),
+            order_transitions: VecDeque::new(),
+            latest_commentary: None,
+            signals: Vec::new(),
+            prediction_cache: HashMap::new(),
+            pending_batch: None,
+            market_maker: MarketMaker::default(),
+            price_alerts: Vec::new(),
+            conditional_orders: Vec::new(),
+            thesis_history: HashMap::new(),
+            sampler: Sampler::default(),
+            cycle_sample: None,
+            cycle_parse: None,
+            marks: HashMap::new(),
+            candidate_ranking: Vec::new(),
+            neg_risk_baskets: Vec::new(),
+            neg_risk_events: HashSet::new(),
+            notifications: NotificationManager::default(),
+            db: None,
+            db_error: None,
+            session: None,
+            last_commentary_attempt: None,
+            cost_marks: Vec::new(),
+            polymarket_calls: CallTracker::default(),
+            claude_calls: CallTracker::default(),
+            peg_calls: CallTracker::default(),
+            peg_attempted_at: None,
+            last_cycle: None,
+            cycle_reports: VecDeque::new(),
+            guest: GuestMode::default(),
+            guest_pin: None,
+            locale: Locale::default(),
             is_running: false,
             start_time: None,
         }
     }
 
-    /// Initialize clients with API keys
-    pub fn configure(&mut self, config: BotConfig) {
-        self.polymarket = Some(PolymarketClient::new(
-            &config.polymarket_api_key,
-            &config.polymarket_secret,
-            &config.polymarket_passphrase,
-        ));
-        self.claude = Some(ClaudeClient::new(
-            &config.claude_api_key,
-            &config.claude_model,
-        ));
+    /// Validate the config, then initialize clients with API keys, proxy and endpoint overrides
+    pub fn configure(&mut self, config: BotConfig) -> std::result::Result<(), Vec<ConfigError>> {
+        config::validate(&config)?;
+
+        // Build every client before swapping any in, so a failure leaves the old set intact
+        let client_error = |e: anyhow::Error| vec![ConfigError { field: "proxy_url".to_string(), message: e.to_string() }];
+        let polymarket = PolymarketClient::new(&config).map_err(client_error)?;
+        let mut claude = ClaudeClient::new(&config).map_err(client_error)?;
+        let embedder = Embedder::new(&config).map_err(client_error)?;
+        let chain = ChainClient::new(&config).map_err(client_error)?;
+        let price_feed = PriceFeed::new(&config).map_err(client_error)?;
+        claude.set_prompt_packs(self.prompt_packs.clone());
+
+        self.polymarket = Some(polymarket);
+        self.claude = Some(claude);
+        self.embedder = Some(embedder);
+        self.chain = Some(chain);
+        self.price_feed = Some(price_feed);
+        self.stats.currency = config.currency.currency.symbol().to_string();
+        self.notifications.set_config(config.notifications.clone());
         self.config = config;
-        self.add_activity("Configuration updated successfully", ActivityType::Info);
+        self.add_message(Message::new("config.updated"), ActivityType::Info);
+        Ok(())
+    }
+
+    /// Route analyses through `packs` from now on
+    pub fn attach_prompt_packs(&mut self, packs: PromptPacks) {
+        if let Some(ref mut claude) = self.claude {
+            claude.set_prompt_packs(packs.clone());
+        }
+        self.prompt_packs = packs;
+    }
+
+    /// Re-read the prompt-pack files after they were edited
+    pub fn reload_prompt_packs(&mut self) -> Result<Vec<PromptPack>> {
+        let packs = self.prompt_packs.reload()?;
+        self.attach_prompt_packs(packs);
+        let msg = Message::new("prompt_packs.reloaded").arg("count", self.prompt_packs.packs().len());
+        self.add_message(msg, ActivityType::Info);
+        Ok(self.get_prompt_packs())
+    }
+
+    pub fn get_prompt_packs(&self) -> Vec<PromptPack> {
+        self.prompt_packs.packs().to_vec()
+    }
+
+    /// Calibration per category and prompt pack, to judge whether specialization helps
+    pub fn get_pack_calibration(&self) -> Vec<PackCalibration> {
+        scoring::pack_calibration(&self.signals)
+    }
+
+    /// Write history through to `db` from now on. An engine with no orders or ledger yet,
+    /// as at startup, first restores its orders, ledger, recent activity and thesis reviews
+    /// from it; balance history, signals, alerts and the prediction cache are not restored.
+    pub fn attach_database(&mut self, db: Database) {
+        let reviews = db.rows(Table::Reviews)
+            .into_iter()
+            .filter_map(|row| serde_json::from_value(row.clone()).ok())
+            .collect();
+        self.set_thesis_history(reviews);
+        let restore = self.orders.is_empty() && self.ledger.events().is_empty();
+        if restore {
+            self.restore_from(&db);
+        }
+        self.db = Some(db);
+        if restore {
+            self.close_orphaned_orders();
+        }
+        self.persist_history();
+    }
+
+    fn restore_from(&mut self, db: &Database) {
+        self.orders = db.rows(Table::Orders)
+            .into_iter()
+            .filter_map(|row| serde_json::from_value(row.clone()).ok())
+            .collect();
+        self.orders.sort_by_key(|o| o.created_at);
+        let events = [Table::Ledger, Table::Fills, Table::Resolutions]
+            .into_iter()
+            .flat_map(|table| db.rows(table))
+            .filter_map(db::ledger_event)
+            .collect();
+        self.ledger = Ledger::from_events(events);
+
+        let rows = db.rows(Table::Activity);
+        let recent = &rows[rows.len().saturating_sub(500)..];
+        let mut activity: Vec<ActivityEntry> = recent.iter()
+            .filter_map(|row| serde_json::from_value((*row).clone()).ok())
+            .collect();
+        activity.sort_by_key(|e| e.timestamp);
+        // Sequence numbers restart with the app; renumber so feed cursors stay ordered
+        for (i, entry) in activity.iter_mut().enumerate() {
+            entry.seq = i as u64 + 1;
+        }
+        self.activity_seq = activity.len() as u64;
+        self.activity_log = activity;
+
+        self.apply_ledger_totals();
+        self.update_stats();
+    }
+
+    /// Settle working orders whose fill state did not survive a restart or import: the
+    /// filled part of each stays as a filled order and the rest is cancelled
+    fn close_orphaned_orders(&mut self) {
+        let orphaned: Vec<usize> = self.orders.iter()
+            .enumerate()
+            .filter(|(_, o)| o.status.is_working() && !o.is_imported() && o.clob_order_id.is_none())
+            .filter(|(_, o)| !self.resting_orders.iter().any(|r| r.order_id == o.id))
+            .filter(|(_, o)| !self.scheduled_slices.iter().any(|s| s.parent_id == o.id || s.child_id == o.id))
+            .map(|(i, _)| i)
+            .collect();
+        for &index in &orphaned {
+            self.close_remainder(index);
+        }
+        if !orphaned.is_empty() {
+            self.add_message(Message::new("orders.orphaned").arg("count", orphaned.len()), ActivityType::Warning);
+        }
+    }
+
+    /// Start the trading bot
+    pub fn start(&mut self) {
+        self.is_running = true;
+        self.start_time = Some(Utc::now());
+        self.session = Some(SessionRecord {
+            id: Uuid::new_v4().to_string(),
+            started_at: Utc::now(),
+            stopped_at: None,
+            cycles: 0,
+            start_balance: self.stats.current_balance,
+            end_balance: self.stats.current_balance,
+        });
+        self.persist_history();
+        if self.config.environment == Environment::Testnet {
+            self.add_message(Message::new("bot.testnet"), ActivityType::Warning);
+        }
+        if self.config.watch_only {
+            self.add_message(Message::new("bot.started_watch_only"), ActivityType::Info);
+        } else {
+            self.add_message(Message::new("bot.started"), ActivityType::Info);
+        }
+    }
+
+    /// Stop the trading bot
+    pub fn stop(&mut self) {
+        self.is_running = false;
+        if let Some(ref mut session) = self.session {
+            session.stopped_at = Some(Utc::now());
+            session.end_balance = self.stats.current_balance;
+        }
+        self.persist_history();
+        self.add_message(Message::new("bot.stopped"), ActivityType::Warning);
+    }
+
+    /// Run one cycle of market scanning + trading, returning the feed entries it produced
+    pub async fn run_cycle(&mut self) -> Result<Vec<ActivityEntry>> {
+        self.cycle(None).await
+    }
+
+    /// Run a cycle against markets fetched by the caller, so several engines can share one feed
+    pub async fn run_cycle_on(&mut self, markets: Vec<Market>) -> Result<Vec<ActivityEntry>> {
+        self.cycle(Some(markets)).await
+    }
+
+    async fn cycle(&mut self, feed: Option<Vec<Market>>) -> Result<Vec<ActivityEntry>> {
+        let since = self.activity_seq;
+        let started = Utc::now();
+        let (orders_before, costs_before) = (self.orders.len(), self.stats.api_costs);
+        self.cycle_sample = None;
+        let result = self.run_cycle_inner(feed).await;
+        if self.is_running {
+            self.record_cycle_report(since, started, orders_before, costs_before, result.as_ref().err());
+        }
+        self.report_breaker_events();
+        // An aborted cycle's counts belong to it, not to the next one
+        self.flush_tallies();
+        result?;
+        self.last_cycle = Some(Utc::now());
+        self.record_cost_mark();
+        if let Some(ref mut session) = self.session {
+            session.cycles += 1;
+            session.end_balance = self.stats.current_balance;
+        }
+        self.persist_history();
+
+        Ok(self.render_activity_since(since, self.config.feed_log_level))
+    }
+
+    /// Whether the next analysis fits the Anthropic quota left by the last response. When it
+    /// does not, the `remaining` analyses are deferred to a later cycle rather than waited
+    /// for, since a wait here would hold the engine lock.
+    fn pace_claude(&mut self, remaining: usize) -> bool {
+        let Some(ref claude) = self.claude else { return true };
+        let delay = claude.rate_limits().pacing_delay(
+            self.config.max_prompt_tokens as u64,
+            ANALYSIS_MAX_TOKENS as u64,
+            Utc::now(),
+        );
+        let Some(delay) = delay else { return true };
+        let msg = Message::new("claude.deferred").arg("count", remaining).arg("secs", delay.num_seconds().max(1));
+        self.add_message(msg, ActivityType::Warning);
+        false
+    }
+
+    /// Log circuit breakers that opened or closed during the cycle
+    fn report_breaker_events(&mut self) {
+        let services = [
+            ("Polymarket", self.polymarket_calls.take_events()),
+            ("Claude", self.claude_calls.take_events()),
+        ];
+        for (service, events) in services {
+            for event in events {
+                match event {
+                    BreakerEvent::Tripped { failures, cooldown_secs } => {
+                        let msg = Message::new("breaker.tripped")
+                            .arg("service", service)
+                            .arg("failures", failures)
+                            .arg("cooldown", cooldown_secs);
+                        let title = format!("{} calls paused", service);
+                        self.notifications.notify(NotificationKind::ApiFailure, &title, &msg.render(self.locale));
+                        self.add_message(msg, ActivityType::Warning);
+                    }
+                    BreakerEvent::Reopened => {
+                        let msg = Message::new("breaker.reopened").arg("service", service);
+                        self.add_message_at(msg, ActivityType::Warning, LogLevel::Debug);
+                    }
+                    BreakerEvent::Recovered => {
+                        self.add_message(Message::new("breaker.recovered").arg("service", service), ActivityType::Info);
+                    }
+                }
+            }
+        }
+    }
+
+    fn record_cycle_report(
+        &mut self,
+        since: u64,
+        started: chrono::DateTime<Utc>,
+        orders_before: usize,
+        costs_before: f64,
+        error: Option<&anyhow::Error>,
+    ) {
+        let start = self.activity_log.partition_point(|e| e.seq <= since);
+        let entries = &self.activity_log[start..];
+        let report = CycleReport {
+            cycle: self.stats.cycle,
+            started_at: started,
+            duration_ms: (Utc::now() - started).num_milliseconds().max(0) as u64,
+            orders_placed: self.orders.len().saturating_sub(orders_before) as u32,
+            api_cost: self.stats.api_costs - costs_before,
+            balance: self.stats.current_balance,
+            entries: entries.len() as u32,
+            errors: entries.iter().filter(|e| matches!(e.entry_type, ActivityType::Error)).count() as u32,
+            tallies: self.tallies.clone(),
+            error: error.map(|e| format!("{:#}", e)),
+            sampling: self.cycle_sample.as_ref().map(|(strategy, _)| *strategy),
+            sampled_markets: self.cycle_sample.take().map(|(_, ids)| ids).unwrap_or_default(),
+            markets_parsed: self.cycle_parse.map_or(0, |stats| stats.markets),
+            markets_per_sec: self.cycle_parse.take().and_then(|stats| stats.markets_per_sec()),
+        };
+        if self.cycle_reports.len() >= diagnostics::MAX_CYCLE_REPORTS {
+            self.cycle_reports.pop_front();
+        }
+        self.cycle_reports.push_back(report);
+    }
+
+    async fn run_cycle_inner(&mut self, feed: Option<Vec<Market>>) -> Result<()> {
+        if !self.is_running {
+            return Ok(());
+        }
+
+        self.stats.cycle += 1;
+
+        // Update uptime
+        if let Some(start) = self.start_time {
+            let elapsed = Utc::now() - start;
+            let hours = elapsed.num_hours();
+            let minutes = elapsed.num_minutes() % 60;
+            let seconds = elapsed.num_seconds() % 60;
+            self.stats.uptime = format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
+        }
+
+        if self.config.watch_only {
+            self.run_watch_cycle().await;
+            self.maybe_generate_commentary(&[]).await;
+            return Ok(());
+        }
+
+        if feed.is_none() && self.polymarket.is_some() && !self.polymarket_calls.allows() {
+            self.tally("tally.circuit_open");
+            return Ok(());
+        }
+
+        // Scan markets
+        let fetched = match (feed, &self.polymarket) {
+            (Some(markets), _) => Ok(markets),
+            (None, Some(client)) => {
+                let fetched = client.get_markets_with_stats(100, 0).await;
+                self.polymarket_calls.record(&fetched, &self.config.circuit_breaker);
+                fetched.map(|(markets, stats)| {
+                    self.cycle_parse = Some(stats);
+                    markets
+                })
+            }
+            (None, None) => return Ok(()),
+        };
+
+        let msg = Message::new("cycle.scanning").arg("cycle", self.stats.cycle);
+        self.add_message_at(msg, ActivityType::Info, LogLevel::Debug);
+
+        let markets = match fetched {
+            Ok(markets) => {
+                self.stats.markets_scanned += markets.len() as u64;
+                for market in &markets {
+                    self.persist(Table::Markets, &market.id, market);
+                }
+                let msg = Message::new("cycle.processing").arg("count", markets.len());
+                self.add_message_at(msg, ActivityType::Info, LogLevel::Debug);
+                markets
+            }
+            Err(e) => {
+                let msg = Message::new("markets.fetch_failed").arg("error", e);
+                self.notifications.notify(NotificationKind::ApiFailure, "Polymarket API failure", &msg.render(self.locale));
+                self.add_message(msg, ActivityType::Error);
+                return Ok(());
+            }
+        };
+
+        self.index_markets(&markets).await;
+
+        // Analyze markets with AI
+        // Markets being made are quoted, not traded directionally
+        let mm = &self.config.market_maker;
+        let directional: Vec<&Market> = markets
+            .iter()
+            .filter(|m| !(mm.enabled && mm.markets.contains(&m.id)))
+            .collect();
+        let universe = directional.len();
+        let directional = self.sampler.sample(directional, &self.config.sampling, self.stats.cycle);
+        let msg = Message::new("cycle.sampled")
+            .arg("count", directional.len())
+            .arg("total", universe)
+            .arg("strategy", format!("{:?}", self.config.sampling.strategy));
+        self.add_message_at(msg, ActivityType::Info, LogLevel::Debug);
+        self.cycle_sample = Some((
+            self.config.sampling.strategy,
+            directional.iter().map(|m| m.id.clone()).collect(),
+        ));
+        let candidates = self.dedupe_candidates(directional);
+        // Only markets that got a live prediction; ones paced out, skipped or failed go to the batch
+        let mut analyzed_live: HashSet<&str> = HashSet::new();
+        let mut analyzed = Vec::new();
+        let total = candidates.len();
+        for (i, market) in candidates.into_iter().enumerate() {
+            if self.claude.is_some() && !self.claude_calls.allows() {
+                self.tally("tally.circuit_open");
+                continue;
+            }
+            if !self.pace_claude(total - i) {
+                break;
+            }
+            let features = self.market_features(market).await;
+            if let Some(ref mut claude) = self.claude {
+                let analysis = claude.analyze_market(market, Some(&features)).await;
+                self.claude_calls.record(&analysis, &self.config.circuit_breaker);
+                match analysis {
+                    Ok(prediction) => {
+                        self.stats.api_costs = claude.estimate_cost();
+                        self.record_signal(market, &prediction);
+                        self.cache_prediction(&prediction, PredictionSource::Live);
+                        analyzed_live.insert(market.id.as_str());
+                        analyzed.push((market, prediction));
+                    }
+                    Err(e) => {
+                        let err_msg = Message::new("analysis.failed")
+                            .arg("market", truncate_str(&market.question, 40))
+                            .arg("error", e);
+                        self.add_message_at(err_msg, ActivityType::Inference, LogLevel::Debug);
+                        self.tally("tally.analysis_failed");
+                    }
+                }
+            }
+        }
+
+        self.trade_ranked(analyzed).await;
+
+        // Everything else scanned is re-analyzed at batch pricing when stale
+        self.schedule_batch(&markets, &analyzed_live).await;
+
+        self.run_market_maker(&markets).await;
+
+        if self.config.neg_risk.enabled {
+            self.run_neg_risk(&markets).await;
+        }
+
+        // Work remaining slices of large orders
+        self.process_due_slices();
+        self.process_paper_fills().await;
+
+        self.maybe_generate_commentary(&markets).await;
+
+        // Check held positions against a fresh analysis before the exit review
+        if self.config.reunderwrite.enabled {
+            self.reunderwrite_positions(&markets).await;
+        }
+
+        // Sell out of positions close to resolution when holding no longer pays
+        if self.config.exits.enabled {
+            self.plan_exits(&markets).await;
+        }
+
+        // Settle orders whose markets have resolved
+        self.resolve_pending_orders().await;
+
+        self.mark_positions(&markets).await;
+
+        // Update balance history
+        self.balance_history.push(BalancePoint {
+            timestamp: Utc::now(),
+            local_time: String::new(),
+            balance: self.stats.current_balance,
+            label: format!("{}h", self.balance_history.len()),
+        });
+
+        // Update derived stats
+        self.update_stats();
+
+        Ok(())
+    }
+
+    /// Refresh the price of every held outcome from the scan, fetching held markets it missed
+    async fn mark_positions(&mut self, scanned: &[Market]) {
+        let held: HashSet<String> = self.orders.iter()
+            .filter(|o| held_cost(o) > 0.0)
+            .map(|o| o.market_id.clone())
+            .collect();
+        let mut fetched = Vec::new();
+        for market_id in held.iter().filter(|id| !scanned.iter().any(|m| m.id == **id)) {
+            let Some(ref client) = self.polymarket else { break };
+            if !self.polymarket_calls.allows() {
+                break;
+            }
+            let market = client.get_market(market_id).await;
+            self.polymarket_calls.record(&market, &self.config.circuit_breaker);
+            if let Ok(Some(market)) = market {
+                fetched.push(market);
+            }
+        }
+
+        self.marks.retain(|(market_id, _), _| held.contains(market_id));
+        for market in scanned.iter().chain(&fetched).filter(|m| held.contains(&m.id)) {
+            for (outcome, price) in market.outcomes.iter().zip(&market.outcome_prices) {
+                self.marks.insert((market.id.clone(), outcome.clone()), *price);
+            }
+        }
+    }
+
+    /// Realized balance with open positions revalued at the last market price and at the
+    /// model's fair value. A position without a price yet counts at cost, and without a
+    /// prediction at its market price.
+    fn marked_equity(&self) -> (f64, f64) {
+        let mut market_equity = self.stats.current_balance;
+        let mut model_equity = self.stats.current_balance;
+        for order in &self.orders {
+            let cost = held_cost(order);
+            if cost <= 0.0 || order.price <= 0.0 {
+                continue;
+            }
+            let shares = order.shares_for(cost);
+            let mark = self.marks
+                .get(&(order.market_id.clone(), order.outcome.clone()))
+                .copied()
+                .unwrap_or(order.entry_price());
+            let fair = self.fair_value(order).unwrap_or(mark);
+            market_equity += shares * mark - cost;
+            model_equity += shares * fair - cost;
+        }
+        (market_equity, model_equity)
+    }
+
+    /// Import the account's historical fills into the order ledger, skipping ones already present
+    pub async fn import_trade_history(&mut self) -> Result<ImportSummary> {
+        const PAGE_SIZE: u32 = 500;
+        const MAX_PAGES: u32 = 20;
+
+        if self.config.wallet_address.is_empty() {
+            return Err(ConfigError::new("wallet_address", "Required to import trade history").into());
+        }
+        let Some(ref client) = self.polymarket else {
+            return Err(NotConfigured("Polymarket client").into());
+        };
+
+        let mut trades = Vec::new();
+        for page in 0..MAX_PAGES {
+            let batch = client.get_trades(&self.config.wallet_address, PAGE_SIZE, page * PAGE_SIZE).await?;
+            let done = (batch.len() as u32) < PAGE_SIZE;
+            trades.extend(batch);
+            if done {
+                break;
+            }
+        }
+
+        let mut known: HashSet<String> = self.orders.iter()
+            .filter_map(|o| o.external_id.clone())
+            .collect();
+        let mut summary = ImportSummary { fetched: trades.len() as u32, ..Default::default() };
+
+        // Oldest first, the order the bot's own orders are kept in. Imported trades stay out
+        // of the ledger: they were not placed by the bot and its stats and reports leave them out.
+        trades.sort_by_key(|t| t.timestamp);
+        for trade in trades {
+            if !known.insert(trade.external_id.clone()) {
+                summary.duplicates += 1;
+                continue;
+            }
+
+            // Buys stay open until sold or resolved; sells are closing fills
+            let status = match trade.side {
+                OrderSide::Buy => OrderStatus::Filled,
+                OrderSide::Sell => OrderStatus::Resolved,
+            };
+            let stake = trade.size * trade.price;
+            self.orders.push(Order {
+                id: Uuid::new_v4().to_string(),
+                market_id: trade.market_id,
+                market_name: trade.market_name,
+                side: trade.side,
+                outcome: trade.outcome,
+                price: trade.price,
+                size: stake,
+                resolved_at: if matches!(status, OrderStatus::Resolved) { Some(trade.timestamp) } else { None },
+                status,
+                created_at: trade.timestamp,
+                pnl: None,
+                category: None,
+                parent_id: None,
+                child_ids: Vec::new(),
+                filled_size: stake,
+                fill_price: None,
+                resolution: None,
+                external_id: Some(trade.external_id),
+                token_id: None,
+                clob_order_id: None,
+                replaces: None,
+                replaced_by: None,
+                exited_pnl: 0.0,
+                status_changed_at: None,
+            });
+            summary.imported += 1;
+        }
+        if summary.imported > 0 {
+            self.net_imported_trades();
+        }
+
+        let msg = Message::new("import.done")
+            .arg("imported", summary.imported)
+            .arg("duplicates", summary.duplicates);
+        self.persist_history();
+        self.add_message(msg, ActivityType::Info);
+        Ok(summary)
+    }
+
+    /// Match imported sells against earlier imported buys of the same asset, oldest lot first.
+    /// Each sell's pnl is its proceeds less the cost of the shares it closed, and buys whose
+    /// shares were all sold are marked resolved. Sell pnl is recomputed on every import.
+    fn net_imported_trades(&mut self) {
+        let mut by_asset: HashMap<(String, String), Vec<usize>> = HashMap::new();
+        for (i, order) in self.orders.iter().enumerate().filter(|(_, o)| o.is_imported()) {
+            by_asset.entry((order.market_id.clone(), order.outcome.clone())).or_default().push(i);
+        }
+
+        // Buys whose shares were all sold, with when; statuses change once matching is done
+        let mut closed: Vec<(usize, chrono::DateTime<Utc>)> = Vec::new();
+        for mut indices in by_asset.into_values() {
+            indices.sort_by_key(|&i| self.orders[i].created_at);
+            // Open lots as (order index, shares left)
+            let mut lots: VecDeque<(usize, f64)> = VecDeque::new();
+            for i in indices {
+                let order = &self.orders[i];
+                let shares = if order.price > 0.0 { order.size / order.price } else { 0.0 };
+                if matches!(order.side, OrderSide::Buy) {
+                    lots.push_back((i, shares));
+                    continue;
+                }
+
+                let sold_at = order.created_at;
+                let mut remaining = shares;
+                let mut matched = 0.0;
+                let mut cost = 0.0;
+                while remaining > 1e-9 {
+                    let Some(lot) = lots.front_mut() else { break };
+                    let take = lot.1.min(remaining);
+                    cost += take * self.orders[lot.0].price;
+                    matched += take;
+                    remaining -= take;
+                    lot.1 -= take;
+                    if lot.1 <= 1e-9 {
+                        closed.push((lot.0, sold_at));
+                        lots.pop_front();
+                    }
+                }
+                let sell = &mut self.orders[i];
+                sell.pnl = if matched > 0.0 { Some(matched * sell.price - cost) } else { None };
+            }
+        }
+
+        // Buys closed by an earlier import are Resolved already
+        for (i, sold_at) in closed {
+            if self.orders[i].status == OrderStatus::Filled && self.transition_order(i, OrderStatus::Resolved) {
+                self.orders[i].resolved_at = Some(sold_at);
+            }
+        }
+    }
+
+    /// Watch-only cycle: import the account's positions and track PnL and resolutions
+    async fn run_watch_cycle(&mut self) {
+        if self.config.wallet_address.is_empty() {
+            self.add_message(Message::new("watch.requires_wallet"), ActivityType::Warning);
+            return;
+        }
+
+        if !self.polymarket_calls.allows() {
+            self.tally("tally.circuit_open");
+            return;
+        }
+        let fetched = match self.polymarket {
+            Some(ref client) => client.get_positions(&self.config.wallet_address).await,
+            None => return,
+        };
+        self.polymarket_calls.record(&fetched, &self.config.circuit_breaker);
+
+        let positions = match fetched {
+            Ok(positions) => positions,
+            Err(e) => {
+                let msg = Message::new("positions.fetch_failed").arg("error", e);
+                self.notifications.notify(NotificationKind::ApiFailure, "Polymarket API failure", &msg.render(self.locale));
+                self.add_message(msg, ActivityType::Error);
+                return;
+            }
+        };
+
+        // Alert once per position when its market resolves
+        for position in positions.iter().filter(|p| p.redeemable) {
+            let key = format!("{}:{}", position.market_id, position.outcome);
+            if self.alerted_resolutions.insert(key) {
+                let won = position.current_price >= 0.5;
+                let msg = Message::new("position.resolved")
+                    .arg("market", truncate_str(&position.market_name, 40))
+                    .arg("outcome", &position.outcome)
+                    .arg("pnl", i18n::signed_usd(position.pnl));
+                self.notifications.notify_resolution(position.pnl, &msg.render(self.locale));
+                self.add_message(msg, if won { ActivityType::Resolved } else { ActivityType::Warning });
+            }
+        }
+
+        let total_cost: f64 = positions.iter().map(|p| p.initial_value).sum();
+        let total_value: f64 = positions.iter().map(|p| p.current_value).sum();
+        self.portfolio = PortfolioSummary {
+            resolved_count: positions.iter().filter(|p| p.redeemable).count() as u32,
+            positions,
+            total_cost,
+            total_value,
+            unrealized_pnl: total_value - total_cost,
+            category_utilization: Vec::new(),
+        };
+
+        let msg = Message::new("watch.summary")
+            .arg("count", self.portfolio.positions.len())
+            .arg("value", format!("{:.2}", total_value))
+            .arg("pnl", i18n::signed_usd(self.portfolio.unrealized_pnl));
+        self.add_message(msg, ActivityType::Info);
+
+        self.balance_history.push(BalancePoint {
+            timestamp: Utc::now(),
+            local_time: String::new(),
+            balance: total_value,
+            label: format!("{}h", self.balance_history.len()),
+        });
+    }
+
+    /// Market-maker timer tick: requote between scan cycles, fetching the quoted markets
+    pub async fn refresh_quotes(&mut self) {
+        self.run_market_maker(&[]).await;
+    }
+
+    /// Refresh two-sided quotes on the market-making markets around the model's fair value
+    async fn run_market_maker(&mut self, scanned: &[Market]) {
+        let config = self.config.market_maker.clone();
+        if !config.enabled {
+            return;
+        }
+
+        for market_id in &config.markets {
+            let market = match scanned.iter().find(|m| &m.id == market_id) {
+                Some(market) => market.clone(),
+                None => match self.polymarket {
+                    Some(ref client) => match client.get_market(market_id).await {
+                        Ok(Some(market)) => market,
+                        _ => continue,
+                    },
+                    None => return,
+                },
+            };
+            let Some(outcome) = market.outcomes.first().cloned() else { continue };
+            let Some(snapshot) = self.outcome_book(&market, &outcome).await else { continue };
+            let fair = self.maker_fair_value(&market).await;
+
+            let book = self.market_maker.book_mut(&market);
+            let events = market_maker::update(book, &snapshot, fair, market.tick_size, &config, Utc::now());
+            let book = book.clone();
+            for event in events {
+                self.log_maker_event(&book, event);
+            }
+        }
+    }
+
+    /// Rank analyzed candidates by composite score and trade the top-K that clear
+    /// `min_score`, best first, while free capital lasts. Only orders actually placed take
+    /// a slot, so a candidate held back by the risk or execution checks makes way for the next.
+    async fn trade_ranked(&mut self, analyzed: Vec<(&Market, AIPrediction)>) {
+        let config = self.config.scoring.clone();
+        let calibration = scoring::category_calibration(&self.signals);
+        let now = Utc::now();
+        let mut ranked: Vec<(&Market, AIPrediction, CandidateScore)> = analyzed
+            .into_iter()
+            .map(|(market, prediction)| {
+                let score = scoring::score(market, &prediction, &calibration, &config, now);
+                (market, prediction, score)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.2.score.total_cmp(&a.2.score));
+
+        let mut taken = 0;
+        for (market, prediction, score) in ranked.iter_mut() {
+            if prediction.edge < self.config.min_edge_threshold || score.score < config.min_score {
+                self.tally("tally.below_score");
+                continue;
+            }
+            if taken >= config.top_k {
+                self.tally("tally.outranked");
+                continue;
+            }
+            let free_capital = self.free_capital();
+            if free_capital <= 1.0 {
+                self.tally("tally.no_capital");
+                continue;
+            }
+
+            // Found an edge!
+            let edge_msg = Message::new("edge.found")
+                .arg("market", truncate_str(&market.question, 40))
+                .arg("size", format!("{:.0}", prediction.recommended_size * free_capital))
+                .arg("edge", format!("{:.2}", prediction.edge))
+                .arg("fair", format!("{:.2}", prediction.fair_price))
+                .arg("score", format!("{:.2}", score.score));
+            self.add_message(edge_msg, ActivityType::Edge);
+
+            // Place order (simulated for safety)
+            let order_size = (prediction.recommended_size * free_capital)
+                .min(self.config.max_bet_size)
+                .min(free_capital);
+
+            if order_size > 1.0 && self.config.auto_trading {
+                if let Some(order_size) = self.apply_risk_limits(market, prediction, order_size) {
+                    if self.place_order(market, prediction, order_size).await {
+                        taken += 1;
+                        score.selected = true;
+                    }
+                }
+            }
+        }
+        self.candidate_ranking = ranked.into_iter().map(|(_, _, score)| score).collect();
+    }
+
+    /// Scores of the last cycle's analyzed candidates, best first
+    pub fn get_candidate_ranking(&self) -> Vec<CandidateScore> {
+        self.candidate_ranking.clone()
+    }
+
+    /// Watch an outcome's price against a threshold; `outcome` defaults to the first one
+    pub async fn create_price_alert(
+        &mut self,
+        market_id: &str,
+        condition: AlertCondition,
+        threshold: f64,
+        outcome: Option<String>,
+    ) -> Result<PriceAlert> {
+        if !(threshold > 0.0 && threshold < 1.0) {
+            return Err(ConfigError::new("threshold", "Must be between 0 and 1").into());
+        }
+        let client = self.polymarket.as_ref().ok_or(NotConfigured("Polymarket client"))?;
+        let market = client.get_market(market_id).await?
+            .ok_or_else(|| NotFound(format!("Market {} not found", market_id)))?;
+        let outcome = match outcome {
+            Some(wanted) => market.outcomes.iter()
+                .find(|o| o.eq_ignore_ascii_case(&wanted))
+                .cloned()
+                .ok_or_else(|| ConfigError::new("outcome", format!("{} is not an outcome of this market", wanted)))?,
+            None => market.outcomes.first().cloned().unwrap_or_else(|| "Yes".to_string()),
+        };
+
+        let now = Utc::now();
+        let alert = PriceAlert {
+            id: Uuid::new_v4().to_string(),
+            market_id: market.id.clone(),
+            market_name: market.question.clone(),
+            last_price: outcome_price(&market, &outcome),
+            outcome,
+            condition,
+            threshold,
+            created_at: now,
+            last_checked: Some(now),
+            triggered_at: None,
+            triggered_price: None,
+        };
+        let msg = alert_message(Message::new("alert.created"), &alert, alert.last_price);
+        self.add_message(msg, ActivityType::Info);
+        self.price_alerts.push(alert.clone());
+        Ok(alert)
+    }
+
+    pub fn delete_price_alert(&mut self, alert_id: &str) -> Result<()> {
+        let before = self.price_alerts.len();
+        self.price_alerts.retain(|a| a.id != alert_id);
+        if self.price_alerts.len() == before {
+            return Err(NotFound(format!("Alert {} not found", alert_id)).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_price_alerts(&self) -> Vec<PriceAlert> {
+        self.price_alerts.clone()
+    }
+
+    /// Fetch current prices for active alerts and fire the ones whose condition is met.
+    /// Runs on its own timer, independent of trading cycles.
+    pub async fn check_price_alerts(&mut self) {
+        let mut market_ids: Vec<String> = self.price_alerts.iter()
+            .filter(|a| a.is_active())
+            .map(|a| a.market_id.clone())
+            .collect();
+        market_ids.sort();
+        market_ids.dedup();
+        if market_ids.is_empty() {
+            return;
+        }
+
+        let Some(ref client) = self.polymarket else { return };
+        let mut markets = HashMap::new();
+        for market_id in market_ids {
+            if let Ok(Some(market)) = client.get_market(&market_id).await {
+                markets.insert(market_id, market);
+            }
+        }
+
+        let now = Utc::now();
+        let mut fired = Vec::new();
+        for alert in self.price_alerts.iter_mut().filter(|a| a.is_active()) {
+            let Some(price) = markets.get(&alert.market_id).and_then(|m| outcome_price(m, &alert.outcome)) else {
+                continue;
+            };
+            if alert.fires_at(price) {
+                alert.triggered_at = Some(now);
+                alert.triggered_price = Some(price);
+                fired.push(alert.clone());
+            }
+            alert.last_price = Some(price);
+            alert.last_checked = Some(now);
+        }
+
+        for alert in fired {
+            let msg = alert_message(Message::new("alert.triggered"), &alert, alert.triggered_price);
+            self.notifications.notify(NotificationKind::PriceAlert, "Price alert", &msg.render(self.locale));
+            self.add_message(msg, ActivityType::Warning);
+        }
+    }
+
+    /// Park the market's latest signal until `trigger` fires. `size` fixes the stake;
+    /// otherwise it is sized from the signal and free capital when it triggers.
+    pub async fn park_conditional_order(
+        &mut self,
+        market_id: &str,
+        trigger: EntryTrigger,
+        size: Option<f64>,
+        expires_at: Option<chrono::DateTime<Utc>>,
+    ) -> Result<ConditionalOrder> {
+        let now = Utc::now();
+        match trigger {
+            EntryTrigger::Price { threshold, .. } if !(threshold > 0.0 && threshold < 1.0) => {
+                return Err(ConfigError::new("threshold", "Must be between 0 and 1").into());
+            }
+            EntryTrigger::Liquidity { min_liquidity } if min_liquidity <= 0.0 => {
+                return Err(ConfigError::new("min_liquidity", "Must be positive").into());
+            }
+            _ => {}
+        }
+        if size.is_some_and(|s| s < 1.0) {
+            return Err(ConfigError::new("size", "Must be at least $1").into());
+        }
+        if expires_at.is_some_and(|t| t <= now) {
+            return Err(ConfigError::new("expires_at", "Must be in the future").into());
+        }
+
+        let prediction = self.prediction_cache.get(market_id)
+            .map(|c| c.prediction.clone())
+            .ok_or_else(|| NotFound(format!("No signal for market {}; analyze it first", market_id)))?;
+        let client = self.polymarket.as_ref().ok_or(NotConfigured("Polymarket client"))?;
+        let market = client.get_market(market_id).await?
+            .ok_or_else(|| NotFound(format!("Market {} not found", market_id)))?;
+
+        let order = ConditionalOrder {
+            id: Uuid::new_v4().to_string(),
+            market_id: market.id.clone(),
+            market_name: market.question.clone(),
+            last_price: outcome_price(&market, &prediction.predicted_outcome),
+            outcome: prediction.predicted_outcome,
+            trigger,
+            size,
+            expires_at,
+            created_at: now,
+            status: ConditionalStatus::Parked,
+            last_checked: Some(now),
+            closed_at: None,
+        };
+        self.add_message(conditional_message(Message::new("conditional.parked"), &order), ActivityType::Info);
+        self.conditional_orders.push(order.clone());
+        Ok(order)
+    }
+
+    pub fn cancel_conditional_order(&mut self, order_id: &str) -> Result<()> {
+        let order = self.conditional_orders.iter_mut()
+            .find(|o| o.id == order_id)
+            .ok_or_else(|| NotFound(format!("Conditional order {} not found", order_id)))?;
+        if !order.is_parked() {
+            anyhow::bail!("Conditional order {} is no longer parked", order_id);
+        }
+        order.close(ConditionalStatus::Cancelled, Utc::now());
+        let msg = conditional_message(Message::new("conditional.cancelled"), order);
+        self.add_message(msg, ActivityType::Info);
+        Ok(())
+    }
+
+    pub fn get_conditional_orders(&self) -> Vec<ConditionalOrder> {
+        self.conditional_orders.clone()
+    }
+
+    /// Expire stale conditional orders, then trade the ones whose trigger holds.
+    /// Triggered signals still go through the edge check, risk limits and execution gate.
+    pub async fn check_conditional_orders(&mut self) {
+        let now = Utc::now();
+        let mut expired = Vec::new();
+        for order in self.conditional_orders.iter_mut().filter(|o| o.is_parked() && o.is_expired(now)) {
+            order.close(ConditionalStatus::Expired, now);
+            expired.push(conditional_message(Message::new("conditional.expired"), order));
+        }
+        for msg in expired {
+            self.add_message(msg, ActivityType::Info);
+        }
+
+        let mut market_ids: Vec<String> = self.conditional_orders.iter()
+            .filter(|o| o.is_parked())
+            .map(|o| o.market_id.clone())
+            .collect();
+        market_ids.sort();
+        market_ids.dedup();
+        if market_ids.is_empty() {
+            return;
+        }
+
+        let Some(ref client) = self.polymarket else { return };
+        let mut markets = HashMap::new();
+        for market_id in market_ids {
+            if let Ok(Some(market)) = client.get_market(&market_id).await {
+                markets.insert(market_id, market);
+            }
+        }
+
+        let mut triggered = Vec::new();
+        for order in self.conditional_orders.iter_mut().filter(|o| o.is_parked()) {
+            let Some(market) = markets.get(&order.market_id) else { continue };
+            let price = outcome_price(market, &order.outcome);
+            if order.is_triggered(market, price, now) {
+                triggered.push(order.id.clone());
+            }
+            if price.is_some() {
+                order.last_price = price;
+            }
+            order.last_checked = Some(now);
+        }
+
+        for id in triggered {
+            let Some(order) = self.conditional_orders.iter().find(|o| o.id == id).cloned() else { continue };
+            let Some(market) = markets.get(&order.market_id) else { continue };
+            let order_id = self.execute_conditional(&order, market).await;
+            if let Some(parked) = self.conditional_orders.iter_mut().find(|o| o.id == id) {
+                parked.close(ConditionalStatus::Triggered { order_id }, now);
+            }
+        }
+    }
+
+    /// Trade a triggered conditional order from its market's latest signal, re-checking the
+    /// edge at the current price. Nothing is placed while auto trading is off. Returns the id
+    /// of the order placed, if any.
+    async fn execute_conditional(&mut self, order: &ConditionalOrder, market: &Market) -> Option<String> {
+        let msg = conditional_message(Message::new("conditional.triggered"), order);
+        self.add_message(msg, ActivityType::Edge);
+        if !self.config.auto_trading {
+            self.add_message(conditional_message(Message::new("conditional.manual"), order), ActivityType::Warning);
+            return None;
+        }
+
+        let mut prediction = self.prediction_cache.get(&order.market_id)?.prediction.clone();
+        if let Some(price) = order.last_price {
+            prediction.edge = prediction.fair_price - price;
+        }
+        if prediction.predicted_outcome != order.outcome || prediction.edge <= 0.0 {
+            let msg = conditional_message(Message::new("conditional.no_edge"), order)
+                .arg("edge", format!("{:+.2}", prediction.edge));
+            self.add_message(msg, ActivityType::Warning);
+            return None;
+        }
+
+        let free_capital = self.free_capital();
+        let size = order.size
+            .unwrap_or(prediction.recommended_size * free_capital)
+            .min(self.config.max_bet_size)
+            .min(free_capital);
+        if size <= 1.0 {
+            self.tally("tally.no_capital");
+            return None;
+        }
+        let size = self.apply_risk_limits(market, &prediction, size)?;
+
+        let placed_before = self.orders.len();
+        self.place_order(market, &prediction, size).await;
+        self.orders.get(placed_before).map(|o| o.id.clone())
+    }
+
+    /// Check the currency of record against USD at most every `PEG_CHECK_SECS`, warning when
+    /// it leaves the peg and again when it returns. Runs on the alert timer. Failed checks
+    /// are throttled the same way, and show in health once the last status goes stale.
+    pub async fn check_peg(&mut self) {
+        let now = Utc::now();
+        if self.peg_attempted_at.is_some_and(|at| (now - at).num_seconds() < currency::PEG_CHECK_SECS) {
+            return;
+        }
+        let Some(ref feed) = self.price_feed else { return };
+        self.peg_attempted_at = Some(now);
+        let config = self.config.currency.clone();
+        let fetched = feed.usd_price(config.currency).await;
+        // Already throttled, so the feed never needs its circuit opened
+        self.peg_calls.record(&fetched, &CircuitBreakerConfig { failure_threshold: 0, ..Default::default() });
+        let price = match fetched {
+            Ok(price) => price,
+            Err(e) => {
+                log::warn!("{} price check failed: {:#}", config.currency.symbol(), e);
+                return;
+            }
+        };
+
+        let status = PegStatus::new(&config, price, now);
+        let was_depegged = self.peg.as_ref().is_some_and(|p| p.depegged);
+        let msg = Message::new(if status.depegged { "peg.lost" } else { "peg.restored" })
+            .arg("currency", config.currency.symbol())
+            .arg("price", format!("{:.4}", price))
+            .arg("threshold", format!("{:.2}", config.depeg_threshold));
+        if status.depegged && !was_depegged {
+            let msg = if status.paused { msg.arg("action", "new entries paused") } else { msg.arg("action", "trading continues") };
+            self.notifications.notify(NotificationKind::RiskHalt, "Stablecoin depeg", &msg.render(self.locale));
+            self.add_message(msg, ActivityType::Warning);
+        } else if !status.depegged && was_depegged {
+            self.add_message(msg, ActivityType::Info);
+        }
+        self.peg = Some(status);
+    }
+
+    pub fn get_peg_status(&self) -> Option<PegStatus> {
+        self.peg.clone()
+    }
+
+    /// Price the Yes and No baskets of each scanned neg-risk event across all of its
+    /// outcomes and buy the better one when it clears `min_edge`
+    async fn run_neg_risk(&mut self, scanned: &[Market]) {
+        let config = self.config.neg_risk.clone();
+        let event_ids: Vec<String> = negrisk::scanned_events(scanned)
+            .into_iter()
+            .filter(|id| !self.neg_risk_events.contains(id))
+            .take(config.max_events as usize)
+            .collect();
+
+        for event_id in event_ids {
+            if !self.polymarket_calls.allows() {
+                return;
+            }
+            let Some(ref client) = self.polymarket else { return };
+            let fetched = client.get_event_markets(&event_id).await;
+            self.polymarket_calls.record(&fetched, &self.config.circuit_breaker);
+            let Ok((title, markets)) = fetched else { continue };
+            let Some(event) = NegRiskEvent::new(&event_id, title, markets) else { continue };
+
+            let mut yes_asks = Vec::with_capacity(event.markets.len());
+            let mut no_asks = Vec::with_capacity(event.markets.len());
+            for market in &event.markets {
+                yes_asks.push(self.best_ask(market, "Yes").await);
+                no_asks.push(self.best_ask(market, "No").await);
+            }
+            let Some(basket) = negrisk::find_opportunity(&event, &yes_asks, &no_asks, &config, Utc::now()) else {
+                continue;
+            };
+
+            let free = self.free_capital();
+            if basket.cost() > free {
+                let msg = Message::new("negrisk.no_capital")
+                    .arg("event", truncate_str(&event.title, 40))
+                    .arg("cost", format!("${:.2}", basket.cost()))
+                    .arg("free", format!("${:.2}", free));
+                self.add_message_at(msg, ActivityType::Warning, LogLevel::Debug);
+                continue;
+            }
+            self.buy_basket(&event, basket);
+        }
+    }
+
+    async fn best_ask(&self, market: &Market, outcome: &str) -> Option<BookLevel> {
+        let book = self.outcome_book(market, outcome).await?;
+        book.asks.into_iter().min_by(|a, b| a.price.total_cmp(&b.price))
+    }
+
+    /// Paper-fill every leg at its best ask. A No set is converted on the spot, so its
+    /// legs settle immediately; a Yes set is held and its legs resolve like any order.
+    /// Legs are exempt from the entry price band: the set pays out whichever leg wins, so a
+    /// leg's own price says nothing about the risk, and a band would break up the set.
+    fn buy_basket(&mut self, event: &NegRiskEvent, mut basket: NegRiskOpportunity) {
+        let now = Utc::now();
+        let converted = basket.kind == BasketKind::NoConversion;
+        let proceeds = basket.payout_per_set * basket.sets;
+        let share_of_proceeds = proceeds / basket.legs.len() as f64;
+
+        for (leg, market) in basket.legs.iter().zip(&event.markets) {
+            let stake = leg.price * basket.sets;
+            let order = Order {
+                id: Uuid::new_v4().to_string(),
+                market_id: leg.market_id.clone(),
+                market_name: leg.question.clone(),
+                side: OrderSide::Buy,
+                outcome: basket.kind.outcome().to_string(),
+                price: leg.price,
+                size: stake,
+                status: if converted { OrderStatus::Resolved } else { OrderStatus::Filled },
+                created_at: now,
+                resolved_at: converted.then_some(now),
+                pnl: converted.then_some(share_of_proceeds - stake),
+                category: market.category.clone(),
+                parent_id: None,
+                child_ids: Vec::new(),
+                filled_size: stake,
+                fill_price: Some(leg.price),
+                resolution: None,
+                external_id: None,
+                token_id: leg.token_id.clone(),
+                clob_order_id: None,
+                replaces: None,
+                replaced_by: None,
+                exited_pnl: 0.0,
+                status_changed_at: None,
+            };
+            self.ledger.order_placed(&order.id, &order.market_id, order.price, order.size);
+            self.ledger.fill(&order.id, order.size);
+            self.orders.push(order);
+        }
+
+        let event_name = truncate_str(&event.title, 40);
+        let msg = Message::new("negrisk.bought")
+            .arg("sets", format!("{:.0}", basket.sets))
+            .arg("outcome", basket.kind.outcome())
+            .arg("event", event_name.clone())
+            .arg("legs", basket.legs.len())
+            .arg("cost", format!("${:.2}", basket.cost()))
+            .arg("payout", format!("{:.0}", basket.payout_per_set))
+            .arg("edge", format!("{:.3}", basket.edge_per_set));
+        self.notifications.notify(NotificationKind::OrderPlaced, "Order placed", &msg.render(self.locale));
+        self.add_message(msg, ActivityType::Order);
+
+        if converted {
+            basket.conversion = match (&self.polymarket, negrisk::index_set(&basket.legs)) {
+                (Some(client), Some(index_set)) => client.conversion_tx(&event.neg_risk_market_id, index_set, basket.sets).ok(),
+                _ => None,
+            };
+            let id = format!("convert:{}:{}", event.event_id, now.timestamp_millis());
+            if self.ledger.conversion(id, &event.event_id, basket.cost(), proceeds) {
+                self.apply_ledger_totals();
+                self.update_stats();
+            }
+            let msg = Message::new("negrisk.converted")
+                .arg("sets", format!("{:.0}", basket.sets))
+                .arg("event", event_name)
+                .arg("proceeds", format!("${:.2}", proceeds))
+                .arg("pnl", i18n::signed_usd(basket.profit()));
+            self.add_message(msg, ActivityType::Resolved);
+        }
+
+        self.neg_risk_events.insert(event.event_id.clone());
+        self.neg_risk_baskets.push(basket);
+        if self.neg_risk_baskets.len() > 50 {
+            self.neg_risk_baskets.remove(0);
+        }
+    }
+
+    /// Neg-risk baskets bought this session, newest first
+    pub fn get_neg_risk_baskets(&self) -> Vec<NegRiskOpportunity> {
+        self.neg_risk_baskets.iter().rev().cloned().collect()
+    }
+
+    /// Fair value of the first outcome from a cached prediction, analyzing live when
+    /// the cache has none younger than `batch_max_age_hours`
+    async fn maker_fair_value(&mut self, market: &Market) -> Option<f64> {
+        let max_age = chrono::Duration::hours(self.config.batch_max_age_hours as i64);
+        let fresh = self.prediction_cache
+            .get(&market.id)
+            .filter(|c| Utc::now() - c.analyzed_at < max_age)
+            .map(|c| c.prediction.clone());
+
+        let prediction = match fresh {
+            Some(prediction) => prediction,
+            None if !self.claude_calls.allows() => return None,
+            None => {
+                let features = self.market_features(market).await;
+                let claude = self.claude.as_mut()?;
+                let analysis = claude.analyze_market(market, Some(&features)).await;
+                self.stats.api_costs = claude.estimate_cost();
+                self.claude_calls.record(&analysis, &self.config.circuit_breaker);
+                let prediction = analysis.ok()?;
+                self.cache_prediction(&prediction, PredictionSource::Live);
+                prediction
+            }
+        };
+
+        let first = market.outcomes.first()?;
+        Some(if prediction.predicted_outcome.eq_ignore_ascii_case(first) {
+            prediction.fair_price
+        } else {
+            1.0 - prediction.fair_price
+        })
+    }
+
+    fn log_maker_event(&mut self, book: &MakerBook, event: MakerEvent) {
+        let market = truncate_str(&book.market_name, 40);
+        let inventory = format!("{:+.0}", book.inventory);
+        match event {
+            MakerEvent::Filled { side, price, size } => {
+                let side = match side {
+                    OrderSide::Buy => "BUY",
+                    OrderSide::Sell => "SELL",
+                };
+                let msg = Message::new("mm.filled")
+                    .arg("side", side)
+                    .arg("size", format!("{:.0}", size))
+                    .arg("price", format!("{:.3}", price))
+                    .arg("market", market)
+                    .arg("inventory", inventory);
+                self.add_message(msg, ActivityType::Order);
+            }
+            MakerEvent::Pulled { moved } => {
+                let msg = Message::new("mm.pulled")
+                    .arg("market", market)
+                    .arg("moved", format!("{:+.3}", moved));
+                self.add_message(msg, ActivityType::Warning);
+            }
+            MakerEvent::Requoted => {
+                let price = |q: Option<market_maker::Quote>| q.map(|q| format!("{:.3}", q.price)).unwrap_or_else(|| "-".to_string());
+                let msg = Message::new("mm.quoted")
+                    .arg("market", market)
+                    .arg("bid", price(book.bid))
+                    .arg("ask", price(book.ask))
+                    .arg("inventory", inventory);
+                self.add_message_at(msg, ActivityType::Info, LogLevel::Debug);
+            }
+        }
+    }
+
+    pub fn get_market_maker(&self) -> Vec<MakerBook> {
+        let books = self.market_maker.books();
+        if self.guest.masks_balances() { guest::mask_maker_books(books) } else { books }
+    }
+
+    /// Indicators for a market; price-based ones are skipped when the history is unavailable
+    async fn market_features(&self, market: &Market) -> MarketFeatures {
+        let history = match (&self.polymarket, market.token_ids.first()) {
+            (Some(polymarket), Some(token_id)) => polymarket.get_price_history(token_id).await.unwrap_or_default(),
+            _ => vec![],
+        };
+        MarketFeatures::compute(market, &history, Utc::now())
+    }
+
+    fn record_signal(&mut self, market: &Market, prediction: &AIPrediction) {
+        let price = market.outcomes
+            .iter()
+            .position(|o| o.eq_ignore_ascii_case(&prediction.predicted_outcome))
+            .and_then(|i| market.outcome_prices.get(i).copied())
+            .unwrap_or(0.0);
+
+        self.signals.push(Signal {
+            timestamp: Utc::now(),
+            market_id: market.id.clone(),
+            market_name: market.question.clone(),
+            category: market.category.clone(),
+            outcome: prediction.predicted_outcome.clone(),
+            price,
+            fair_price: prediction.fair_price,
+            edge: prediction.edge,
+            confidence: prediction.confidence,
+            liquidity: market.liquidity,
+            resolution: None,
+            prompt_pack: prediction.prompt_pack.clone(),
+        });
+
+        // Keep last 5000 signals
+        if self.signals.len() > 5000 {
+            self.signals = self.signals.split_off(self.signals.len() - 5000);
+        }
+    }
+
+    /// Win rate and entry prices of settled trades, with the average edge of qualifying signals
+    pub fn measured_performance(&self) -> MeasuredPerformance {
+        let settled: Vec<&Order> = self.orders.iter()
+            .filter(|o| matches!(o.status, OrderStatus::Resolved) && !o.is_parent() && o.pnl.is_some())
+            .collect();
+        let edges: Vec<f64> = self.signals.iter()
+            .map(|s| s.edge)
+            .filter(|e| *e >= self.config.min_edge_threshold)
+            .collect();
+
+        let mean = |values: &[f64]| if values.is_empty() { 0.0 } else { values.iter().sum::<f64>() / values.len() as f64 };
+        let prices: Vec<f64> = settled.iter().map(|o| o.entry_price()).collect();
+        let wins = settled.iter().filter(|o| o.pnl.unwrap_or(0.0) > 0.0).count();
+
+        MeasuredPerformance {
+            settled_trades: settled.len() as u32,
+            win_rate: if settled.is_empty() { 0.0 } else { wins as f64 / settled.len() as f64 },
+            avg_edge: mean(&edges),
+            avg_price: mean(&prices),
+        }
+    }
+
+    /// Fetch resolutions for signals whose markets have not settled yet, the least recently
+    /// checked first so every market is eventually looked up
+    pub async fn refresh_signal_resolutions(&mut self) {
+        const MAX_LOOKUPS: usize = 50;
+
+        let mut market_ids: Vec<String> = self.signals.iter()
+            .filter(|s| !s.resolution.as_ref().is_some_and(|r| r.is_settled()))
+            .map(|s| s.market_id.clone())
+            .collect();
+        market_ids.sort();
+        market_ids.dedup();
+        self.signal_checked_at.retain(|id, _| market_ids.binary_search(id).is_ok());
+        market_ids.sort_by_key(|id| self.signal_checked_at.get(id).copied());
+        market_ids.truncate(MAX_LOOKUPS);
+
+        let mut resolutions = HashMap::new();
+        if let Some(ref client) = self.polymarket {
+            for market_id in market_ids {
+                self.signal_checked_at.insert(market_id.clone(), Utc::now());
+                if let Ok(Some(resolution)) = client.get_market_resolution(&market_id).await {
+                    resolutions.insert(market_id, resolution);
+                }
+            }
+        }
+
+        for signal in self.signals.iter_mut() {
+            if let Some(resolution) = resolutions.get(&signal.market_id) {
+                signal.resolution = Some(resolution.clone());
+            }
+        }
+    }
+
+    /// Embed questions of markets not yet in the similarity index. Scanned and held markets
+    /// are marked as seen so eviction drops the ones no longer in use.
+    async fn index_markets(&mut self, markets: &[Market]) {
+        let held: Vec<String> = self.orders.iter()
+            .filter(|o| o.status == OrderStatus::Filled || o.status.is_working())
+            .map(|o| o.market_id.clone())
+            .collect();
+        for id in markets.iter().map(|m| &m.id).chain(&held) {
+            self.embeddings.touch(id);
+        }
+
+        let new: Vec<&Market> = markets.iter().filter(|m| !self.embeddings.contains(&m.id)).collect();
+        if new.is_empty() {
+            return;
+        }
+        let Some(ref embedder) = self.embedder else {
+            return;
+        };
+
+        let questions: Vec<String> = new.iter().map(|m| m.question.clone()).collect();
+        match embedder.embed(&questions).await {
+            Ok(vectors) => {
+                for (market, vector) in new.iter().zip(vectors) {
+                    if !vector.is_empty() {
+                        self.embeddings.insert(&market.id, &market.question, vector);
+                    }
+                }
+            }
+            Err(e) => {
+                self.add_message_at(Message::new("embedding.failed").arg("error", e), ActivityType::Warning, LogLevel::Debug);
+            }
+        }
+    }
+
+    fn cache_prediction(&mut self, prediction: &AIPrediction, source: PredictionSource) {
+        let cached = CachedPrediction { prediction: prediction.clone(), analyzed_at: Utc::now(), source };
+        let key = format!("{}:{}", prediction.market_id, cached.analyzed_at.timestamp_millis());
+        self.persist(Table::Predictions, &key, &db::prediction_row(&cached));
+        self.prediction_cache.insert(prediction.market_id.clone(), cached);
+    }
+
+    pub fn get_prediction_cache(&self) -> Vec<CachedPrediction> {
+        let mut cached: Vec<CachedPrediction> = self.prediction_cache.values().cloned().collect();
+        cached.sort_by_key(|c| std::cmp::Reverse(c.analyzed_at));
+        cached
+    }
+
+    /// Keep one batch in flight: poll it if pending, otherwise submit the stale deferred markets.
+    /// Batch prompts omit price-history features to avoid a history fetch per market.
+    async fn schedule_batch(&mut self, markets: &[Market], analyzed_live: &HashSet<&str>) {
+        if !self.config.batch_analysis {
+            return;
+        }
+        if self.pending_batch.is_some() {
+            self.poll_batch().await;
+            return;
+        }
+
+        let deferred = batch::select_deferred(
+            markets,
+            analyzed_live,
+            &self.prediction_cache,
+            self.config.batch_max_age_hours,
+            Utc::now(),
+        );
+        if deferred.is_empty() {
+            return;
+        }
+        let entries: Vec<(String, &Market, Option<&MarketFeatures>)> = deferred
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (format!("m{}", i), *m, None))
+            .collect();
+        if !self.claude_calls.allows() {
+            return;
+        }
+
+        let submitted = match self.claude {
+            Some(ref mut claude) => claude.submit_batch(&entries).await,
+            None => return,
+        };
+        self.claude_calls.record(&submitted, &self.config.circuit_breaker);
+
+        match submitted {
+            Ok(id) => {
+                self.pending_batch = Some(PendingBatch {
+                    id,
+                    submitted_at: Utc::now(),
+                    markets: entries.iter().map(|(cid, m, _)| (cid.clone(), (*m).clone())).collect(),
+                });
+                self.add_message(Message::new("batch.submitted").arg("count", entries.len()), ActivityType::Inference);
+            }
+            Err(e) => {
+                self.add_message(Message::new("batch.failed").arg("error", e), ActivityType::Error);
+            }
+        }
+    }
+
+    /// Merge a finished batch into the prediction cache and the signal log
+    async fn poll_batch(&mut self) {
+        if self.pending_batch.as_ref().is_some_and(|p| p.expired(Utc::now())) {
+            if let Some(pending) = self.pending_batch.take() {
+                self.add_message(Message::new("batch.expired").arg("count", pending.markets.len()), ActivityType::Warning);
+            }
+            return;
+        }
+        let (Some(claude), Some(pending)) = (self.claude.as_mut(), self.pending_batch.as_ref()) else {
+            return;
+        };
+        let status = match claude.batch_status(&pending.id).await {
+            Ok(status) => status,
+            Err(e) => {
+                self.add_message_at(Message::new("batch.failed").arg("error", e), ActivityType::Warning, LogLevel::Debug);
+                return;
+            }
+        };
+        let Some(results_url) = status.results_url.filter(|_| status.ended) else {
+            return;
+        };
+
+        let Some(pending) = self.pending_batch.take() else { return };
+        let Some(ref mut claude) = self.claude else { return };
+        let fetched = claude.batch_results(&results_url, &pending.markets).await;
+        self.stats.api_costs = claude.estimate_cost();
+
+        match fetched {
+            Ok((results, skipped)) => {
+                for (custom_id, prediction) in &results {
+                    if let Some(market) = pending.markets.get(custom_id) {
+                        self.record_signal(market, prediction);
+                    }
+                    self.cache_prediction(prediction, PredictionSource::Batch);
+                }
+                self.add_message(Message::new("batch.merged").arg("count", results.len()), ActivityType::Inference);
+                if skipped > 0 {
+                    self.add_message(Message::new("batch.skipped").arg("count", skipped), ActivityType::Warning);
+                }
+            }
+            Err(e) => {
+                self.add_message(Message::new("batch.failed").arg("error", e), ActivityType::Error);
+            }
+        }
+    }
+
+    /// Drop candidates that are near-identical to one already selected this cycle
+    fn dedupe_candidates<'m>(&mut self, candidates: Vec<&'m Market>) -> Vec<&'m Market> {
+        let threshold = self.config.duplicate_similarity as f32;
+        let mut selected: Vec<&Market> = Vec::new();
+
+        for market in candidates {
+            let duplicate = self.embeddings.get(&market.id).is_some_and(|v| {
+                selected.iter().any(|s| {
+                    self.embeddings.get(&s.id).is_some_and(|o| embeddings::cosine(v, o) >= threshold)
+                })
+            });
+            if duplicate {
+                self.tally("tally.near_duplicate");
+            } else {
+                selected.push(market);
+            }
+        }
+        selected
+    }
+
+    /// Markets most similar to `query`, which may be a known market id or free text
+    pub async fn find_similar_markets(&self, query: &str, limit: usize) -> Result<Vec<SimilarMarket>> {
+        if let Some(vector) = self.embeddings.get(query) {
+            return Ok(self.embeddings.most_similar(vector, Some(query), limit));
+        }
+
+        let embedder = self.embedder.as_ref().ok_or(NotConfigured("Embeddings"))?;
+        let vector = embedder.embed(&[query.to_string()]).await?.pop().unwrap_or_default();
+        Ok(self.embeddings.most_similar(&vector, None, limit))
+    }
+
+    /// Generate the periodic AI analyst report once the configured interval has elapsed
+    async fn maybe_generate_commentary(&mut self, markets: &[Market]) {
+        let interval = self.config.commentary_interval_secs as i64;
+        if interval == 0 {
+            return;
+        }
+
+        if let Some(last) = self.last_commentary_attempt {
+            if (Utc::now() - last).num_seconds() < interval {
+                return;
+            }
+        }
+
+        let positions = self.portfolio.positions.clone();
+        // Imported trades are already among the wallet positions
+        let open_orders: Vec<Order> = self.orders.iter()
+            .filter(|o| matches!(o.status, OrderStatus::Filled) && !o.is_parent() && !o.is_imported())
+            .cloned()
+            .collect();
+        if !self.claude_calls.allows() {
+            return;
+        }
+
+        let result = match self.claude {
+            Some(ref mut claude) => {
+                self.last_commentary_attempt = Some(Utc::now());
+                let result = claude.generate_commentary(&positions, &open_orders, markets).await;
+                self.stats.api_costs = claude.estimate_cost();
+                result
+            }
+            None => return,
+        };
+        self.claude_calls.record(&result, &self.config.circuit_breaker);
+
+        match result {
+            Ok(commentary) => {
+                self.latest_commentary = Some(commentary);
+                self.add_message(Message::new("commentary.updated"), ActivityType::Inference);
+            }
+            Err(e) => {
+                let msg = Message::new("commentary.failed").arg("error", e);
+                self.notifications.notify(NotificationKind::ApiFailure, "Claude API failure", &msg.render(self.locale));
+                self.add_message(msg, ActivityType::Error);
+            }
+        }
+    }
+
+    /// Open orders aggregated into one position per market and outcome
+    pub fn get_market_positions(&self) -> Vec<MarketPosition> {
+        let fill_prices = self.fill_prices();
+        let positions = PositionManager::new(&self.config.risk, &self.orders, self.stats.current_balance)
+            .with_fill_prices(&fill_prices)
+            .market_positions();
+        if self.guest.masks_balances() {
+            guest::mask_market_positions(positions)
+        } else {
+            positions
+        }
+    }
+
+    /// Open positions grouped by question similarity, showing exposure that would move together
+    pub fn get_exposure_groups(&self) -> Vec<ExposureGroup> {
+        let threshold = self.config.risk.correlation_similarity as f32;
+        let groups = PositionManager::new(&self.config.risk, &self.orders, self.stats.current_balance)
+            .exposure_groups(|a, b| self.embeddings.similar(a, b, threshold));
+        if self.guest.masks_balances() {
+            guest::mask_exposure_groups(groups)
+        } else {
+            groups
+        }
+    }
+
+    /// Collateral available for sizing new trades
+    fn free_capital(&self) -> f64 {
+        PositionManager::new(&self.config.risk, &self.orders, self.stats.current_balance).free_capital()
+    }
+
+    /// Run a proposed order through the position manager, returning the size allowed (if any):
+    /// first the market's add-on rules and limit, then the category cap, then the cap on
+    /// markets correlated with this one
+    fn apply_risk_limits(&mut self, market: &Market, prediction: &AIPrediction, size: f64) -> Option<f64> {
+        let manager = PositionManager::new(&self.config.risk, &self.orders, self.stats.current_balance);
+        let category = market.category.as_deref();
+        let threshold = self.config.risk.correlation_similarity as f32;
+        // The add-on price is checked in `place_order`, once the entry price is known
+        let decision = manager
+            .check_market_position(&market.id, &prediction.predicted_outcome, size)
+            .and_then(|size| manager.check_category_cap(category, size))
+            .and_then(|size| {
+                manager.check_correlated_exposure(|id| self.embeddings.similar(&market.id, id, threshold), size)
+            });
+        match decision {
+            SizeDecision::Approved(size) => Some(size),
+            SizeDecision::Downsized { size, reason } => {
+                let msg = Message::new("risk.downsized").arg("size", format!("{:.2}", size)).arg("reason", reason);
+                self.add_message(msg, ActivityType::Warning);
+                Some(size)
+            }
+            SizeDecision::Rejected(reason) => {
+                self.risk_skipped(market, reason);
+                None
+            }
+        }
+    }
+
+    fn risk_skipped(&mut self, market: &Market, reason: String) {
+        let msg = Message::new("risk.skipped")
+            .arg("market", truncate_str(&market.question, 40))
+            .arg("reason", reason);
+        self.notifications.notify(NotificationKind::RiskHalt, "Trade blocked by risk limits", &msg.render(self.locale));
+        self.add_message(msg, ActivityType::Warning);
+    }
+
+    /// Fill price of each order with an execution report that has one
+    fn fill_prices(&self) -> HashMap<String, f64> {
+        self.execution_reports
+            .iter()
+            .filter_map(|(id, report)| report.fill_price.map(|price| (id.clone(), price)))
+            .collect()
+    }
+
+    /// Place a simulated order, slicing it TWAP-style when it exceeds top-of-book depth.
+    /// Returns false when a check held the order back.
+    async fn place_order(&mut self, market: &Market, prediction: &AIPrediction, size: f64) -> bool {
+        if self.peg.as_ref().is_some_and(|p| p.paused) {
+            self.tally("tally.peg_paused");
+            return false;
+        }
+        let normalized = match execution::normalize_order(
+            prediction.fair_price,
+            size,
+            &OrderSide::Buy,
+            market.tick_size,
+            market.min_order_size,
+        ) {
+            Ok(normalized) => normalized,
+            Err(reason) => {
+                let msg = Message::new("order.below_minimum")
+                    .arg("market", truncate_str(&market.question, 40))
+                    .arg("reason", reason);
+                self.add_message_at(msg, ActivityType::Warning, LogLevel::Debug);
+                self.tally("tally.below_minimum");
+                return false;
+            }
+        };
+        let size = normalized.notional;
+
+        let book = self.outcome_book(market, &prediction.predicted_outcome).await;
+        let entry = book.as_ref()
+            .and_then(|b| b.asks.iter().map(|l| l.price).min_by(|a, b| a.total_cmp(b)))
+            .or_else(|| outcome_price(market, &prediction.predicted_outcome));
+        if let Some(entry) = entry {
+            if let Err(reason) = execution::check_price_band(entry, self.config.min_entry_price, self.config.max_entry_price) {
+                let msg = Message::new("order.price_band")
+                    .arg("market", truncate_str(&market.question, 40))
+                    .arg("reason", reason);
+                self.add_message_at(msg, ActivityType::Warning, LogLevel::Debug);
+                self.tally("tally.price_band");
+                return false;
+            }
+        }
+        let guarded_price = entry.unwrap_or(normalized.price);
+        if let Err(reason) = execution::check_side_guardrails(
+            &self.config.side_guardrails,
+            market.category.as_deref(),
+            &prediction.predicted_outcome,
+            guarded_price,
+        ) {
+            let msg = Message::new("order.guardrail")
+                .arg("market", truncate_str(&market.question, 40))
+                .arg("reason", reason);
+            self.add_message_at(msg, ActivityType::Warning, LogLevel::Debug);
+            self.tally("tally.guardrail");
+            return false;
+        }
+
+        let fill_prices = self.fill_prices();
+        let manager = PositionManager::new(&self.config.risk, &self.orders, self.stats.current_balance)
+            .with_fill_prices(&fill_prices);
+        let add_on = manager.check_add_on_price(&market.id, &prediction.predicted_outcome, guarded_price);
+        let preflight = manager.preflight(market.category.as_deref(), size);
+        if let Err(reason) = add_on {
+            self.risk_skipped(market, reason);
+            return false;
+        }
+        if preflight.blocked() {
+            let reason = format!(
+                "worst-case loss ${:.2} is {:.0}% of equity, limit {:.0}%",
+                preflight.worst_case_loss,
+                preflight.worst_case_fraction * 100.0,
+                preflight.limit.unwrap_or_default() * 100.0
+            );
+            self.risk_skipped(market, reason);
+            return false;
+        }
+
+        let signals = match book {
+            Some(ref book) => Some(self.microstructure(market, &prediction.predicted_outcome, book).await),
+            None => None,
+        };
+        if let Some(ref signals) = signals {
+            let min_edge = self.config.min_edge_threshold;
+            if signals.veto_buy(prediction.edge, min_edge, self.config.max_offer_pressure) {
+                let msg = Message::new("order.offer_pressure")
+                    .arg("market", truncate_str(&market.question, 40))
+                    .arg("pressure", format!("{:+.2}", signals.offer_pressure()));
+                self.add_message(msg, ActivityType::Warning);
+                return false;
+            }
+        }
+
+        let depth = book.as_ref().map(|b| execution::top_of_book_depth(b, &OrderSide::Buy));
+        let mut order = self.simulate_order(market, prediction, size);
+        order.price = normalized.price;
+        let mut report = ExecutionReport::new(&order, prediction, book.as_ref());
+        report.microstructure = signals;
+        report.preflight = Some(preflight);
+        self.execution_reports.insert(order.id.clone(), report);
+
+        self.ledger.order_placed(&order.id, &order.market_id, order.price, order.size);
+
+        // Every slice must still meet the exchange minimum on its own
+        let max_slices = self.config.max_order_slices
+            .min((normalized.shares / market.min_order_size).floor() as u32);
+
+        let queue_model = self.config.paper_fills.queue_model;
+        let token_id = outcome_token(market, &prediction.predicted_outcome);
+        match (depth, book.as_ref(), token_id) {
+            (Some(depth), _, _) if !queue_model && max_slices > 1 && size > depth => {
+                // Each slice takes at most the top of the book, so it fills at what one slice sweeps
+                let slice_price = book.as_ref()
+                    .map(|b| fills::sweep(b, &order.side, order.price, size / max_slices as f64))
+                    .filter(|&(_, shares)| shares > 0.0)
+                    .map_or(order.price, |(spent, shares)| spent / shares);
+                let slices = execution::plan_slices(
+                    &order.id,
+                    size,
+                    depth,
+                    max_slices,
+                    self.config.slice_interval_secs,
+                    slice_price,
+                    Utc::now(),
+                );
+                let order_msg = Message::new("order.sliced")
+                    .arg("size", format!("{:.2}", size))
+                    .arg("market", truncate_str(&market.question, 40))
+                    .arg("slices", slices.len())
+                    .arg("slice_size", format!("{:.2}", slices[0].size))
+                    .arg("depth", format!("{:.2}", depth));
+                self.orders.push(Order {
+                    status: OrderStatus::Open,
+                    filled_size: 0.0,
+                    child_ids: slices.iter().map(|s| s.child_id.clone()).collect(),
+                    ..order
+                });
+                self.scheduled_slices.extend(slices);
+                self.notifications.notify(NotificationKind::OrderPlaced, "Order placed", &order_msg.render(self.locale));
+                self.add_message(order_msg, ActivityType::Order);
+
+                // First slice goes out right away
+                self.process_due_slices();
+            }
+            (_, Some(book), Some(token_id)) if queue_model => {
+                self.rest_paper_order(order, token_id, book);
+            }
+            _ => {
+                // Without a book, the quoted price when it beats the limit
+                let price = match (book.as_ref(), entry) {
+                    (Some(book), _) => fills::average_fill_price(&order, book),
+                    (None, Some(entry)) => entry.min(order.price),
+                    (None, None) => order.price,
+                };
+                self.fill_paper_order(order, price);
+            }
+        }
+        true
+    }
+
+    /// Fill a paper order in full at once at an average of `price`, as when there is no book
+    /// to queue it against
+    fn fill_paper_order(&mut self, mut order: Order, price: f64) {
+        order.status = OrderStatus::Filled;
+        order.filled_size = order.size;
+        order.fill_price = Some(price);
+        let order_msg = Message::new("order.placed")
+            .arg("size", format!("{:.2}", order.size))
+            .arg("market", truncate_str(&order.market_name, 40));
+        self.notifications.notify(NotificationKind::OrderPlaced, "Order placed", &order_msg.render(self.locale));
+        self.add_message(order_msg, ActivityType::Order);
+        self.ledger.fill(&order.id, order.size);
+        self.orders.push(order);
+    }
+
+    /// Orderbook of the outcome's token, if it can be fetched
+    async fn outcome_book(&self, market: &Market, outcome: &str) -> Option<OrderBook> {
+        if !self.polymarket_calls.allows() {
+            return None;
+        }
+        let token_id = outcome_token(market, outcome)?;
+        self.polymarket.as_ref()?.get_orderbook(token_id).await.ok()
+    }
+
+    /// Book and tape signals for the outcome token; the tape is optional
+    async fn microstructure(&self, market: &Market, outcome: &str, book: &OrderBook) -> MicrostructureSignals {
+        let trades = match (&self.polymarket, outcome_token(market, outcome)) {
+            (Some(client), Some(token_id)) => client
+                .get_market_trades(&market.id, 100)
+                .await
+                .map(|trades| trades.into_iter().filter(|t| t.asset == token_id).collect::<Vec<_>>())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        MicrostructureSignals::compute(book, &trades)
+    }
+
+    /// Structured health of the APIs, the scan loop and supporting subsystems
+    pub fn get_health(&self) -> HealthStatus {
+        let polymarket = self.polymarket_calls.health(self.polymarket.is_some(), "Polymarket");
+        let claude_rate_limits = self.claude.as_ref().map(|c| c.rate_limits().clone());
+        let mut claude = self.claude_calls.health(self.claude.is_some(), "Claude");
+        if let Some(ref limits) = claude_rate_limits {
+            claude = health::with_rate_limits(claude, limits, Utc::now());
+        }
+        let websocket = health::websocket_health();
+        let cycle = health::cycle_health(self.is_running, self.last_cycle, self.config.scan_interval_secs);
+        let persistence = health::persistence_health(self.db.as_ref(), self.db_error.as_deref());
+        let peg = health::peg_health(&self.peg_calls, self.peg.as_ref(), self.price_feed.is_some());
+
+        let hour_ago = Utc::now() - chrono::Duration::hours(1);
+        HealthStatus {
+            overall: health::overall(&[&polymarket, &claude, &websocket, &cycle, &persistence, &peg]),
+            polymarket,
+            claude,
+            websocket,
+            cycle,
+            persistence,
+            peg,
+            recent_errors: self.activity_log.iter()
+                .filter(|e| matches!(e.entry_type, ActivityType::Error) && e.timestamp >= hour_ago)
+                .count() as u32,
+            pending_slices: self.scheduled_slices.len() as u32,
+            claude_rate_limits,
+            checked_at: Utc::now(),
+        }
+    }
+
+    /// Fetch one page of markets and time parsing it `rounds` times, typed and through a
+    /// `Value` tree
+    pub async fn benchmark_market_parsing(&self, rounds: u32) -> Result<ParseBenchmark> {
+        let client = self.polymarket.as_ref().ok_or(NotConfigured("Polymarket client"))?;
+        let body = client.get_markets_body(500, 0).await?;
+        gamma::benchmark(&body, rounds)
+    }
+
+    /// Connectivity, credential, clock, disk and RPC checks for the current configuration.
+    /// Authenticated checks use read-only calls, so this is safe to run at any time.
+    pub async fn run_diagnostics(&self) -> DiagnosticsReport {
+        let mut checks = Vec::new();
+
+        checks.push(match config::validate(&self.config) {
+            Ok(()) => DiagnosticCheck::passed("Configuration", "Valid"),
+            Err(errors) => {
+                let fields: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                DiagnosticCheck::failed("Configuration", fields.join("; "))
+            }
+        });
+
+        match self.polymarket {
+            Some(ref client) => {
+                let started = std::time::Instant::now();
+                let markets = client.get_markets(1, 0).await
+                    .map(|m| format!("Reachable, {} market(s) listed", m.len()));
+                checks.push(DiagnosticCheck::from_result("Gamma API", started, markets));
+
+                let started = std::time::Instant::now();
+                let sent_at = Utc::now();
+                match client.server_time().await {
+                    Ok(server_time) => {
+                        checks.push(DiagnosticCheck::from_result("CLOB", started, Ok("Reachable".to_string())));
+                        checks.push(diagnostics::clock_skew_check(server_time, sent_at, Utc::now()));
+                    }
+                    Err(e) => {
+                        checks.push(DiagnosticCheck::from_result("CLOB", started, Err(e)));
+                        checks.push(DiagnosticCheck::skipped("Clock skew", "CLOB unreachable"));
+                    }
+                }
+
+                if client.is_configured() {
+                    let started = std::time::Instant::now();
+                    let valid = client.check_credentials().await.map(|_| "Authenticated".to_string());
+                    checks.push(DiagnosticCheck::from_result("CLOB credentials", started, valid));
+                } else {
+                    checks.push(DiagnosticCheck::skipped("CLOB credentials", "API key, secret, passphrase or wallet address not entered"));
+                }
+            }
+            None => {
+                for name in ["Gamma API", "CLOB", "Clock skew", "CLOB credentials"] {
+                    checks.push(DiagnosticCheck::failed(name, "Polymarket client not configured"));
+                }
+            }
+        }
+
+        match self.claude {
+            Some(ref claude) if claude.is_configured() => {
+                let started = std::time::Instant::now();
+                let valid = claude.check_credentials().await.map(|_| "API key accepted".to_string());
+                checks.push(DiagnosticCheck::from_result("Anthropic credentials", started, valid));
+            }
+            _ => checks.push(DiagnosticCheck::skipped("Anthropic credentials", "No API key entered")),
+        }
+
+        match self.chain {
+            Some(ref chain) => {
+                let started = std::time::Instant::now();
+                let block = chain.block_number().await.map(|b| format!("Block {}", b));
+                checks.push(DiagnosticCheck::from_result("Polygon RPC", started, block));
+            }
+            None => checks.push(DiagnosticCheck::failed("Polygon RPC", "Chain client not configured")),
+        }
+
+        match self.db {
+            Some(ref db) => {
+                let started = std::time::Instant::now();
+                let writable = db.check_writable().map(|_| format!("Writable: {}", db.path().display()));
+                checks.push(DiagnosticCheck::from_result("Disk", started, writable));
+            }
+            None => checks.push(DiagnosticCheck::warned("Disk", "History database unavailable; nothing is written to disk")),
+        }
+
+        DiagnosticsReport::new(checks)
+    }
+
+    /// Files for a bug-report bundle: version info, the config with secrets redacted, the
+    /// structured activity log, health, and the last `cycles` cycle reports
+    pub fn diagnostics_bundle(&self, cycles: usize) -> Result<Vec<BundleFile>> {
+        let version = serde_json::json!({
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+            "environment": self.config.environment,
+            "generated_at": Utc::now(),
+            "running": self.is_running,
+            "cycles_run": self.stats.cycle,
+            "history_db": self.db.is_some(),
+            "db_error": self.db_error,
+        });
+        let mut activity = Vec::new();
+        for entry in &self.activity_log {
+            serde_json::to_writer(&mut activity, entry)?;
+            activity.push(b'\n');
+        }
+        let recent: Vec<&CycleReport> = self.cycle_reports.iter().rev().take(cycles).rev().collect();
+        Ok(vec![
+            BundleFile::json("version.json", &version)?,
+            BundleFile::json("config.json", &guest::redact_config(&self.config))?,
+            BundleFile { name: "activity.jsonl".to_string(), contents: activity },
+            BundleFile::json("health.json", &self.get_health())?,
+            BundleFile::json("cycles.json", &recent)?,
+        ])
+    }
+
+    /// Persistent state as a versioned archive, credentials left out
+    pub fn export_state(&self) -> StateArchive {
+        StateArchive {
+            version: STATE_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: Utc::now(),
+            config: archive::without_secrets(&self.config),
+            stats: self.stats.clone(),
+            ledger: self.ledger.clone(),
+            orders: self.orders.clone(),
+            balance_history: self.balance_history.clone(),
+            predictions: self.prediction_cache.values().cloned().collect(),
+            signals: self.signals.clone(),
+            price_alerts: self.price_alerts.clone(),
+            conditional_orders: self.conditional_orders.clone(),
+            history: self.db.as_ref().map(|db| db.export_rows()).unwrap_or_default(),
+            row_version: migrations::ROW_VERSION,
+        }
+    }
+
+    /// Replace the persistent state with an archive's. This machine's credentials are kept,
+    /// and the bot must be stopped so no cycle works on orders being swapped out.
+    pub fn import_state(&mut self, archive: StateArchive) -> Result<()> {
+        if self.is_running {
+            anyhow::bail!("Stop the bot before importing state");
+        }
+        let config = archive::with_secrets_of(archive.config, &self.config);
+        self.configure(config)
+            .map_err(|mut errors| anyhow::Error::from(errors.remove(0)).context("Archived configuration is invalid"))?;
+
+        self.stats = archive.stats;
+        self.ledger = archive.ledger;
+        self.orders = archive.orders;
+        self.balance_history = archive.balance_history;
+        self.prediction_cache = archive.predictions
+            .into_iter()
+            .map(|cached| (cached.prediction.market_id.clone(), cached))
+            .collect();
+        self.signals = archive.signals;
+        self.price_alerts = archive.price_alerts;
+        self.conditional_orders = archive.conditional_orders;
+        let reviews = archive.history.iter()
+            .filter(|stored| stored.table == Table::Reviews)
+            .filter_map(|stored| serde_json::from_value(stored.row.clone()).ok())
+            .collect();
+        self.set_thesis_history(reviews);
+        // Working state of the previous orders means nothing for the imported ones, and
+        // nothing will work the archived orders that were still open
+        self.scheduled_slices.clear();
+        self.resting_orders.clear();
+        self.execution_reports.clear();
+        self.order_transitions.clear();
+        self.marks.clear();
+        self.close_orphaned_orders();
+        self.apply_ledger_totals();
+        self.update_stats();
+
+        if let Some(ref mut db) = self.db {
+            let mut written = Ok(());
+            for stored in &archive.history {
+                written = written.and(db.upsert(stored.table, &stored.key, &stored.row).map(|_| ()));
+            }
+            self.record_db_write(written);
+        }
+        self.persist_history();
+
+        let msg = Message::new("state.imported")
+            .arg("orders", self.orders.len())
+            .arg("events", self.ledger.events().len())
+            .arg("exported_at", archive.exported_at.format("%Y-%m-%d %H:%M").to_string());
+        self.add_message(msg, ActivityType::Info);
+        Ok(())
+    }
+
+    pub fn get_execution_report(&self, order_id: &str) -> Option<ExecutionReport> {
+        let report = self.execution_reports.get(order_id).cloned();
+        if self.guest.masks_balances() { report.map(guest::mask_execution_report) } else { report }
+    }
+
+    /// Maker/taker split and spread captured or paid across every recorded fill
+    pub fn get_execution_quality(&self) -> ExecutionQuality {
+        let quality = execution::execution_quality(self.execution_reports.values());
+        if self.guest.masks_balances() { guest::mask_execution_quality(quality) } else { quality }
+    }
+
+    /// Put a paper order on the simulated book: what crosses the book fills now at the
+    /// levels' prices, the rest waits at the limit behind the size already queued there
+    fn rest_paper_order(&mut self, mut order: Order, token_id: &str, book: &OrderBook) {
+        let now = Utc::now();
+        let (spent, shares, resting) = fills::place(&order, token_id, book, &self.config.paper_fills, now);
+        let filled = if resting.is_some() { spent.min(order.size) } else { order.size };
+        let price = if shares > 0.0 { filled / shares } else { order.price };
+        order.status = OrderStatus::Open;
+        order.filled_size = 0.0;
+        let change = if filled > 0.0 { lifecycle::fill(&mut order, filled, price, now) } else { Ok(None) };
+
+        let order_msg = match resting {
+            Some(ref resting) => {
+                if filled > 0.0 {
+                    self.ledger.partial_fill(&order.id, 1, filled);
+                }
+                Message::new("order.resting")
+                    .arg("size", format!("{:.2}", order.size))
+                    .arg("market", truncate_str(&order.market_name, 40))
+                    .arg("price", format!("{:.3}", order.price))
+                    .arg("filled", format!("{:.2}", filled))
+                    .arg("queue", format!("{:.0}", resting.queue_ahead))
+            }
+            None => {
+                self.ledger.fill(&order.id, order.size);
+                Message::new("order.placed")
+                    .arg("size", format!("{:.2}", order.size))
+                    .arg("market", truncate_str(&order.market_name, 40))
+            }
+        };
+        self.notifications.notify(NotificationKind::OrderPlaced, "Order placed", &order_msg.render(self.locale));
+        self.add_message(order_msg, ActivityType::Order);
+        self.orders.push(order);
+        if let Ok(Some(change)) = change {
+            self.record_transition(Ok(change));
+        }
+        self.resting_orders.extend(resting);
+    }
+
+    /// Fill resting paper orders from the trades printed and the book since the last cycle,
+    /// and cancel remainders that outlived `paper_fills.max_rest_secs`
+    async fn process_paper_fills(&mut self) {
+        if self.resting_orders.is_empty() {
+            return;
+        }
+        let now = Utc::now();
+        let mut trades: HashMap<String, Vec<MarketTrade>> = HashMap::new();
+        let mut books: HashMap<String, OrderBook> = HashMap::new();
+        if let Some(client) = self.polymarket.as_ref().filter(|_| self.polymarket_calls.allows()) {
+            for resting in &self.resting_orders {
+                if !trades.contains_key(&resting.market_id) {
+                    if let Ok(market_trades) = client.get_market_trades(&resting.market_id, 200).await {
+                        trades.insert(resting.market_id.clone(), market_trades);
+                    }
+                }
+                if !books.contains_key(&resting.token_id) {
+                    if let Ok(book) = client.get_orderbook(&resting.token_id).await {
+                        books.insert(resting.token_id.clone(), book);
+                    }
+                }
+            }
+        }
+
+        for mut resting in std::mem::take(&mut self.resting_orders) {
+            // Cancelled or amended away in the meantime
+            let Some(index) = self.orders.iter().position(|o| o.id == resting.order_id && o.status.is_working()) else {
+                continue;
+            };
+
+            let market_trades = trades.get(&resting.market_id).map(Vec::as_slice).unwrap_or_default();
+            let shares = resting.match_flow(market_trades, books.get(&resting.token_id));
+            if shares > 0.0 {
+                let order = &mut self.orders[index];
+                let unfilled = order.size - order.filled_size;
+                let size = if resting.remaining <= 1e-9 { unfilled } else { (shares * resting.price).min(unfilled) };
+                match lifecycle::fill(order, size, resting.price, now) {
+                    Ok(change) => {
+                        let msg = Message::new("order.paper_fill")
+                            .arg("size", format!("{:.2}", size))
+                            .arg("market", truncate_str(&order.market_name, 40))
+                            .arg("filled", format!("{:.2}", order.filled_size))
+                            .arg("total", format!("{:.2}", order.size));
+                        self.ledger.partial_fill(&resting.order_id, resting.fills, size);
+                        if let Some(change) = change {
+                            self.record_transition(Ok(change));
+                        }
+                        self.add_message(msg, ActivityType::Order);
+                    }
+                    Err(illegal) => {
+                        self.record_transition(Err(illegal));
+                        continue;
+                    }
+                }
+            }
+
+            if self.orders[index].status == OrderStatus::Filled {
+                continue;
+            }
+            if resting.is_expired(now) {
+                self.expire_resting_order(index);
+            } else {
+                self.resting_orders.push(resting);
+            }
+        }
+    }
+
+    /// A partly filled order keeps what filled and completes at that size; an unfilled one is cancelled
+    fn expire_resting_order(&mut self, index: usize) {
+        let order = &self.orders[index];
+        let market = truncate_str(&order.market_name, 40);
+        let msg = if order.filled_size > 0.0 {
+            Message::new("order.rest_expired")
+                .arg("market", market)
+                .arg("filled", format!("{:.2}", order.filled_size))
+                .arg("total", format!("{:.2}", order.size))
+        } else {
+            Message::new("order.rest_cancelled").arg("market", market)
+        };
+        self.close_remainder(index);
+        self.add_message(msg, ActivityType::Order);
+    }
+
+    /// Stop working what is left of an order: a partly filled one completes at its filled
+    /// size, an unfilled one is cancelled. The execution report was written for the full
+    /// size, so only the filled part is left counting as a fill.
+    fn close_remainder(&mut self, index: usize) {
+        let order = &mut self.orders[index];
+        if order.filled_size > 0.0 {
+            order.size = order.filled_size;
+            if let Some(report) = self.execution_reports.get_mut(&order.id) {
+                report.order_size = order.filled_size;
+            }
+            self.transition_order(index, OrderStatus::Filled);
+        } else {
+            self.execution_reports.remove(&order.id);
+            self.transition_order(index, OrderStatus::Cancelled);
+        }
+    }
+
+    /// Paper orders waiting on the simulated book
+    pub fn get_resting_orders(&self) -> Vec<RestingOrder> {
+        if self.guest.masks_balances() {
+            guest::mask_resting_orders(self.resting_orders.clone())
+        } else {
+            self.resting_orders.clone()
+        }
+    }
+
+    /// Place child orders whose scheduled time has come and roll their fills into the parent
+    fn process_due_slices(&mut self) {
+        let now = Utc::now();
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.scheduled_slices)
+            .into_iter()
+            .partition(|s| s.due_at <= now);
+        self.scheduled_slices = pending;
+
+        for slice in due {
+            let Some(parent) = self.orders.iter_mut().find(|o| o.id == slice.parent_id) else {
+                continue;
+            };
+            // A parent that is no longer working (cancelled, or restored already filled) gets no more slices
+            let filled = match lifecycle::fill(parent, slice.size, slice.price, now) {
+                Ok(change) => change,
+                Err(illegal) => {
+                    self.record_transition(Err(illegal));
+                    continue;
+                }
+            };
+
+            self.ledger.fill(&slice.child_id, slice.size);
+            let index = parent.child_ids.iter().position(|id| *id == slice.child_id).unwrap_or(0) + 1;
+            let total = parent.child_ids.len();
+
+            let child = Order {
+                id: slice.child_id,
+                parent_id: Some(parent.id.clone()),
+                child_ids: Vec::new(),
+                size: slice.size,
+                filled_size: slice.size,
+                fill_price: Some(slice.price),
+                status: OrderStatus::Filled,
+                created_at: now,
+                status_changed_at: None,
+                ..parent.clone()
+            };
+            let msg = Message::new("order.slice")
+                .arg("index", index)
+                .arg("total", total)
+                .arg("size", format!("{:.2}", child.size))
+                .arg("market", truncate_str(&child.market_name, 40));
+            self.orders.push(child);
+            if let Some(change) = filled {
+                self.record_transition(Ok(change));
+            }
+            self.add_message(msg, ActivityType::Order);
+        }
+    }
+
+    /// Keep an applied status change, or report one refused as illegal. Returns whether it applied.
+    fn record_transition(&mut self, result: Result<OrderTransition, IllegalTransition>) -> bool {
+        match result {
+            Ok(change) => {
+                log::debug!("Order {}: {:?} -> {:?}", change.order_id, change.from, change.to);
+                let msg = Message::new("order.transition")
+                    .arg("market", truncate_str(&change.market_name, 40))
+                    .arg("from", format!("{:?}", change.from))
+                    .arg("to", format!("{:?}", change.to));
+                self.add_message_at(msg, ActivityType::Info, LogLevel::Debug);
+                if self.order_transitions.len() >= lifecycle::MAX_TRANSITIONS {
+                    self.order_transitions.pop_front();
+                }
+                self.order_transitions.push_back(change);
+                true
+            }
+            Err(illegal) => {
+                log::warn!("{}", illegal);
+                let market = self.orders.iter()
+                    .find(|o| o.id == illegal.order_id)
+                    .map(|o| truncate_str(&o.market_name, 40))
+                    .unwrap_or_else(|| illegal.order_id.clone());
+                let msg = Message::new("order.illegal_transition")
+                    .arg("market", market)
+                    .arg("from", format!("{:?}", illegal.from))
+                    .arg("to", format!("{:?}", illegal.to));
+                self.add_message(msg, ActivityType::Error);
+                false
+            }
+        }
+    }
+
+    fn transition_order(&mut self, index: usize, to: OrderStatus) -> bool {
+        let result = lifecycle::transition(&mut self.orders[index], to, Utc::now());
+        self.record_transition(result)
+    }
+
+    /// Recent status changes, oldest first, optionally for one order
+    pub fn get_order_transitions(&self, order_id: Option<&str>) -> Vec<OrderTransition> {
+        self.order_transitions
+            .iter()
+            .filter(|t| order_id.is_none_or(|id| t.order_id == id))
+            .cloned()
+            .collect()
+    }
+
+    fn simulate_order(&self, market: &Market, prediction: &AIPrediction, size: f64) -> Order {
+        Order {
+            id: Uuid::new_v4().to_string(),
+            market_id: market.id.clone(),
+            market_name: market.question.clone(),
+            side: OrderSide::Buy,
+            outcome: prediction.predicted_outcome.clone(),
+            price: prediction.fair_price,
+            size,
+            status: OrderStatus::Filled,
+            created_at: Utc::now(),
+            resolved_at: None,
+            pnl: None,
+            category: market.category.clone(),
+            parent_id: None,
+            child_ids: Vec::new(),
+            filled_size: size,
+            fill_price: None,
+            resolution: None,
+            external_id: None,
+            token_id: market.outcomes
+                .iter()
+                .position(|o| *o == prediction.predicted_outcome)
+                .and_then(|i| market.token_ids.get(i).cloned()),
+            clob_order_id: None,
+            replaces: None,
+            replaced_by: None,
+            exited_pnl: 0.0,
+            status_changed_at: None,
+        }
+    }
+
+    /// Reprice/resize a working order, or what is left of a partly filled one, by cancelling
+    /// it and placing a replacement for `new_size`; the filled part stays with the original.
+    /// The CLOB has no native replace: if the cancel fails nothing changes, and if the
+    /// replacement is rejected the original remainder is placed again.
+    pub async fn amend_order(&mut self, order_id: &str, new_price: f64, new_size: f64) -> Result<Order> {
+        if new_price <= 0.0 || new_price >= 1.0 {
+            return Err(ConfigError::new("new_price", "Must be between 0 and 1").into());
+        }
+        if new_size <= 0.0 || new_size > self.config.max_bet_size {
+            let message = format!("Must be between 0 and the max bet size (${:.2})", self.config.max_bet_size);
+            return Err(ConfigError::new("new_size", message).into());
+        }
+
+        let Some(index) = self.orders.iter().position(|o| o.id == order_id) else {
+            return Err(NotFound(format!("Order {} not found", order_id)).into());
+        };
+        let original = self.orders[index].clone();
+        if !original.status.is_working() || original.is_parent() || original.parent_id.is_some() {
+            return Err(ConfigError::new("order_id", "Only working, unsliced orders can be amended").into());
+        }
+        if original.is_imported() {
+            return Err(ConfigError::new("order_id", "Imported orders are not managed by the bot").into());
+        }
+
+        let market = match self.polymarket {
+            Some(ref client) => client.get_market(&original.market_id).await.ok().flatten(),
+            None => None,
+        };
+        let (tick_size, min_order_size, neg_risk) = market
+            .map(|m| (m.tick_size, m.min_order_size, m.neg_risk))
+            .unwrap_or((execution::DEFAULT_TICK_SIZE, execution::DEFAULT_MIN_ORDER_SIZE, false));
+        let normalized = execution::normalize_order(new_price, new_size, &original.side, tick_size, min_order_size)
+            .map_err(|reason| ConfigError::new("new_size", reason))?;
+        let (new_price, new_size) = (normalized.price, normalized.notional);
+
+        let mut replacement = Order {
+            id: Uuid::new_v4().to_string(),
+            price: new_price,
+            size: new_size,
+            filled_size: 0.0,
+            fill_price: None,
+            status: OrderStatus::Open,
+            created_at: Utc::now(),
+            clob_order_id: None,
+            replaces: Some(original.id.clone()),
+            replaced_by: None,
+            exited_pnl: 0.0,
+            status_changed_at: None,
+            ..original.clone()
+        };
+        let token_id = original.token_id.clone().unwrap_or_default();
+
+        if let Some(ref clob_id) = original.clob_order_id {
+            let Some(ref client) = self.polymarket else {
+                return Err(NotConfigured("Polymarket client").into());
+            };
+            client.cancel_order(clob_id).await?;
+            let placed = self
+                .place_signed_order(&token_id, &original.side, new_price, normalized.shares, neg_risk)
+                .await;
+            match placed {
+                Ok(id) => replacement.clob_order_id = Some(id),
+                Err(e) => {
+                    let remaining = original.size - original.filled_size;
+                    let shares = if original.price > 0.0 { remaining / original.price } else { 0.0 };
+                    let restored = self
+                        .place_signed_order(&token_id, &original.side, original.price, shares, neg_risk)
+                        .await;
+                    let market_name = truncate_str(&original.market_name, 40);
+                    match restored {
+                        Ok(id) => {
+                            self.orders[index].clob_order_id = Some(id);
+                            let msg = Message::new("order.amend_restored").arg("market", market_name).arg("error", &e);
+                            self.add_message(msg, ActivityType::Warning);
+                        }
+                        Err(restore_error) => {
+                            self.close_remainder(index);
+                            let msg = Message::new("order.amend_failed")
+                                .arg("market", market_name)
+                                .arg("error", &e)
+                                .arg("restore_error", &restore_error);
+                            self.add_message(msg, ActivityType::Error);
+                        }
+                    }
+                    self.persist_history();
+                    return Err(e);
+                }
+            }
+        } else {
+            self.resting_orders.retain(|r| r.order_id != original.id);
+        }
+
+        self.close_remainder(index);
+        self.orders[index].replaced_by = Some(replacement.id.clone());
+        let msg = Message::new("order.amended")
+            .arg("old_size", format!("{:.2}", original.size - original.filled_size))
+            .arg("old_price", format!("{:.3}", original.price))
+            .arg("new_size", format!("{:.2}", new_size))
+            .arg("new_price", format!("{:.3}", new_price))
+            .arg("market", truncate_str(&original.market_name, 40));
+        self.add_message(msg, ActivityType::Order);
+        self.ledger.order_placed(&replacement.id, &replacement.market_id, replacement.price, replacement.size);
+
+        let replacement_id = replacement.id.clone();
+        if replacement.clob_order_id.is_some() {
+            self.orders.push(replacement);
+        } else {
+            let book = match self.polymarket {
+                Some(ref client) if !token_id.is_empty() => client.get_orderbook(&token_id).await.ok(),
+                _ => None,
+            };
+            match book {
+                Some(book) => self.rest_paper_order(replacement, &token_id, &book),
+                None => {
+                    let price = replacement.price;
+                    self.fill_paper_order(replacement, price);
+                }
+            }
+        }
+        self.persist_history();
+        self.orders.iter().find(|o| o.id == replacement_id).cloned()
+            .ok_or_else(|| NotFound(format!("Order {} not found", replacement_id)).into())
+    }
+
+    /// Review filled positions in markets resolving within the exit window against the bid
+    async fn plan_exits(&mut self, scanned: &[Market]) {
+        let config = self.config.exits.clone();
+        let now = Utc::now();
+        let held: Vec<Order> = self.orders.iter()
+            .filter(|o| matches!(o.status, OrderStatus::Filled) && matches!(o.side, OrderSide::Buy))
+            .filter(|o| !o.is_parent() && !o.is_imported())
+            .cloned()
+            .collect();
+
+        let mut markets: HashMap<String, Option<Market>> = HashMap::new();
+        let mut decisions = Vec::new();
+        for order in held {
+            if !markets.contains_key(&order.market_id) {
+                let market = match scanned.iter().find(|m| m.id == order.market_id) {
+                    Some(market) => Some(market.clone()),
+                    None => match self.polymarket {
+                        Some(ref client) => client.get_market(&order.market_id).await.ok().flatten(),
+                        None => None,
+                    },
+                };
+                markets.insert(order.market_id.clone(), market);
+            }
+            let Some(market) = markets.get(&order.market_id).and_then(|m| m.as_ref()) else {
+                continue;
+            };
+            let Some(end) = market.end_date.as_deref().and_then(time::parse_timestamp) else {
+                continue;
+            };
+            let hours_left = (end - now).num_minutes() as f64 / 60.0;
+            if hours_left <= 0.0 || hours_left > config.window_hours as f64 {
+                continue;
+            }
+            let Some(fair) = self.fair_value(&order) else {
+                continue;
+            };
+            let Some(bid) = self.outcome_book(market, &order.outcome).await
+                .and_then(|book| book.bids.iter().map(|l| l.price).max_by(f64::total_cmp))
+            else {
+                continue;
+            };
+            decisions.push((order, exits::plan_exit(bid, fair, hours_left, &config)));
+        }
+
+        let mut exited = false;
+        for (order, decision) in decisions {
+            exited |= self.apply_exit(&order, &decision);
+        }
+        if exited {
+            self.apply_ledger_totals();
+            self.update_stats();
+        }
+    }
+
+    /// Re-analyze held positions not reviewed for `interval_hours`, least recently reviewed
+    /// first, and compare the fresh fair value with the one each was entered on
+    async fn reunderwrite_positions(&mut self, scanned: &[Market]) {
+        if self.claude.is_none() {
+            return;
+        }
+        let config = self.config.reunderwrite.clone();
+        let now = Utc::now();
+        let interval = chrono::Duration::hours(config.interval_hours as i64);
+
+        let mut held: HashMap<String, Vec<Order>> = HashMap::new();
+        for order in self.orders.iter()
+            .filter(|o| o.status == OrderStatus::Filled && matches!(o.side, OrderSide::Buy))
+            .filter(|o| !o.is_parent() && !o.is_imported())
+        {
+            let key = reunderwrite::position_key(&order.market_id, &order.outcome);
+            held.entry(key).or_default().push(order.clone());
+        }
+        let mut due: Vec<(chrono::DateTime<Utc>, Vec<Order>)> = held
+            .into_iter()
+            .filter_map(|(key, mut orders)| {
+                orders.sort_by_key(|o| o.created_at);
+                // A position never reviewed is due `interval_hours` after its first entry
+                let last = self.thesis_history.get(&key)
+                    .and_then(|reviews| reviews.last())
+                    .map_or(orders[0].created_at, |review| review.reviewed_at);
+                (now - last >= interval).then_some((last, orders))
+            })
+            .collect();
+        due.sort_by_key(|(last, _)| *last);
+        due.truncate(config.max_per_cycle as usize);
+
+        let total = due.len();
+        let mut exited = false;
+        for (i, (_, orders)) in due.into_iter().enumerate() {
+            if !self.claude_calls.allows() {
+                self.tally("tally.circuit_open");
+                continue;
+            }
+            if !self.pace_claude(total - i) {
+                break;
+            }
+            exited |= self.review_position(scanned, &orders, &config).await;
+        }
+        if exited {
+            self.apply_ledger_totals();
+            self.update_stats();
+        }
+    }
+
+    /// Re-analyze one position, record the review and, under `auto_act`, act on it.
+    /// Returns whether the position was exited.
+    async fn review_position(&mut self, scanned: &[Market], orders: &[Order], config: &ReunderwriteConfig) -> bool {
+        let first = &orders[0];
+        let market = match scanned.iter().find(|m| m.id == first.market_id) {
+            Some(market) => Some(market.clone()),
+            None => match self.polymarket {
+                Some(ref client) => client.get_market(&first.market_id).await.ok().flatten(),
+                None => None,
+            },
+        };
+        let Some(market) = market else { return false };
+        let Some(market_price) = outcome_price(&market, &first.outcome) else { return false };
+
+        let features = self.market_features(&market).await;
+        let Some(ref mut claude) = self.claude else { return false };
+        let analysis = claude.analyze_market(&market, Some(&features)).await;
+        self.claude_calls.record(&analysis, &self.config.circuit_breaker);
+        let prediction = match analysis {
+            Ok(prediction) => {
+                self.stats.api_costs = claude.estimate_cost();
+                prediction
+            }
+            Err(e) => {
+                let msg = Message::new("analysis.failed")
+                    .arg("market", truncate_str(&market.question, 40))
+                    .arg("error", e);
+                self.add_message_at(msg, ActivityType::Inference, LogLevel::Debug);
+                self.tally("tally.analysis_failed");
+                return false;
+            }
+        };
+        self.record_signal(&market, &prediction);
+        self.cache_prediction(&prediction, PredictionSource::Live);
+
+        // The thesis is the prediction the position was first entered on; orders are
+        // recorded at its fair price, which stands in when the report is gone
+        let report_id = first.parent_id.as_ref().unwrap_or(&first.id);
+        let entry_fair = self.execution_reports
+            .get(report_id)
+            .map_or(first.price, |report| reunderwrite::fair_for(&report.prediction, &first.outcome));
+        let fresh_fair = reunderwrite::fair_for(&prediction, &first.outcome);
+        let recommendation = reunderwrite::recommend(entry_fair, fresh_fair, market_price, config);
+
+        let acted = config.auto_act && match recommendation {
+            Recommendation::Hold => false,
+            Recommendation::Exit => self.exit_position(&market, orders, fresh_fair, market_price).await,
+            Recommendation::Add => self.add_to_position(&market, &prediction, &first.outcome).await,
+        };
+
+        let review = ThesisReview {
+            market_id: market.id.clone(),
+            market_name: market.question.clone(),
+            outcome: first.outcome.clone(),
+            entry_fair,
+            fresh_fair,
+            drift: fresh_fair - entry_fair,
+            market_price,
+            edge: fresh_fair - market_price,
+            recommendation,
+            acted,
+            reasoning: prediction.reasoning.clone(),
+            reviewed_at: Utc::now(),
+        };
+        let msg = Message::new(if acted { "thesis.acted" } else { "thesis.review" })
+            .arg("market", truncate_str(&review.market_name, 40))
+            .arg("outcome", &review.outcome)
+            .arg("entry", format!("{:.2}", review.entry_fair))
+            .arg("fresh", format!("{:.2}", review.fresh_fair))
+            .arg("drift", format!("{:+.2}", review.drift))
+            .arg("price", format!("{:.2}", review.market_price))
+            .arg("recommendation", format!("{:?}", review.recommendation));
+        let entry_type = match recommendation {
+            Recommendation::Hold => ActivityType::Inference,
+            Recommendation::Add => ActivityType::Edge,
+            Recommendation::Exit => ActivityType::Warning,
+        };
+        self.add_message(msg, entry_type);
+        self.record_review(review);
+        acted && recommendation == Recommendation::Exit
+    }
+
+    /// Close every filled order of a position at the bid, or at the market price without a book
+    async fn exit_position(&mut self, market: &Market, orders: &[Order], fair: f64, market_price: f64) -> bool {
+        let bid = self.outcome_book(market, &orders[0].outcome).await
+            .and_then(|book| book.bids.iter().map(|l| l.price).max_by(f64::total_cmp))
+            .unwrap_or(market_price);
+        let hours_left = market.end_date.as_deref()
+            .and_then(time::parse_timestamp)
+            .map_or(0.0, |end| (end - Utc::now()).num_minutes() as f64 / 60.0);
+        let decision = ExitDecision {
+            action: ExitAction::Close,
+            ..exits::plan_exit(bid, fair, hours_left, &self.config.exits)
+        };
+        let mut exited = false;
+        for order in orders {
+            exited |= self.apply_exit(order, &decision);
+        }
+        exited
+    }
+
+    /// Buy more of a held outcome through the usual sizing, risk limits and execution gate.
+    /// Only when the fresh prediction favors that outcome, since orders follow the prediction.
+    async fn add_to_position(&mut self, market: &Market, prediction: &AIPrediction, outcome: &str) -> bool {
+        if !self.config.auto_trading || prediction.predicted_outcome != outcome {
+            return false;
+        }
+        let free_capital = self.free_capital();
+        let size = (prediction.recommended_size * free_capital)
+            .min(self.config.max_bet_size)
+            .min(free_capital);
+        if size <= 1.0 {
+            return false;
+        }
+        let Some(size) = self.apply_risk_limits(market, prediction, size) else { return false };
+        let before = self.orders.len();
+        self.place_order(market, prediction, size).await;
+        self.orders.len() > before
+    }
+
+    fn record_review(&mut self, review: ThesisReview) {
+        let key = reunderwrite::position_key(&review.market_id, &review.outcome);
+        let row_key = format!("{}:{}", key, review.reviewed_at.timestamp_millis());
+        self.persist(Table::Reviews, &row_key, &review);
+        self.push_review(key, review);
+    }
+
+    fn push_review(&mut self, key: String, review: ThesisReview) {
+        let reviews = self.thesis_history.entry(key).or_default();
+        reviews.push(review);
+        if reviews.len() > reunderwrite::MAX_REVIEWS_PER_POSITION {
+            reviews.remove(0);
+        }
+    }
+
+    /// Replace the review history, e.g. from the database or an archive
+    fn set_thesis_history(&mut self, mut reviews: Vec<ThesisReview>) {
+        reviews.sort_by_key(|r| r.reviewed_at);
+        self.thesis_history.clear();
+        for review in reviews {
+            let key = reunderwrite::position_key(&review.market_id, &review.outcome);
+            self.push_review(key, review);
+        }
+    }
+
+    /// Thesis reviews, oldest first, for one market or every position
+    pub fn get_thesis_history(&self, market_id: Option<&str>) -> Vec<ThesisReview> {
+        let mut reviews: Vec<ThesisReview> = self.thesis_history
+            .values()
+            .flatten()
+            .filter(|r| market_id.is_none_or(|id| r.market_id == id))
+            .cloned()
+            .collect();
+        reviews.sort_by_key(|r| r.reviewed_at);
+        reviews
+    }
+
+    /// Panic button: stop the bot, cancel every working and conditional order, then close
+    /// every position, paper ones at the mid and wallet ones with marketable sells. Each
+    /// step is logged, and a failed step does not stop the rest.
+    pub async fn flatten_all(&mut self) -> FlattenReport {
+        let bot_stopped = self.is_running;
+        if bot_stopped {
+            self.stop();
+        }
+        let mut report = FlattenReport::new(bot_stopped);
+        self.add_message(Message::new("flatten.started"), ActivityType::Warning);
+
+        // Nothing queued may fill once flattening starts
+        self.scheduled_slices.clear();
+        self.resting_orders.clear();
+        let working: Vec<usize> = (0..self.orders.len()).filter(|&i| self.orders[i].status.is_working()).collect();
+        for index in working {
+            let order = self.orders[index].clone();
+            let cancelled = match (&order.clob_order_id, &self.polymarket) {
+                (Some(clob_id), Some(client)) => client.cancel_order(clob_id).await
+                    .map(|_| "Cancelled on the CLOB".to_string())
+                    .map_err(|e| format!("{:#}", e)),
+                (Some(_), None) => Err("Polymarket client not configured".to_string()),
+                (None, _) => Ok("Cancelled".to_string()),
+            };
+            if cancelled.is_ok() {
+                if order.filled_size > 0.0 && !order.is_parent() {
+                    // What already filled is a position, closed below
+                    self.orders[index].size = order.filled_size;
+                    self.transition_order(index, OrderStatus::Filled);
+                } else {
+                    self.transition_order(index, OrderStatus::Cancelled);
+                }
+            }
+            self.flatten_step(&mut report, FlattenAction::CancelOrder, &order.market_name, cancelled);
+        }
+
+        let now = Utc::now();
+        let mut parked = Vec::new();
+        for order in self.conditional_orders.iter_mut().filter(|o| o.is_parked()) {
+            order.close(ConditionalStatus::Cancelled, now);
+            parked.push(order.market_name.clone());
+        }
+        for market_name in parked {
+            self.flatten_step(&mut report, FlattenAction::CancelConditional, &market_name, Ok("Cancelled".to_string()));
+        }
+
+        // Live and imported orders leave wallet positions, sold below; only simulated fills close here
+        let paper: Vec<Order> = self.orders.iter()
+            .filter(|o| o.status == OrderStatus::Filled && matches!(o.side, OrderSide::Buy))
+            .filter(|o| !o.is_parent() && !o.is_imported() && o.clob_order_id.is_none())
+            .cloned()
+            .collect();
+        for order in paper {
+            let book = match (&self.polymarket, &order.token_id) {
+                (Some(client), Some(token_id)) => client.get_orderbook(token_id).await.ok(),
+                _ => None,
+            };
+            let closed = match book.as_ref().and_then(flatten::mid) {
+                Some(mid) => self.close_paper_position(&order, mid),
+                None => Err("No order book to price the close".to_string()),
+            };
+            if let Ok((pnl, _)) = closed {
+                report.realized_pnl += pnl;
+            }
+            let closed = closed.map(|(_, detail)| detail);
+            self.flatten_step(&mut report, FlattenAction::ClosePaper, &order.market_name, closed);
+        }
+
+        self.sell_wallet_positions(&mut report).await;
+
+        self.apply_ledger_totals();
+        self.update_stats();
+        self.persist_history();
+        report.finished_at = Utc::now();
+        let msg = Message::new("flatten.finished")
+            .arg("steps", report.steps.len())
+            .arg("failed", report.failed())
+            .arg("pnl", i18n::signed_usd(report.realized_pnl));
+        self.notifications.notify(NotificationKind::RiskHalt, "Positions flattened", &msg.render(self.locale));
+        self.add_message(msg, if report.failed() > 0 { ActivityType::Error } else { ActivityType::Warning });
+        report
+    }
+
+    /// Close a whole paper position at `price`; returns the PnL and a description
+    fn close_paper_position(&mut self, order: &Order, price: f64) -> std::result::Result<(f64, String), String> {
+        let Some(index) = self.orders.iter().position(|o| o.id == order.id) else {
+            return Err("Order no longer exists".to_string());
+        };
+        if order.price <= 0.0 || !self.orders[index].status.can_become(OrderStatus::Resolved) {
+            return Err("Position cannot be closed".to_string());
+        }
+        let pnl = order.shares() * price - order.size;
+        if !self.ledger.exit(&order.id, &order.market_id, pnl, 1.0) {
+            return Err("Already closed".to_string());
+        }
+        let held = &mut self.orders[index];
+        held.exited_pnl += pnl;
+        held.pnl = Some(held.exited_pnl);
+        held.resolved_at = Some(Utc::now());
+        self.transition_order(index, OrderStatus::Resolved);
+        Ok((pnl, format!("Closed ${:.2} at mid {:.3}, {}", order.size, price, i18n::signed_usd(pnl))))
+    }
+
+    /// Sell every position the configured wallet holds, each limit priced low enough to
+    /// take all the bids its size needs
+    async fn sell_wallet_positions(&mut self, report: &mut FlattenReport) {
+        let Some(ref client) = self.polymarket else { return };
+        if !client.is_configured() || self.config.wallet_address.trim().is_empty() {
+            return;
+        }
+        let positions = match client.get_positions(&self.config.wallet_address).await {
+            Ok(positions) => positions,
+            Err(e) => {
+                self.flatten_step(report, FlattenAction::SellLive, "Wallet positions", Err(format!("{:#}", e)));
+                return;
+            }
+        };
+
+        let mut results = Vec::new();
+        for position in positions.iter().filter(|p| p.size > 0.0 && !p.redeemable) {
+            let sold = match client.get_orderbook(&position.asset_id).await {
+                Ok(book) => match flatten::marketable_sell_price(&book, position.size) {
+                    Some(price) => self
+                        .place_signed_order(&position.asset_id, &OrderSide::Sell, price, position.size, position.neg_risk)
+                        .await
+                        .map(|id| format!("Sell {:.2} shares at {:.3} (order {})", position.size, price, id))
+                        .map_err(|e| format!("{:#}", e)),
+                    None => Err("No bids to sell into".to_string()),
+                },
+                Err(e) => Err(format!("{:#}", e)),
+            };
+            results.push((position.market_name.clone(), sold));
+        }
+        for (market_name, sold) in results {
+            self.flatten_step(report, FlattenAction::SellLive, &market_name, sold);
+        }
+    }
+
+    /// Have the signer RPC's wallet sign a limit order and post it to the CLOB; returns the
+    /// CLOB order id
+    async fn place_signed_order(&self, token_id: &str, side: &OrderSide, price: f64, shares: f64, neg_risk: bool) -> Result<String> {
+        let client = self.polymarket.as_ref().ok_or(NotConfigured("Polymarket client"))?;
+        if !client.is_configured() {
+            return Err(NotConfigured("Polymarket API credentials").into());
+        }
+        let signer = ChainClient::signer(&self.config)?.ok_or(NotConfigured("Signer RPC"))?;
+        let unsigned = client.build_order(token_id, side, price, shares, neg_risk);
+        let signature = signer.sign_typed_data(&self.config.wallet_address, &unsigned.typed_data).await?;
+        client.post_order(&unsigned, &signature).await
+    }
+
+    fn flatten_step(&mut self, report: &mut FlattenReport, action: FlattenAction, market_name: &str, result: std::result::Result<String, String>) {
+        let (key, entry_type, detail) = match result {
+            Ok(ref detail) => ("flatten.step", ActivityType::Order, detail.clone()),
+            Err(ref error) => ("flatten.step_failed", ActivityType::Error, error.clone()),
+        };
+        let msg = Message::new(key)
+            .arg("action", format!("{:?}", action))
+            .arg("market", truncate_str(market_name, 40))
+            .arg("detail", detail);
+        self.add_message(msg, entry_type);
+        report.push(action, market_name, result);
+    }
+
+    /// Model probability of the order's outcome: the latest analysis of the market, or the
+    /// prediction the order was placed on
+    fn fair_value(&self, order: &Order) -> Option<f64> {
+        let prediction = match self.prediction_cache.get(&order.market_id) {
+            Some(cached) => &cached.prediction,
+            None => {
+                let report_id = order.parent_id.as_ref().unwrap_or(&order.id);
+                &self.execution_reports.get(report_id)?.prediction
+            }
+        };
+        Some(if prediction.predicted_outcome == order.outcome {
+            prediction.fair_price
+        } else {
+            1.0 - prediction.fair_price
+        })
     }
 
-    /// Start the trading bot
-    pub fn start(&mut self) {
-        self.is_running = true;
-        self.start_time = Some(Utc::now());
-        self.add_activity("🟢 Bot started - Survival Mode active", ActivityType::Info);
-    }
+    /// Sell the decided share of a position at the bid. Returns whether anything was sold.
+    fn apply_exit(&mut self, order: &Order, decision: &ExitDecision) -> bool {
+        let fraction = match decision.action {
+            ExitAction::Hold => {
+                let msg = exit_message(Message::new("exit.hold"), &order.market_name, decision);
+                self.add_message_at(msg, ActivityType::Info, LogLevel::Debug);
+                return false;
+            }
+            ExitAction::Reduce(fraction) => fraction,
+            ExitAction::Close => 1.0,
+        };
+        if order.price <= 0.0 {
+            return false;
+        }
+        let sold = order.size * fraction;
+        let pnl = order.shares_for(sold) * decision.bid - sold;
+        let Some(index) = self.orders.iter().position(|o| o.id == order.id) else {
+            return false;
+        };
+        if fraction >= 1.0 && !self.orders[index].status.can_become(OrderStatus::Resolved) {
+            return false;
+        }
+        if !self.ledger.exit(&order.id, &order.market_id, pnl, fraction) {
+            return false;
+        }
 
-    /// Stop the trading bot
-    pub fn stop(&mut self) {
-        self.is_running = false;
-        self.add_activity("🔴 Bot stopped", ActivityType::Warning);
+        let held = &mut self.orders[index];
+        held.exited_pnl += pnl;
+        let msg = if fraction >= 1.0 {
+            held.pnl = Some(held.exited_pnl);
+            held.resolved_at = Some(Utc::now());
+            self.transition_order(index, OrderStatus::Resolved);
+            Message::new("exit.close")
+        } else {
+            held.size -= sold;
+            held.filled_size *= 1.0 - fraction;
+            Message::new("exit.reduce").arg("fraction", format!("{:.0}%", fraction * 100.0))
+        };
+
+        let msg = exit_message(msg.arg("pnl", i18n::signed_usd(pnl)), &order.market_name, decision);
+        self.notifications.notify_resolution(pnl, &msg.render(self.locale));
+        self.add_message(msg, if pnl >= 0.0 { ActivityType::Resolved } else { ActivityType::Warning });
+        true
     }
 
-    /// Run one cycle of market scanning + trading
-    pub async fn run_cycle(&mut self) -> Result<Vec<ActivityEntry>> {
-        let mut new_activities: Vec<ActivityEntry> = Vec::new();
+    async fn resolve_pending_orders(&mut self) {
+        let mut market_ids: Vec<String> = self.orders.iter()
+            .filter(|o| matches!(o.status, OrderStatus::Filled) && !o.is_parent() && !o.is_imported())
+            .map(|o| o.market_id.clone())
+            .collect();
+        market_ids.sort();
+        market_ids.dedup();
 
-        if !self.is_running {
-            return Ok(new_activities);
+        let mut resolutions = HashMap::new();
+        if let Some(ref client) = self.polymarket {
+            for market_id in market_ids {
+                if let Ok(Some(resolution)) = client.get_market_resolution(&market_id).await {
+                    resolutions.insert(market_id, resolution);
+                }
+            }
         }
 
-        self.stats.cycle += 1;
+        let mut settled = Vec::new();
+        let mut disputed = Vec::new();
+        let mut transitions = Vec::new();
+        let now = Utc::now();
 
-        // Update uptime
-        if let Some(start) = self.start_time {
-            let elapsed = Utc::now() - start;
-            let hours = elapsed.num_hours();
-            let minutes = elapsed.num_minutes() % 60;
-            let seconds = elapsed.num_seconds() % 60;
-            self.stats.uptime = format!("{:02}:{:02}:{:02}", hours, minutes, seconds);
+        for order in self.orders.iter_mut() {
+            if !matches!(order.status, OrderStatus::Filled) || order.is_parent() || order.is_imported() {
+                continue;
+            }
+            let Some(resolution) = resolutions.get(&order.market_id) else {
+                continue;
+            };
+            order.resolution = Some(resolution.clone());
+
+            if resolution.status == ResolutionStatus::Disputed {
+                disputed.push((order.market_id.clone(), order.market_name.clone()));
+                continue;
+            }
+            if !resolution.is_settled() {
+                continue;
+            }
+
+            // Shares bought = stake / average fill price; invalid markets refund the stake
+            let pnl = match resolution.payout_for(&order.outcome) {
+                Some(payout) if order.price > 0.0 => order.shares() * payout - order.size,
+                _ => 0.0,
+            };
+
+            order.pnl = Some(pnl + order.exited_pnl);
+            order.resolved_at = Some(resolution.resolved_at.unwrap_or(now));
+            transitions.push(lifecycle::transition(order, OrderStatus::Resolved, now));
+
+            let counted = resolution.status != ResolutionStatus::Invalid;
+            if self.ledger.resolution(&order.id, &order.market_id, pnl, counted) {
+                settled.push((pnl, resolution.status.clone()));
+            }
         }
+        self.apply_ledger_totals();
 
-        // Scan markets
-        let markets = if let Some(ref client) = self.polymarket {
-            let msg = format!("Scanning markets... Cycle #{}", self.stats.cycle);
-            self.add_activity(&msg, ActivityType::Info);
-            new_activities.push(self.activity_log.last().unwrap().clone());
-
-            match client.get_markets(100, 0).await {
-                Ok(markets) => {
-                    self.stats.markets_scanned += markets.len() as u64;
-                    let msg = format!("Processing {} markets...", markets.len());
-                    self.add_activity(&msg, ActivityType::Info);
-                    new_activities.push(self.activity_log.last().unwrap().clone());
-                    markets
-                }
-                Err(e) => {
-                    self.add_activity(
-                        &format!("Error fetching markets: {}", e),
-                        ActivityType::Error,
-                    );
-                    new_activities.push(self.activity_log.last().unwrap().clone());
-                    return Ok(new_activities);
+        // Roll child results up into their sliced parents once every slice has resolved
+        let mut child_results: HashMap<String, (usize, f64)> = HashMap::new();
+        for child in self.orders.iter().filter(|o| matches!(o.status, OrderStatus::Resolved)) {
+            if let Some(ref parent_id) = child.parent_id {
+                let entry = child_results.entry(parent_id.clone()).or_insert((0, 0.0));
+                entry.0 += 1;
+                entry.1 += child.pnl.unwrap_or(0.0);
+            }
+        }
+        for parent in self.orders.iter_mut().filter(|o| o.is_parent() && matches!(o.status, OrderStatus::Filled)) {
+            if let Some((count, pnl)) = child_results.get(&parent.id) {
+                if *count == parent.child_ids.len() {
+                    parent.pnl = Some(*pnl);
+                    parent.resolved_at = Some(now);
+                    transitions.push(lifecycle::transition(parent, OrderStatus::Resolved, now));
                 }
             }
-        } else {
-            return Ok(new_activities);
-        };
+        }
+        for change in transitions {
+            self.record_transition(change);
+        }
 
-        // Analyze markets with AI
-        for market in markets.iter().take(10) {
-            if let Some(ref mut claude) = self.claude {
-                match claude.analyze_market(market).await {
-                    Ok(prediction) => {
-                        self.stats.api_costs = claude.estimate_cost();
+        for (market_id, market_name) in disputed {
+            if self.alerted_resolutions.insert(format!("disputed:{}", market_id)) {
+                let msg = Message::new("resolution.disputed").arg("market", truncate_str(&market_name, 40));
+                self.add_message(msg, ActivityType::Warning);
+            }
+        }
 
-                        if prediction.edge >= self.config.min_edge_threshold as f64 {
-                            // Found an edge!
-                            let edge_msg = format!(
-                                "Edge: \"{}\" > ${:.0} @ {:.2} (fair {:.2})",
-                                truncate_str(&market.question, 40),
-                                prediction.recommended_size * self.stats.current_balance,
-                                prediction.edge,
-                            );
-                            self.add_activity(&edge_msg, ActivityType::Edge);
-                            new_activities.push(self.activity_log.last().unwrap().clone());
-
-                            // Place order (simulated for safety)
-                            let order_size = (prediction.recommended_size * self.stats.current_balance)
-                                .min(self.config.max_bet_size);
-
-                            if order_size > 1.0 && self.config.auto_trading {
-                                let order = self.simulate_order(market, &prediction, order_size);
-                                let order_msg = format!(
-                                    "ORDER ${:.2} → \"{}\"",
-                                    order_size,
-                                    truncate_str(&market.question, 40)
-                                );
-                                self.add_activity(&order_msg, ActivityType::Order);
-                                new_activities.push(self.activity_log.last().unwrap().clone());
-                                self.orders.push(order);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        let err_msg = format!("Inference: -${:.3}", 0.002);
-                        self.add_activity(&err_msg, ActivityType::Inference);
-                        new_activities.push(self.activity_log.last().unwrap().clone());
-                    }
-                }
+        for (pnl, status) in settled {
+            let resolve_msg = match status {
+                ResolutionStatus::FiftyFifty => Message::new("resolution.fifty_fifty").arg("pnl", i18n::signed_usd(pnl)),
+                ResolutionStatus::Invalid => Message::new("resolution.invalid"),
+                _ => Message::new("resolution.resolved").arg("pnl", i18n::signed_usd(pnl)),
+            };
+            self.notifications.notify_resolution(pnl, &resolve_msg.render(self.locale));
+            self.add_message(resolve_msg, if pnl >= 0.0 { ActivityType::Resolved } else { ActivityType::Warning });
+        }
+
+        // Keep every open order and imported history, plus the last 50 settled bot orders.
+        // A sliced order and its children go together, once every one of them has settled.
+        let group = |o: &Order| o.parent_id.clone().unwrap_or_else(|| o.id.clone());
+        let mut groups: Vec<String> = Vec::new();
+        let mut open_groups: HashSet<String> = HashSet::new();
+        for order in &self.orders {
+            let key = group(order);
+            if order.external_id.is_some() || !order.status.is_terminal() {
+                open_groups.insert(key.clone());
+            }
+            if !groups.contains(&key) {
+                groups.push(key);
             }
         }
+        let settled: Vec<String> = groups.into_iter().filter(|g| !open_groups.contains(g)).collect();
+        if settled.len() > 50 {
+            let dropped: HashSet<&String> = settled[..settled.len() - 50].iter().collect();
+            self.orders.retain(|o| !dropped.contains(&group(o)));
 
-        // Simulate some resolved trades for demo
-        self.resolve_pending_orders();
+            let kept: HashSet<&str> = self.orders.iter().map(|o| o.id.as_str()).collect();
+            self.execution_reports.retain(|id, _| kept.contains(id.as_str()));
+        }
+    }
 
-        // Update balance history
-        self.balance_history.push(BalancePoint {
-            timestamp: Utc::now().format("%H:%M:%S").to_string(),
-            balance: self.stats.current_balance,
-            label: format!("{}h", self.balance_history.len()),
-        });
+    /// Record a manual deposit (positive) or withdrawal (negative)
+    pub fn record_capital_flow(&mut self, amount: f64, note: &str) -> Result<()> {
+        if amount.is_nan() || amount == 0.0 {
+            return Err(ConfigError::new("amount", "Must be non-zero").into());
+        }
+        if amount < 0.0 && -amount > self.free_capital() {
+            let message = format!("Withdrawal exceeds free capital (${:.2})", self.free_capital());
+            return Err(ConfigError::new("amount", message).into());
+        }
 
-        // Update derived stats
+        self.ledger.capital_flow(format!("flow:manual:{}", Uuid::new_v4()), amount, FlowSource::Manual, note, Utc::now());
+        self.apply_ledger_totals();
         self.update_stats();
 
-        Ok(new_activities)
+        let msg = if amount > 0.0 {
+            Message::new("funds.deposit").arg("amount", format!("{:.2}", amount))
+        } else {
+            Message::new("funds.withdrawal").arg("amount", format!("{:.2}", -amount))
+        };
+        self.add_message(msg, ActivityType::Info);
+        Ok(())
     }
 
-    fn simulate_order(&self, market: &Market, prediction: &AIPrediction, size: f64) -> Order {
-        Order {
-            id: Uuid::new_v4().to_string(),
-            market_id: market.id.clone(),
-            market_name: market.question.clone(),
-            side: OrderSide::Buy,
-            outcome: prediction.predicted_outcome.clone(),
-            price: prediction.fair_price,
-            size,
-            status: OrderStatus::Filled,
-            created_at: Utc::now().format("%H:%M:%S").to_string(),
-            resolved_at: None,
-            pnl: None,
+    /// Which token approvals the exchange contracts have; live orders cannot settle without them
+    pub async fn check_allowances(&self) -> Result<AllowanceStatus> {
+        if self.config.wallet_address.is_empty() {
+            return Err(ConfigError::new("wallet_address", "Required to check allowances").into());
         }
+        let Some(ref chain) = self.chain else {
+            return Err(NotConfigured("Chain client").into());
+        };
+        wallet::check_allowances(chain, &self.config.wallet_address).await
     }
 
-    fn resolve_pending_orders(&mut self) {
-        let mut rng_seed = self.stats.cycle as f64;
-        
-        for order in self.orders.iter_mut() {
-            if matches!(order.status, OrderStatus::Filled) {
-                // Simple simulation: ~65% win rate
-                rng_seed = (rng_seed * 1.1 + 0.3) % 1.0;
-                let won = rng_seed > 0.35;
+    /// Build an approval for every missing allowance and send them through the signer RPC
+    /// when one is configured; without one they are returned unsigned for an external wallet
+    pub async fn set_allowances(&mut self) -> Result<ApprovalBatch> {
+        let status = self.check_allowances().await?;
+        let Some(ref chain) = self.chain else {
+            return Err(NotConfigured("Chain client").into());
+        };
+        let mut transactions = wallet::build_approvals(chain, &status).await?;
+        if transactions.is_empty() {
+            self.add_message(Message::new("wallet.approved"), ActivityType::Info);
+            return Ok(ApprovalBatch { transactions, sent: false });
+        }
 
-                let pnl = if won {
-                    order.size * (1.0 / order.price - 1.0) * 0.3 // Partial win
-                } else {
-                    -order.size * 0.7 // Partial loss
-                };
+        let sent = match ChainClient::signer(&self.config)? {
+            Some(signer) => {
+                if let Err(e) = wallet::send_approvals(&signer, &mut transactions).await {
+                    let msg = Message::new("wallet.approval_failed")
+                        .arg("sent", transactions.iter().filter(|t| t.tx_hash.is_some()).count())
+                        .arg("total", transactions.len())
+                        .arg("error", &e);
+                    self.add_message(msg, ActivityType::Error);
+                    return Err(e);
+                }
+                true
+            }
+            None => false,
+        };
+        let fee: f64 = transactions.iter().map(|t| t.max_fee()).sum();
+        let msg = Message::new(if sent { "wallet.approvals_sent" } else { "wallet.approvals_built" })
+            .arg("count", transactions.len())
+            .arg("fee", format!("{:.4}", fee));
+        self.add_message(msg, ActivityType::Info);
+        Ok(ApprovalBatch { transactions, sent })
+    }
 
-                order.pnl = Some(pnl);
-                order.status = OrderStatus::Resolved;
-                order.resolved_at = Some(Utc::now().format("%H:%M:%S").to_string());
+    /// Record collateral transfers between the wallet and outside addresses since the last scan.
+    /// Transfers to or from the exchange and CTF contracts are trade settlement, not capital flows.
+    /// The first scan looks back one window; later ones page through every block since the last,
+    /// resuming on the next call when the gap is longer than `MAX_WINDOWS` windows.
+    pub async fn detect_capital_flows(&mut self) -> Result<u32> {
+        const MAX_SCAN_BLOCKS: u64 = 10_000;
+        const MAX_WINDOWS: u64 = 20;
 
-                self.stats.current_balance += pnl;
-                self.stats.total_trades += 1;
+        if self.config.wallet_address.is_empty() {
+            return Err(ConfigError::new("wallet_address", "Required to detect transfers").into());
+        }
+        let Some(ref chain) = self.chain else {
+            return Err(NotConfigured("Chain client").into());
+        };
 
-                if pnl > 0.0 {
-                    self.stats.wins += 1;
-                    if pnl > self.stats.best_trade {
-                        self.stats.best_trade = pnl;
-                    }
-                } else {
-                    self.stats.losses += 1;
-                    if pnl < self.stats.worst_trade {
-                        self.stats.worst_trade = pnl;
-                    }
-                }
+        let head = chain.block_number().await?;
+        let from = match self.last_transfer_block {
+            Some(last) => last + 1,
+            None => head.saturating_sub(MAX_SCAN_BLOCKS),
+        };
+        if from > head {
+            return Ok(0);
+        }
+        let to = head.min(from + MAX_SCAN_BLOCKS * MAX_WINDOWS - 1);
 
-                let resolve_msg = format!(
-                    "RESOLVED {}${:.2}",
-                    if pnl >= 0.0 { "+" } else { "" },
-                    pnl
-                );
-                self.add_activity(&resolve_msg, if pnl >= 0.0 { ActivityType::Resolved } else { ActivityType::Warning });
+        let wallet = self.config.wallet_address.to_lowercase();
+        let network = chain.network();
+        let settlement: Vec<String> = [
+            &network.exchange_address,
+            &network.neg_risk_exchange_address,
+            &network.conditional_tokens,
+        ]
+        .iter()
+        .map(|a| a.to_lowercase())
+        .collect();
+
+        let mut transfers = Vec::new();
+        let mut start = from;
+        while start <= to {
+            let end = to.min(start + MAX_SCAN_BLOCKS - 1);
+            transfers.extend(chain.collateral_transfers(&wallet, start, end).await?);
+            start = end + 1;
+        }
+        let mut recorded = 0;
+        for t in transfers {
+            let incoming = t.to.eq_ignore_ascii_case(&wallet);
+            let counterparty = if incoming { &t.from } else { &t.to };
+            if settlement.contains(&counterparty.to_lowercase()) || t.from.eq_ignore_ascii_case(&t.to) {
+                continue;
             }
+
+            let amount = if incoming { t.amount } else { -t.amount };
+            let id = format!("flow:tx:{}:{}", t.tx_hash, t.log_index);
+            let source = FlowSource::Transfer { tx_hash: t.tx_hash.clone() };
+            let at = t.block_timestamp.unwrap_or_else(Utc::now);
+            if self.ledger.capital_flow(id, amount, source, counterparty, at) {
+                recorded += 1;
+            }
+        }
+        self.last_transfer_block = Some(to);
+
+        if recorded > 0 {
+            self.apply_ledger_totals();
+            self.update_stats();
+            self.add_message(Message::new("funds.detected").arg("count", recorded), ActivityType::Info);
         }
+        if to < head {
+            self.add_message(Message::new("funds.scan_behind").arg("blocks", head - to), ActivityType::Warning);
+        }
+        Ok(recorded)
+    }
 
-        // Remove resolved orders from active list (keep last 50 for history)
-        if self.orders.len() > 50 {
-            self.orders = self.orders.split_off(self.orders.len() - 50);
+    pub fn get_capital_flows(&self) -> Vec<CapitalFlow> {
+        let flows = self.ledger.capital_flows();
+        if self.guest.masks_balances() {
+            flows.into_iter().map(|f| CapitalFlow { amount: 0.0, net_deposits: 0.0, ..f }).collect()
+        } else {
+            flows
         }
     }
 
+    /// Derive balance and trade counters from the ledger rather than mutating them in place
+    /// Book a simulated demo trade as placed, filled and resolved in the ledger, so the stats
+    /// move exactly as they would for a real settlement
+    pub fn record_demo_trade(&mut self, market_id: &str, price: f64, size: f64, pnl: f64) {
+        let id = Uuid::new_v4().to_string();
+        self.ledger.order_placed(&id, market_id, price, size);
+        self.ledger.fill(&id, size);
+        self.ledger.resolution(&id, market_id, pnl, true);
+        self.apply_ledger_totals();
+        self.update_stats();
+    }
+
+    fn apply_ledger_totals(&mut self) {
+        let totals = self.ledger.totals();
+        self.stats.current_balance = self.stats.initial_balance + totals.realized_pnl + totals.net_deposits;
+        self.stats.net_deposits = totals.net_deposits;
+        self.stats.wins = totals.wins;
+        self.stats.losses = totals.losses;
+        self.stats.total_trades = totals.total_trades;
+        self.stats.best_trade = totals.best_trade;
+        self.stats.worst_trade = totals.worst_trade;
+    }
+
     fn update_stats(&mut self) {
-        self.stats.total_pnl = self.stats.current_balance - self.stats.initial_balance;
+        self.stats.total_pnl = self.stats.current_balance - self.stats.initial_balance - self.stats.net_deposits;
+        self.stats.total_equity = self.stats.current_balance;
+        (self.stats.mark_to_market_equity, self.stats.model_equity) = self.marked_equity();
+        self.stats.free_capital = self.free_capital();
 
-        let pnl_pct = if self.stats.initial_balance > 0.0 {
-            (self.stats.total_pnl / self.stats.initial_balance) * 100.0
-        } else {
-            0.0
-        };
-        self.stats.total_pnl_pct = format!(
-            "{}${:.1}k",
-            if self.stats.total_pnl >= 0.0 { "+" } else { "" },
-            self.stats.total_pnl / 1000.0
-        );
+        self.stats.total_pnl_pct = format!("{:+.1}%", self.stats.pnl_percent());
 
         if self.stats.total_trades > 0 {
             self.stats.win_rate = (self.stats.wins as f64 / self.stats.total_trades as f64) * 100.0;
             
             let total_bet: f64 = self.orders.iter()
-                .filter(|o| matches!(o.status, OrderStatus::Resolved))
+                .filter(|o| matches!(o.status, OrderStatus::Resolved) && !o.is_parent() && !o.is_imported())
                 .map(|o| o.size)
                 .sum();
             self.stats.avg_bet = if self.stats.total_trades > 0 {
@@ -293,6 +3535,7 @@ impl TradingEngine {
         // Sharpe ratio approximation
         if self.stats.total_trades > 1 {
             let returns: Vec<f64> = self.orders.iter()
+                .filter(|o| !o.is_parent() && !o.is_imported())
                 .filter_map(|o| o.pnl)
                 .collect();
             let mean = returns.iter().sum::<f64>() / returns.len() as f64;
@@ -312,11 +3555,49 @@ impl TradingEngine {
     }
 
     pub fn add_activity(&mut self, message: &str, entry_type: ActivityType) {
+        let level = LogLevel::default_for(&entry_type);
+        self.add_activity_at(message, entry_type, level);
+    }
+
+    /// Record an activity at an explicit level; entries below the persist level are dropped
+    pub fn add_activity_at(&mut self, message: &str, entry_type: ActivityType, level: LogLevel) {
+        self.push_activity(message.to_string(), None, entry_type, level);
+    }
+
+    /// Record a localizable message; the entry keeps its key so it can be re-rendered
+    pub fn add_message(&mut self, message: Message, entry_type: ActivityType) {
+        let level = LogLevel::default_for(&entry_type);
+        self.add_message_at(message, entry_type, level);
+    }
+
+    pub fn add_message_at(&mut self, message: Message, entry_type: ActivityType, level: LogLevel) {
+        let text = message.render(Locale::En);
+        self.push_activity(text, Some(message), entry_type, level);
+    }
+
+    fn push_activity(&mut self, text: String, message: Option<Message>, entry_type: ActivityType, level: LogLevel) {
+        if level < self.config.persist_log_level {
+            return;
+        }
+
+        let (key, params) = match message {
+            Some(m) => (Some(m.key), m.params),
+            None => (None, Default::default()),
+        };
+        self.activity_seq += 1;
         let entry = ActivityEntry {
-            timestamp: Utc::now().format("[%H:%M:%S]").to_string(),
-            message: message.to_string(),
+            seq: self.activity_seq,
+            timestamp: Utc::now(),
+            local_time: String::new(),
+            message: text,
             entry_type,
+            level,
+            key,
+            params,
         };
+        // Sequence numbers restart with the app, so the key also carries the time
+        let key = format!("{}:{}", entry.timestamp.timestamp_millis(), entry.seq);
+        self.persist(Table::Activity, &key, &entry);
         self.activity_log.push(entry);
 
         // Keep last 500 entries
@@ -325,16 +3606,401 @@ impl TradingEngine {
         }
     }
 
+    /// Count a repetitive event to be reported as a single summary line at the end of the cycle.
+    /// `key` is a message key taking a `count` parameter.
+    fn tally(&mut self, key: &str) {
+        match self.tallies.iter_mut().find(|(k, _)| k == key) {
+            Some((_, count)) => *count += 1,
+            None => self.tallies.push((key.to_string(), 1)),
+        }
+    }
+
+    fn flush_tallies(&mut self) {
+        for (key, count) in std::mem::take(&mut self.tallies) {
+            self.add_message(Message::new(&key).arg("count", count), ActivityType::Info);
+        }
+    }
+
+    fn persist<T: serde::Serialize>(&mut self, table: Table, key: &str, row: &T) {
+        if let Some(ref mut db) = self.db {
+            let written = db.upsert(table, key, row);
+            self.record_db_write(written.map(|_| ()));
+        }
+    }
+
+    fn record_cost_mark(&mut self) {
+        let now = Utc::now();
+        self.cost_marks.push((now, self.stats.api_costs));
+        let cutoff = now - chrono::Duration::days(reports::COST_RETENTION_DAYS);
+        let stale = self.cost_marks.partition_point(|(t, _)| *t < cutoff);
+        self.cost_marks.drain(..stale);
+    }
+
+    /// Sync orders, fill and settlement events, and the session row to the database.
+    /// Unchanged rows are skipped, so this is cheap to call every cycle.
+    fn persist_history(&mut self) {
+        let Some(ref mut db) = self.db else { return };
+        let mut written = Ok(());
+        for order in &self.orders {
+            written = written.and(db.upsert(Table::Orders, &order.id, order).map(|_| ()));
+        }
+        for event in self.ledger.events() {
+            let table = match event.kind {
+                LedgerEventKind::Fill { .. } => Table::Fills,
+                LedgerEventKind::Resolution { .. } | LedgerEventKind::Exit { .. } | LedgerEventKind::Conversion { .. } => Table::Resolutions,
+                LedgerEventKind::OrderPlaced { .. } | LedgerEventKind::Deposit { .. } | LedgerEventKind::Withdrawal { .. } => Table::Ledger,
+            };
+            written = written.and(db.upsert(table, &event.id, &db::ledger_row(event)).map(|_| ()));
+        }
+        if let Some(ref session) = self.session {
+            written = written.and(db.upsert(Table::Sessions, &session.id, session).map(|_| ()));
+        }
+        self.record_db_write(written);
+    }
+
+    fn record_db_write(&mut self, written: Result<()>) {
+        match written {
+            Ok(()) => self.db_error = None,
+            Err(e) => {
+                if self.db_error.is_none() {
+                    log::warn!("History write failed: {:#}", e);
+                }
+                self.db_error = Some(format!("{:#}", e));
+            }
+        }
+    }
+
+    /// Filtered, sorted page of one history table; backs the history views and exports
+    pub fn query_history(&self, query: &HistoryQuery) -> Result<HistoryPage> {
+        let db = self.db.as_ref().ok_or(NotConfigured("History database"))?;
+        let page = db.query(query);
+        Ok(if self.guest.masks_balances() { guest::mask_history(page) } else { page })
+    }
+
+    /// Performance between `from` and `to`, from the ledger, balance history, signals and
+    /// the important activity of the period
+    pub fn build_report(&self, period: ReportPeriod, from: chrono::DateTime<Utc>, to: chrono::DateTime<Utc>) -> PerformanceReport {
+        let in_period = |t: &chrono::DateTime<Utc>| *t >= from && *t < to;
+        let balance_at = |t: chrono::DateTime<Utc>| {
+            self.balance_history.iter().take_while(|p| p.timestamp <= t).last().map(|p| p.balance)
+        };
+        let start_balance = balance_at(from)
+            .or_else(|| self.balance_history.first().map(|p| p.balance))
+            .unwrap_or(self.stats.initial_balance);
+        let market_name = |order_id: &str, market_id: &str| {
+            self.orders.iter()
+                .find(|o| o.id == order_id)
+                .map(|o| truncate_str(&o.market_name, 60))
+                .unwrap_or_else(|| market_id.to_string())
+        };
+
+        let mut report = PerformanceReport {
+            period,
+            from,
+            to,
+            generated_at: Utc::now(),
+            start_balance,
+            end_balance: balance_at(to).unwrap_or(start_balance),
+            realized_pnl: 0.0,
+            trades_placed: 0,
+            volume: 0.0,
+            wins: 0,
+            losses: 0,
+            best_trade: None,
+            worst_trade: None,
+            api_costs: 0.0,
+            signals_recorded: self.signals.iter().filter(|s| in_period(&s.timestamp)).count() as u32,
+            calibration: Vec::new(),
+            notable_events: self.notable_events(from, to),
+            path: None,
+        };
+
+        // Ledgers from before imported trades were kept out still hold their placements
+        let imported: HashSet<&str> = self.orders.iter().filter(|o| o.is_imported()).map(|o| o.id.as_str()).collect();
+        for event in self.ledger.events().iter().filter(|e| in_period(&e.timestamp)) {
+            let (order_id, market_id, pnl, counted) = match event.kind {
+                LedgerEventKind::OrderPlaced { ref order_id, .. } if imported.contains(order_id.as_str()) => continue,
+                LedgerEventKind::OrderPlaced { size, .. } => {
+                    report.trades_placed += 1;
+                    report.volume += size;
+                    continue;
+                }
+                LedgerEventKind::Resolution { ref order_id, ref market_id, pnl, counted } => (order_id, market_id, pnl, counted),
+                LedgerEventKind::Exit { ref order_id, ref market_id, pnl, fraction } => (order_id, market_id, pnl, fraction >= 1.0),
+                LedgerEventKind::Conversion { cost, proceeds, .. } => {
+                    report.realized_pnl += proceeds - cost;
+                    continue;
+                }
+                _ => continue,
+            };
+            report.realized_pnl += pnl;
+            if !counted {
+                continue;
+            }
+            if pnl > 0.0 {
+                report.wins += 1;
+            } else {
+                report.losses += 1;
+            }
+            if report.best_trade.as_ref().is_none_or(|t| pnl > t.pnl) {
+                report.best_trade = Some(ReportTrade { market: market_name(order_id, market_id), pnl });
+            }
+            if report.worst_trade.as_ref().is_none_or(|t| pnl < t.pnl) {
+                report.worst_trade = Some(ReportTrade { market: market_name(order_id, market_id), pnl });
+            }
+        }
+
+        // The cost estimate restarts with the app, so only increases count
+        report.api_costs = self.cost_marks
+            .windows(2)
+            .filter(|w| in_period(&w[1].0))
+            .map(|w| (w[1].1 - w[0].1).max(0.0))
+            .sum();
+
+        let mut calibration: Vec<CategoryCalibration> = scoring::category_calibration(&self.signals)
+            .into_iter()
+            .map(|(category, score)| CategoryCalibration { category, score })
+            .collect();
+        calibration.sort_by(|a, b| b.score.total_cmp(&a.score));
+        report.calibration = calibration;
+        report
+    }
+
+    /// Important activity in the period, newest first; read from the database when attached
+    /// since the in-memory log only keeps the last few hundred entries
+    fn notable_events(&self, from: chrono::DateTime<Utc>, to: chrono::DateTime<Utc>) -> Vec<NotableEvent> {
+        let entries: Vec<ActivityEntry> = match self.db {
+            Some(ref db) => {
+                let mut query = HistoryQuery {
+                    table: Table::Activity,
+                    filter: Default::default(),
+                    sort: Default::default(),
+                    page: Default::default(),
+                };
+                query.filter.from = Some(from);
+                query.filter.to = Some(to);
+                query.filter.fields.insert("level".to_string(), serde_json::json!(LogLevel::Important));
+                query.page.size = reports::MAX_NOTABLE_EVENTS as u32;
+                db.query(&query)
+                    .rows
+                    .into_iter()
+                    .filter_map(|row| serde_json::from_value(row).ok())
+                    .collect()
+            }
+            None => self.activity_log.iter()
+                .rev()
+                .filter(|e| e.level == LogLevel::Important && e.timestamp >= from && e.timestamp < to)
+                .take(reports::MAX_NOTABLE_EVENTS)
+                .cloned()
+                .collect(),
+        };
+        entries
+            .into_iter()
+            .map(|e| NotableEvent {
+                timestamp: e.timestamp,
+                message: match e.key {
+                    Some(key) => Message { key, params: e.params }.render(self.locale),
+                    None => e.message,
+                },
+            })
+            .collect()
+    }
+
+    /// Log a report written by the scheduler and send its summary to the notifier
+    pub fn report_written(&mut self, report: &PerformanceReport, written: Result<()>) {
+        match written {
+            Ok(()) => {
+                let path = report.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+                self.notifications.notify(NotificationKind::Report, report.period.title(), &report.summary());
+                self.add_message(Message::new("report.written").arg("summary", report.summary()).arg("path", path), ActivityType::Info);
+            }
+            Err(e) => {
+                self.add_message(Message::new("report.failed").arg("error", format!("{:#}", e)), ActivityType::Error);
+            }
+        }
+    }
+
     pub fn get_stats(&self) -> BotStats {
-        self.stats.clone()
+        if self.guest.masks_balances() {
+            guest::mask_stats(&self.stats)
+        } else {
+            self.stats.clone()
+        }
     }
 
-    pub fn get_activity_log(&self) -> Vec<ActivityEntry> {
-        self.activity_log.clone()
+    /// The config as shown to the UI; secrets are redacted in guest mode
+    pub fn get_config(&self) -> BotConfig {
+        if self.guest.enabled {
+            guest::redact_config(&self.config)
+        } else {
+            self.config.clone()
+        }
+    }
+
+    /// Turning guest mode on takes an unlock PIN. While it is on, turning it off or unmasking
+    /// balances needs that PIN again, so it cannot be lifted from the shared screen.
+    pub fn set_guest_mode(&mut self, enabled: bool, mask_balances: bool, pin: Option<&str>) -> Result<GuestMode> {
+        let pin = pin.filter(|p| !p.is_empty());
+        if self.guest.enabled {
+            let lowers = !enabled || (self.guest.mask_balances && !mask_balances);
+            let unlocked = pin.map(guest::hash_pin) == self.guest_pin;
+            if lowers && !unlocked {
+                return Err(ConfigError::new("pin", "Wrong unlock PIN").into());
+            }
+        } else if enabled {
+            let pin = pin.ok_or_else(|| ConfigError::new("pin", "Set an unlock PIN to turn on guest mode"))?;
+            self.guest_pin = Some(guest::hash_pin(pin));
+        }
+        if !enabled {
+            self.guest_pin = None;
+        }
+        self.guest = GuestMode { enabled, mask_balances };
+        Ok(self.guest)
+    }
+
+    pub fn get_ledger(&self) -> Vec<LedgerEvent> {
+        let events = self.ledger.events().to_vec();
+        if self.guest.masks_balances() {
+            guest::mask_ledger(events)
+        } else {
+            events
+        }
+    }
+
+    /// Refuse state changes requested from the UI while in guest mode
+    pub fn ensure_writable(&self) -> Result<()> {
+        if self.guest.enabled {
+            return Err(GuestModeBlocked.into());
+        }
+        Ok(())
+    }
+
+    /// Activities at or above the configured feed level, for the UI
+    pub fn get_activity_feed(&self) -> Arc<Vec<ActivityEntry>> {
+        let key = self.view_key(self.activity_seq, self.config.feed_log_level);
+        self.feed_view.get(key, || self.render_activity_since(0, self.config.feed_log_level))
+    }
+
+    /// Every persisted activity regardless of feed level
+    pub fn get_activity_log(&self) -> Arc<Vec<ActivityEntry>> {
+        let key = self.view_key(self.activity_seq, LogLevel::Debug);
+        self.activity_view.get(key, || self.render_activity_since(0, LogLevel::Debug))
+    }
+
+    /// Feed entries added after `cursor`, rendering only those. A cursor from before a
+    /// restart, or one already trimmed out of the log, returns the whole feed instead.
+    pub fn get_activity_since(&self, cursor: u64) -> ActivityDelta {
+        let oldest = self.activity_log.first().map(|e| e.seq).unwrap_or(self.activity_seq + 1);
+        let reset = cursor > self.activity_seq || (cursor > 0 && cursor + 1 < oldest);
+        let since = if reset { 0 } else { cursor };
+        ActivityDelta {
+            entries: self.render_activity_since(since, self.config.feed_log_level),
+            cursor: self.activity_seq,
+            reset,
+        }
+    }
+
+    fn render_activity_since(&self, since: u64, min_level: LogLevel) -> Vec<ActivityEntry> {
+        let tz = &self.config.display_timezone;
+        let mask = self.guest.masks_balances();
+        // Entries are in sequence order, so only the tail past `since` is visited
+        let start = self.activity_log.partition_point(|e| e.seq <= since);
+        self.activity_log[start..]
+            .iter()
+            .filter(|e| e.level >= min_level)
+            .map(|e| {
+                let message = match &e.key {
+                    Some(key) => Message { key: key.clone(), params: e.params.clone() }.render(self.locale),
+                    None => e.message.clone(),
+                };
+                ActivityEntry {
+                    local_time: time::format_display(&e.timestamp, tz),
+                    message: if mask { guest::mask_amounts(&message) } else { message },
+                    ..e.clone()
+                }
+            })
+            .collect()
+    }
+
+    pub fn get_balance_history(&self) -> Arc<Vec<BalancePoint>> {
+        // Points are only ever appended, so the length identifies the history
+        let key = self.view_key(self.balance_history.len() as u64, LogLevel::Debug);
+        self.balance_view.get(key, || self.render_balance_points(0))
+    }
+
+    /// Stats when they changed since `cursor`, and balance points added after it
+    pub fn get_stats_delta(&self, cursor: StatsCursor) -> StatsDelta {
+        let stats = self.get_stats();
+        let fingerprint = snapshot::stats_revision(&stats);
+        let reset = cursor.balance_points > self.balance_history.len();
+        let from = if reset { 0 } else { cursor.balance_points };
+        StatsDelta {
+            stats: (fingerprint != cursor.stats || reset).then_some(stats),
+            balance_points: self.render_balance_points(from),
+            cursor: StatsCursor { stats: fingerprint, balance_points: self.balance_history.len() },
+            reset,
+        }
+    }
+
+    fn render_balance_points(&self, from: usize) -> Vec<BalancePoint> {
+        let tz = &self.config.display_timezone;
+        let points = self.balance_history[from..]
+            .iter()
+            .map(|p| BalancePoint { local_time: time::format_display(&p.timestamp, tz), ..p.clone() })
+            .collect();
+        if self.guest.masks_balances() {
+            guest::mask_balance_history(points, self.stats.initial_balance)
+        } else {
+            points
+        }
+    }
+
+    pub fn get_orders(&self) -> Arc<Vec<Order>> {
+        let key = self.view_key(snapshot::orders_revision(&self.orders), LogLevel::Debug);
+        self.orders_view.get(key, || {
+            if self.guest.masks_balances() {
+                guest::mask_orders(self.orders.clone())
+            } else {
+                self.orders.clone()
+            }
+        })
+    }
+
+    fn view_key(&self, revision: u64, min_level: LogLevel) -> ViewKey {
+        ViewKey {
+            revision,
+            locale: self.locale,
+            timezone: self.config.display_timezone.clone(),
+            masked: self.guest.masks_balances(),
+            min_level,
+        }
+    }
+
+    pub fn get_portfolio(&self) -> PortfolioSummary {
+        let manager = PositionManager::new(&self.config.risk, &self.orders, self.stats.current_balance);
+        let portfolio = PortfolioSummary {
+            category_utilization: manager.category_utilization(),
+            ..self.portfolio.clone()
+        };
+        if self.guest.masks_balances() {
+            guest::mask_portfolio(portfolio)
+        } else {
+            portfolio
+        }
+    }
+
+    pub fn get_latest_commentary(&self) -> Option<PortfolioCommentary> {
+        let commentary = self.latest_commentary.clone();
+        if self.guest.masks_balances() { commentary.map(guest::mask_commentary) } else { commentary }
     }
 
-    pub fn get_balance_history(&self) -> Vec<BalancePoint> {
-        self.balance_history.clone()
+    pub fn get_prompt_usage(&self) -> Vec<PromptUsage> {
+        self.claude.as_ref().map(|c| c.get_prompt_usage()).unwrap_or_default()
+    }
+
+    /// Prompt-cache (write, read) input tokens since the client was created
+    pub fn get_cache_tokens(&self) -> (u64, u64) {
+        self.claude.as_ref().map(|c| c.get_cache_tokens()).unwrap_or_default()
     }
 }
 
@@ -345,3 +4011,53 @@ fn truncate_str(s: &str, max_len: usize) -> String {
         format!("{}...", &s[..max_len])
     }
 }
+
+fn outcome_token<'m>(market: &'m Market, outcome: &str) -> Option<&'m str> {
+    let idx = market.outcomes.iter().position(|o| o == outcome)?;
+    market.token_ids.get(idx).map(|t| t.as_str())
+}
+
+/// Dollars held in an order's outcome: a filled buy in full, a working one up to its fill.
+/// Parents hold nothing themselves; their children do.
+fn held_cost(order: &Order) -> f64 {
+    if !matches!(order.side, OrderSide::Buy) || order.is_parent() || order.is_imported() {
+        return 0.0;
+    }
+    match order.status {
+        OrderStatus::Filled => order.size,
+        _ if order.status.is_working() => order.filled_size,
+        _ => 0.0,
+    }
+}
+
+fn outcome_price(market: &Market, outcome: &str) -> Option<f64> {
+    let idx = market.outcomes.iter().position(|o| o == outcome)?;
+    market.outcome_prices.get(idx).copied()
+}
+
+fn alert_message(message: Message, alert: &PriceAlert, price: Option<f64>) -> Message {
+    message
+        .arg("market", truncate_str(&alert.market_name, 40))
+        .arg("outcome", &alert.outcome)
+        .arg("condition", alert.condition.symbol())
+        .arg("threshold", format!("{:.3}", alert.threshold))
+        .arg("price", price.map(|p| format!("{:.3}", p)).unwrap_or_else(|| "-".to_string()))
+}
+
+fn conditional_message(message: Message, order: &ConditionalOrder) -> Message {
+    message
+        .arg("market", truncate_str(&order.market_name, 40))
+        .arg("outcome", &order.outcome)
+        .arg("trigger", conditional::describe_trigger(&order.trigger))
+        .arg("price", order.last_price.map(|p| format!("{:.3}", p)).unwrap_or_else(|| "-".to_string()))
+}
+
+fn exit_message(message: Message, market_name: &str, decision: &ExitDecision) -> Message {
+    message
+        .arg("market", truncate_str(market_name, 40))
+        .arg("bid", format!("{:.3}", decision.bid))
+        .arg("fair", format!("{:.3}", decision.fair))
+        .arg("edge", format!("{:+.3}", decision.edge))
+        .arg("ratio", format!("{:.2}", decision.risk_ratio))
+        .arg("hours", format!("{:.0}", decision.hours_left))
+}
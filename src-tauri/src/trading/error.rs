@@ -0,0 +1,86 @@
+use serde::Serialize;
+use super::config::ConfigError;
+use super::guest::GUEST_MODE_ERROR;
+use super::http::ApiError;
+
+/// Error returned by every Tauri command, tagged by `kind` so the frontend can
+/// branch on it instead of matching message text
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind")]
+pub enum CommandError {
+    /// A client the command needs has not been set up by `save_config`
+    NotConfigured { message: String },
+    /// An upstream API throttled the request
+    RateLimited { message: String, retry_after_secs: Option<u64> },
+    /// An upstream API (exchange, Claude, RPC) failed; `code` is the HTTP status when known
+    ExchangeError { code: Option<u16>, message: String },
+    ValidationError { fields: Vec<ConfigError> },
+    /// The engine is in the middle of a cycle; retry shortly
+    Busy { message: String },
+    /// Refused while guest mode is on
+    GuestMode { message: String },
+    NotFound { message: String },
+    Internal { message: String },
+}
+
+impl CommandError {
+    pub fn busy() -> Self {
+        Self::Busy { message: "A cycle is in progress".to_string() }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound { message: message.into() }
+    }
+
+    pub fn internal(message: impl std::fmt::Display) -> Self {
+        Self::Internal { message: message.to_string() }
+    }
+}
+
+/// A client the operation needs is missing, e.g. `NotConfigured("Chain client")`
+#[derive(Debug, thiserror::Error)]
+#[error("{0} not configured")]
+pub struct NotConfigured(pub &'static str);
+
+#[derive(Debug, thiserror::Error)]
+#[error("{}", GUEST_MODE_ERROR)]
+pub struct GuestModeBlocked;
+
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct NotFound(pub String);
+
+impl From<anyhow::Error> for CommandError {
+    fn from(err: anyhow::Error) -> Self {
+        let message = format!("{:#}", err);
+        if let Some(api) = err.downcast_ref::<ApiError>() {
+            return if api.status == 429 {
+                Self::RateLimited { message, retry_after_secs: api.retry_after_secs }
+            } else {
+                Self::ExchangeError { code: Some(api.status), message }
+            };
+        }
+        if let Some(field) = err.downcast_ref::<ConfigError>() {
+            return Self::ValidationError { fields: vec![field.clone()] };
+        }
+        if err.is::<NotConfigured>() {
+            return Self::NotConfigured { message };
+        }
+        if err.is::<GuestModeBlocked>() {
+            return Self::GuestMode { message };
+        }
+        if err.is::<NotFound>() {
+            return Self::NotFound { message };
+        }
+        if let Some(http) = err.downcast_ref::<reqwest::Error>() {
+            return Self::ExchangeError { code: http.status().map(|s| s.as_u16()), message };
+        }
+        Self::Internal { message }
+    }
+}
+
+impl From<Vec<ConfigError>> for CommandError {
+    fn from(fields: Vec<ConfigError>) -> Self {
+        Self::ValidationError { fields }
+    }
+}
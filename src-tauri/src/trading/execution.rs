@@ -0,0 +1,313 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::microstructure::MicrostructureSignals;
+use super::models::*;
+use super::config;
+use super::risk::{self, PreflightRisk};
+
+/// Book levels kept per side in an execution report
+const REPORT_BOOK_LEVELS: usize = 10;
+/// Share quantities are accepted with two decimals
+const SHARE_LOT: f64 = 0.01;
+
+/// Used when a market does not publish its own order rules
+pub const DEFAULT_TICK_SIZE: f64 = 0.01;
+pub const DEFAULT_MIN_ORDER_SIZE: f64 = 5.0;
+
+/// An order price and size rounded to the exchange's increments
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalizedOrder {
+    pub price: f64,
+    pub shares: f64,
+    /// Dollar value, `price * shares`
+    pub notional: f64,
+}
+
+/// Round a price to the tick (down for buys, up for sells, so rounding never worsens the
+/// limit) and a dollar size to whole lots, rejecting orders under the minimum size
+pub fn normalize_order(
+    price: f64,
+    notional: f64,
+    side: &OrderSide,
+    tick_size: f64,
+    min_order_size: f64,
+) -> Result<NormalizedOrder, String> {
+    let tick = if tick_size > 0.0 { tick_size } else { DEFAULT_TICK_SIZE };
+    let ticks = price / tick;
+    // Absorb float noise so 0.57 / 0.01 does not round to 56
+    let ticks = match side {
+        OrderSide::Buy => (ticks + 1e-9).floor(),
+        OrderSide::Sell => (ticks - 1e-9).ceil(),
+    };
+    let price = round_to(ticks * tick, tick).clamp(tick, 1.0 - tick);
+
+    let shares = round_to(((notional / price) / SHARE_LOT + 1e-9).floor() * SHARE_LOT, SHARE_LOT);
+    if shares < min_order_size {
+        return Err(format!(
+            "{:.2} shares @ {:.3} is below the {:.2} share minimum",
+            shares, price, min_order_size
+        ));
+    }
+
+    Ok(NormalizedOrder { price, shares, notional: shares * price })
+}
+
+/// Keep entries inside the configured price band: near 0 or 1 the payoff is lopsided and
+/// a tick of slippage or fees is a large share of the remaining upside
+pub fn check_price_band(price: f64, min_price: f64, max_price: f64) -> Result<(), String> {
+    if price < min_price || price > max_price {
+        return Err(format!("entry {:.3} is outside the {:.2}–{:.2} price band", price, min_price, max_price));
+    }
+    Ok(())
+}
+
+/// Which outcome a category may be bought on, and at what prices; e.g. no Yes buys
+/// above 0.85 in sports is `{"category": "sports", "outcome": "Yes", "max_price": 0.85}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SideGuardrail {
+    /// Category the rule applies to, `*` for all of them
+    pub category: String,
+    /// Outcome the rule applies to (case-insensitive), empty for any outcome
+    pub outcome: String,
+    /// Refuse buys of the outcome altogether
+    pub blocked: bool,
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub min_price: f64,
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub max_price: f64,
+}
+
+impl Default for SideGuardrail {
+    fn default() -> Self {
+        Self { category: "*".to_string(), outcome: String::new(), blocked: false, min_price: 0.0, max_price: 1.0 }
+    }
+}
+
+impl SideGuardrail {
+    fn applies(&self, category: &str, outcome: &str) -> bool {
+        let rule_category = self.category.trim();
+        (rule_category == "*" || risk::category_key(Some(rule_category)) == category)
+            && (self.outcome.trim().is_empty() || self.outcome.trim().eq_ignore_ascii_case(outcome))
+    }
+}
+
+/// Check a buy of `outcome` at `price` against every guardrail covering its category;
+/// the error names the first rule it breaks
+pub fn check_side_guardrails(rules: &[SideGuardrail], category: Option<&str>, outcome: &str, price: f64) -> Result<(), String> {
+    let category = risk::category_key(category);
+    for rule in rules.iter().filter(|r| r.applies(&category, outcome)) {
+        if rule.blocked {
+            return Err(format!("{} buys are disabled in {}", outcome, category));
+        }
+        if price < rule.min_price || price > rule.max_price {
+            return Err(format!(
+                "{} at {:.3} is outside the {:.2}–{:.2} allowed in {}",
+                outcome, price, rule.min_price, rule.max_price, category
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Trim float artifacts by rounding to the decimal places of `step`
+fn round_to(value: f64, step: f64) -> f64 {
+    let decimals = (-step.log10()).ceil().max(0.0) as i32 + 1;
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+/// A child order waiting to be placed as part of a sliced parent order
+#[derive(Debug, Clone)]
+pub struct ScheduledSlice {
+    pub parent_id: String,
+    pub child_id: String,
+    pub size: f64,
+    /// Average price the slice fills at, from the book when the order was sliced
+    pub price: f64,
+    pub due_at: DateTime<Utc>,
+}
+
+/// Which side of the spread a fill took
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Liquidity {
+    /// Rested passively at or inside the spread
+    Maker,
+    /// Crossed the spread against a resting order
+    Taker,
+}
+
+/// Market and model state captured at the moment an order was placed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    pub order_id: String,
+    pub market_id: String,
+    pub outcome: String,
+    pub timestamp: DateTime<Utc>,
+    pub order_price: f64,
+    pub order_size: f64,
+    /// Top levels of the outcome's book; `None` when it could not be fetched
+    pub book: Option<OrderBook>,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+    pub mid_price: Option<f64>,
+    pub spread: Option<f64>,
+    #[serde(default)]
+    pub microstructure: Option<MicrostructureSignals>,
+    /// Portfolio risk simulated before the order was placed
+    #[serde(default)]
+    pub preflight: Option<PreflightRisk>,
+    /// Maker or taker, from the limit price against the book; `None` without a book
+    #[serde(default)]
+    pub liquidity: Option<Liquidity>,
+    /// Price the fill gets: a marketable limit fills at the touch, a passive one at its limit
+    #[serde(default)]
+    pub fill_price: Option<f64>,
+    /// Per share, relative to the mid: positive when captured, negative when paid
+    #[serde(default)]
+    pub spread_captured: Option<f64>,
+    pub prediction: AIPrediction,
+}
+
+impl ExecutionReport {
+    pub fn new(order: &Order, prediction: &AIPrediction, book: Option<&OrderBook>) -> Self {
+        let best_bid = book.and_then(|b| b.bids.iter().map(|l| l.price).max_by(f64::total_cmp));
+        let best_ask = book.and_then(|b| b.asks.iter().map(|l| l.price).min_by(f64::total_cmp));
+        let (mid_price, spread) = match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) => (Some((bid + ask) / 2.0), Some(ask - bid)),
+            _ => (None, None),
+        };
+        // Buys cross at the ask, sells at the bid
+        let (touch, sign) = match order.side {
+            OrderSide::Buy => (best_ask, 1.0),
+            OrderSide::Sell => (best_bid, -1.0),
+        };
+        let (liquidity, fill_price) = match touch {
+            Some(touch) if sign * (order.price - touch) >= -1e-9 => (Some(Liquidity::Taker), Some(touch)),
+            Some(_) => (Some(Liquidity::Maker), Some(order.price)),
+            None => (None, None),
+        };
+        let spread_captured = mid_price.zip(fill_price).map(|(mid, fill)| sign * (mid - fill));
+
+        Self {
+            order_id: order.id.clone(),
+            market_id: order.market_id.clone(),
+            outcome: order.outcome.clone(),
+            timestamp: order.created_at,
+            order_price: order.price,
+            order_size: order.size,
+            book: book.map(top_levels),
+            best_bid,
+            best_ask,
+            mid_price,
+            spread,
+            microstructure: None,
+            preflight: None,
+            liquidity,
+            fill_price,
+            spread_captured,
+            prediction: prediction.clone(),
+        }
+    }
+}
+
+/// Maker/taker split and spread economics across execution reports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecutionQuality {
+    pub fills: u32,
+    pub maker_fills: u32,
+    pub taker_fills: u32,
+    /// Fills placed without a book to classify them against
+    pub unclassified_fills: u32,
+    pub maker_volume: f64,
+    pub taker_volume: f64,
+    /// Share of classified volume filled passively
+    pub maker_share: Option<f64>,
+    /// Dollars earned against the mid by maker fills
+    pub spread_captured: f64,
+    /// Dollars given up against the mid by taker fills
+    pub spread_paid: f64,
+    /// `spread_paid` per dollar of taker volume, in basis points
+    pub taker_cost_bps: Option<f64>,
+    /// Dollars taker fills would have saved resting at the best bid (best ask for sells).
+    /// An upper bound: passive orders also risk not filling at all.
+    pub passive_savings: f64,
+}
+
+pub fn execution_quality<'a>(reports: impl IntoIterator<Item = &'a ExecutionReport>) -> ExecutionQuality {
+    let mut quality = ExecutionQuality::default();
+    for report in reports {
+        quality.fills += 1;
+        let (Some(liquidity), Some(fill), Some(captured)) = (report.liquidity, report.fill_price, report.spread_captured) else {
+            quality.unclassified_fills += 1;
+            continue;
+        };
+        let shares = if report.order_price > 0.0 { report.order_size / report.order_price } else { 0.0 };
+        let volume = shares * fill;
+        match liquidity {
+            Liquidity::Maker => {
+                quality.maker_fills += 1;
+                quality.maker_volume += volume;
+                quality.spread_captured += captured * shares;
+            }
+            Liquidity::Taker => {
+                quality.taker_fills += 1;
+                quality.taker_volume += volume;
+                quality.spread_paid -= captured * shares;
+                if let Some(spread) = report.spread {
+                    quality.passive_savings += spread * shares;
+                }
+            }
+        }
+    }
+    let classified = quality.maker_volume + quality.taker_volume;
+    quality.maker_share = (classified > 0.0).then(|| quality.maker_volume / classified);
+    quality.taker_cost_bps = (quality.taker_volume > 0.0).then(|| quality.spread_paid / quality.taker_volume * 10_000.0);
+    quality
+}
+
+fn top_levels(book: &OrderBook) -> OrderBook {
+    let mut bids = book.bids.clone();
+    let mut asks = book.asks.clone();
+    bids.sort_by(|a, b| b.price.total_cmp(&a.price));
+    asks.sort_by(|a, b| a.price.total_cmp(&b.price));
+    bids.truncate(REPORT_BOOK_LEVELS);
+    asks.truncate(REPORT_BOOK_LEVELS);
+    OrderBook { bids, asks }
+}
+
+/// Dollar depth available at the best level on the side we would take from
+pub fn top_of_book_depth(book: &OrderBook, side: &OrderSide) -> f64 {
+    let level = match side {
+        OrderSide::Buy => book.asks.iter().min_by(|a, b| a.price.total_cmp(&b.price)),
+        OrderSide::Sell => book.bids.iter().max_by(|a, b| a.price.total_cmp(&b.price)),
+    };
+    level.map(|l| l.price * l.size).unwrap_or(0.0)
+}
+
+/// Split `total` into equal slices no larger than the top-of-book depth, spaced
+/// `interval_secs` apart (TWAP-style). The first slice is due immediately.
+pub fn plan_slices(
+    parent_id: &str,
+    total: f64,
+    depth: f64,
+    max_slices: u32,
+    interval_secs: u32,
+    price: f64,
+    now: DateTime<Utc>,
+) -> Vec<ScheduledSlice> {
+    let needed = if depth > 0.0 { (total / depth).ceil() as u32 } else { max_slices };
+    let count = needed.clamp(2, max_slices.max(2));
+    let size = total / count as f64;
+
+    (0..count)
+        .map(|i| ScheduledSlice {
+            parent_id: parent_id.to_string(),
+            child_id: Uuid::new_v4().to_string(),
+            size,
+            price,
+            due_at: now + Duration::seconds(i as i64 * interval_secs as i64),
+        })
+        .collect()
+}
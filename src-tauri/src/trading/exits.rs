@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use super::config;
+
+/// When and how to leave positions before their markets resolve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExitConfig {
+    pub enabled: bool,
+    /// Positions are reviewed once their market resolves within this many hours
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub window_hours: u32,
+    /// Holding needs at least this much edge per unit of resolution risk (edge / σ)
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub min_risk_ratio: f64,
+    /// Share of the position sold when the edge no longer pays for the risk
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub reduce_fraction: f64,
+}
+
+impl Default for ExitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_hours: 72,
+            min_risk_ratio: 0.1,
+            reduce_fraction: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ExitAction {
+    Hold,
+    /// Sell this fraction of the position
+    Reduce(f64),
+    Close,
+}
+
+/// A hold/exit decision with the numbers behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitDecision {
+    pub action: ExitAction,
+    pub bid: f64,
+    pub fair: f64,
+    /// Expected gain per share from holding instead of selling at the bid
+    pub edge: f64,
+    /// `edge` over the standard deviation of the binary payoff, sqrt(fair * (1 - fair))
+    pub risk_ratio: f64,
+    pub hours_left: f64,
+}
+
+/// Compare selling at the bid now with holding a binary that pays 1 with probability `fair`.
+/// Close once the market pays at least fair value; reduce when the remaining edge is
+/// small next to the payoff's variance; otherwise hold to resolution.
+pub fn plan_exit(bid: f64, fair: f64, hours_left: f64, config: &ExitConfig) -> ExitDecision {
+    let edge = fair - bid;
+    let sigma = (fair * (1.0 - fair)).max(0.0).sqrt();
+    let risk_ratio = if sigma > 0.0 { edge / sigma } else if edge > 0.0 { f64::INFINITY } else { 0.0 };
+
+    let action = if edge <= 0.0 {
+        ExitAction::Close
+    } else if risk_ratio < config.min_risk_ratio {
+        ExitAction::Reduce(config.reduce_fraction)
+    } else {
+        ExitAction::Hold
+    };
+
+    ExitDecision { action, bid, fair, edge, risk_ratio, hours_left }
+}
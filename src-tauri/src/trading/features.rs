@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::models::*;
+use super::time;
+
+/// Points per day returned by the CLOB price history at hourly fidelity
+const POINTS_PER_DAY: f64 = 24.0;
+
+/// Compact per-market indicators computed locally and summarized in the analysis prompt
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketFeatures {
+    /// Change in the first outcome's price over the last 7 days
+    pub price_change_7d: Option<f64>,
+    /// Standard deviation of daily price changes
+    pub realized_volatility: Option<f64>,
+    /// Least-squares slope of the implied probability, per day
+    pub probability_drift: Option<f64>,
+    /// Last 24h volume relative to the 7-day daily average (1.0 = flat)
+    pub volume_trend: Option<f64>,
+    pub days_to_resolution: Option<f64>,
+}
+
+impl MarketFeatures {
+    pub fn compute(market: &Market, history: &[PricePoint], now: DateTime<Utc>) -> Self {
+        let window: Vec<&PricePoint> = history
+            .iter()
+            .filter(|p| now - p.timestamp <= chrono::Duration::days(7))
+            .collect();
+
+        let price_change_7d = match (window.first(), window.last()) {
+            (Some(first), Some(last)) if window.len() >= 2 => Some(last.price - first.price),
+            _ => None,
+        };
+
+        let realized_volatility = if window.len() >= 3 {
+            let changes: Vec<f64> = window.windows(2).map(|w| w[1].price - w[0].price).collect();
+            let mean = changes.iter().sum::<f64>() / changes.len() as f64;
+            let variance = changes.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / (changes.len() - 1) as f64;
+            Some(variance.sqrt() * POINTS_PER_DAY.sqrt())
+        } else {
+            None
+        };
+
+        let probability_drift = if window.len() >= 3 {
+            let origin = window[0].timestamp;
+            let xs: Vec<f64> = window
+                .iter()
+                .map(|p| (p.timestamp - origin).num_seconds() as f64 / 86_400.0)
+                .collect();
+            let ys: Vec<f64> = window.iter().map(|p| p.price).collect();
+            slope(&xs, &ys)
+        } else {
+            None
+        };
+
+        let daily_average = market.volume_1wk / 7.0;
+        let volume_trend = (daily_average > 0.0).then(|| market.volume_24h / daily_average);
+
+        let days_to_resolution = market.end_date
+            .as_deref()
+            .and_then(time::parse_timestamp)
+            .map(|end| ((end - now).num_seconds() as f64 / 86_400.0).max(0.0));
+
+        Self {
+            price_change_7d,
+            realized_volatility,
+            probability_drift,
+            volume_trend,
+            days_to_resolution,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.price_change_7d.is_none()
+            && self.realized_volatility.is_none()
+            && self.probability_drift.is_none()
+            && self.volume_trend.is_none()
+            && self.days_to_resolution.is_none()
+    }
+
+    /// One-line summary for the prompt, omitting indicators without enough data
+    pub fn to_prompt_line(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(change) = self.price_change_7d {
+            parts.push(format!("7d change {:+.3}", change));
+        }
+        if let Some(vol) = self.realized_volatility {
+            parts.push(format!("daily vol {:.3}", vol));
+        }
+        if let Some(drift) = self.probability_drift {
+            parts.push(format!("drift {:+.4}/day", drift));
+        }
+        if let Some(trend) = self.volume_trend {
+            parts.push(format!("24h volume {:.1}x weekly avg", trend));
+        }
+        if let Some(days) = self.days_to_resolution {
+            parts.push(format!("{:.1}d to resolution", days));
+        }
+        format!("Features: {}", parts.join(" | "))
+    }
+}
+
+fn slope(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let covariance: f64 = xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    (variance > 0.0).then(|| covariance / variance)
+}
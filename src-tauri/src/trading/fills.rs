@@ -0,0 +1,179 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use super::config;
+use super::models::*;
+
+/// Prices closer than this are the same book level
+const PRICE_EPSILON: f64 = 1e-9;
+
+/// How paper orders fill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaperFillConfig {
+    /// Fill only what crosses the book at placement and rest the remainder behind the size
+    /// already queued at its price, filling it from trade flow. Off fills every paper order
+    /// in full when placed.
+    pub queue_model: bool,
+    /// Unfilled remainders are cancelled after this long
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub max_rest_secs: u32,
+}
+
+impl Default for PaperFillConfig {
+    fn default() -> Self {
+        Self { queue_model: false, max_rest_secs: 3600 }
+    }
+}
+
+/// A paper order's simulated place on the book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestingOrder {
+    pub order_id: String,
+    pub market_id: String,
+    pub token_id: String,
+    pub side: OrderSide,
+    pub price: f64,
+    /// Shares still to fill
+    pub remaining: f64,
+    /// Shares queued at our price ahead of us; trades at the price consume these first
+    pub queue_ahead: f64,
+    /// Trades at or before this time have already been matched
+    pub matched_until: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub fills: u32,
+}
+
+impl RestingOrder {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Trade or book price that reaches past our limit, filling everything still queued
+    fn through(&self, price: f64) -> bool {
+        match self.side {
+            OrderSide::Buy => price < self.price - PRICE_EPSILON,
+            OrderSide::Sell => price > self.price + PRICE_EPSILON,
+        }
+    }
+
+    /// Apply trades since the last match, oldest first, and any liquidity in `book` that now
+    /// crosses the limit. Returns the shares filled.
+    pub fn match_flow(&mut self, trades: &[MarketTrade], book: Option<&OrderBook>) -> f64 {
+        let mut trades: Vec<&MarketTrade> = trades
+            .iter()
+            .filter(|t| t.asset == self.token_id && t.timestamp > self.matched_until)
+            .collect();
+        trades.sort_by_key(|t| t.timestamp);
+
+        let mut filled = 0.0;
+        for trade in trades {
+            self.matched_until = trade.timestamp;
+            if self.remaining <= 0.0 {
+                break;
+            }
+            let fill = if self.through(trade.price) {
+                self.remaining
+            } else if (trade.price - self.price).abs() <= PRICE_EPSILON {
+                let past_queue = (trade.size - self.queue_ahead).max(0.0);
+                self.queue_ahead = (self.queue_ahead - trade.size).max(0.0);
+                past_queue.min(self.remaining)
+            } else {
+                continue;
+            };
+            self.remaining -= fill;
+            filled += fill;
+        }
+
+        if let Some(book) = book {
+            let fill = crossing_size(book, &self.side, self.price).min(self.remaining);
+            self.remaining -= fill;
+            filled += fill;
+        }
+        if filled > 0.0 {
+            self.fills += 1;
+        }
+        filled
+    }
+}
+
+/// Opposite-side shares priced at or better than `price`, which a new order takes at once
+fn crossing_size(book: &OrderBook, side: &OrderSide, price: f64) -> f64 {
+    match side {
+        OrderSide::Buy => book.asks.iter().filter(|l| l.price <= price + PRICE_EPSILON).map(|l| l.size).sum(),
+        OrderSide::Sell => book.bids.iter().filter(|l| l.price >= price - PRICE_EPSILON).map(|l| l.size).sum(),
+    }
+}
+
+/// Take opposite-side levels priced at or better than `limit`, best first, until `budget`
+/// dollars are spent. Returns the dollars spent and the shares they bought.
+pub fn sweep(book: &OrderBook, side: &OrderSide, limit: f64, budget: f64) -> (f64, f64) {
+    let mut levels: Vec<&BookLevel> = match side {
+        OrderSide::Buy => book.asks.iter().filter(|l| l.price <= limit + PRICE_EPSILON).collect(),
+        OrderSide::Sell => book.bids.iter().filter(|l| l.price >= limit - PRICE_EPSILON).collect(),
+    };
+    match side {
+        OrderSide::Buy => levels.sort_by(|a, b| a.price.total_cmp(&b.price)),
+        OrderSide::Sell => levels.sort_by(|a, b| b.price.total_cmp(&a.price)),
+    }
+
+    let (mut spent, mut shares) = (0.0, 0.0);
+    for level in levels.into_iter().filter(|l| l.price > 0.0) {
+        let take = level.size.min((budget - spent) / level.price);
+        if take <= 0.0 {
+            break;
+        }
+        spent += take * level.price;
+        shares += take;
+    }
+    (spent, shares)
+}
+
+/// Average price of a paper order filled in full at once: the levels it crosses at their
+/// own prices, and whatever they cannot cover at its limit
+pub fn average_fill_price(order: &Order, book: &OrderBook) -> f64 {
+    let (spent, shares) = sweep(book, &order.side, order.price, order.size);
+    let at_limit = if order.price > 0.0 { (order.size - spent) / order.price } else { 0.0 };
+    if shares + at_limit > 0.0 { order.size / (shares + at_limit) } else { order.price }
+}
+
+/// Split a new paper order into what fills against the book now, at the prices of the
+/// levels it crosses, and, when any is left, a remainder resting at its limit behind the
+/// same-side size at that price. Returns the dollars filled, the shares they bought and
+/// the remainder.
+pub fn place(
+    order: &Order,
+    token_id: &str,
+    book: &OrderBook,
+    config: &PaperFillConfig,
+    now: DateTime<Utc>,
+) -> (f64, f64, Option<RestingOrder>) {
+    let budget = order.size - order.filled_size;
+    let (spent, shares) = sweep(book, &order.side, order.price, budget);
+    if budget - spent <= PRICE_EPSILON || order.price <= 0.0 {
+        return (budget, shares, None);
+    }
+    let remaining = (budget - spent) / order.price;
+
+    let same_side = match order.side {
+        OrderSide::Buy => &book.bids,
+        OrderSide::Sell => &book.asks,
+    };
+    let queue_ahead = same_side
+        .iter()
+        .filter(|l| (l.price - order.price).abs() <= PRICE_EPSILON)
+        .map(|l| l.size)
+        .sum();
+    let resting = RestingOrder {
+        order_id: order.id.clone(),
+        market_id: order.market_id.clone(),
+        token_id: token_id.to_string(),
+        side: order.side.clone(),
+        price: order.price,
+        remaining,
+        queue_ahead,
+        matched_until: now,
+        expires_at: now + Duration::seconds(config.max_rest_secs as i64),
+        fills: if spent > 0.0 { 1 } else { 0 },
+    };
+    (spent, shares, Some(resting))
+}
@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::models::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlattenAction {
+    /// A working order, cancelled on the CLOB when it rests there
+    CancelOrder,
+    /// A parked conditional order
+    CancelConditional,
+    /// A paper position closed at the mid
+    ClosePaper,
+    /// A wallet position sold with a marketable limit order, signed through the signer RPC
+    SellLive,
+}
+
+/// One thing the emergency flatten did or tried to do
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlattenStep {
+    pub action: FlattenAction,
+    pub market_name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+/// Outcome of `flatten_all`, step by step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlattenReport {
+    pub steps: Vec<FlattenStep>,
+    /// The bot was running and has been stopped so it does not re-enter
+    pub bot_stopped: bool,
+    /// PnL realized by closing paper positions
+    pub realized_pnl: f64,
+    pub finished_at: DateTime<Utc>,
+}
+
+impl FlattenReport {
+    pub fn new(bot_stopped: bool) -> Self {
+        Self { steps: Vec::new(), bot_stopped, realized_pnl: 0.0, finished_at: Utc::now() }
+    }
+
+    pub fn push(&mut self, action: FlattenAction, market_name: &str, result: Result<String, String>) {
+        let (ok, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        self.steps.push(FlattenStep { action, market_name: market_name.to_string(), ok, detail });
+    }
+
+    pub fn failed(&self) -> usize {
+        self.steps.iter().filter(|s| !s.ok).count()
+    }
+}
+
+/// Midpoint of the best bid and ask, or the only side quoted
+pub fn mid(book: &OrderBook) -> Option<f64> {
+    let best_bid = book.bids.iter().map(|l| l.price).max_by(f64::total_cmp);
+    let best_ask = book.asks.iter().map(|l| l.price).min_by(f64::total_cmp);
+    match (best_bid, best_ask) {
+        (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+        (bid, ask) => bid.or(ask),
+    }
+}
+
+/// Lowest bid a sell of `shares` has to reach to fill in full; a limit there crosses
+/// every level it needs. With too little depth, the deepest bid.
+pub fn marketable_sell_price(book: &OrderBook, shares: f64) -> Option<f64> {
+    let mut bids: Vec<&BookLevel> = book.bids.iter().collect();
+    bids.sort_by(|a, b| b.price.total_cmp(&a.price));
+    let mut left = shares;
+    for level in &bids {
+        left -= level.size;
+        if left <= 0.0 {
+            return Some(level.price);
+        }
+    }
+    bids.last().map(|l| l.price)
+}
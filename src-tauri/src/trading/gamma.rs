@@ -0,0 +1,453 @@
+use std::time::Instant;
+use anyhow::Result;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::execution;
+use super::models::*;
+
+/// Gamma market payload, deserialized straight from the response bytes. Only the fields
+/// the bot reads are kept; the rest of the (large) payload is skipped without building a
+/// `serde_json::Value` tree. Every field is lenient, so a market with an odd field still
+/// parses and one bad market does not fail the listing.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GammaMarket {
+    question: Text,
+    condition_id: Text,
+    id: Text,
+    slug: Text,
+    outcomes: StringList,
+    #[serde(rename = "outcomePrices")]
+    outcome_prices: StringList,
+    volume: Number,
+    liquidity: Number,
+    #[serde(rename = "volume24hr")]
+    volume_24h: Number,
+    #[serde(rename = "volume1wk")]
+    volume_1wk: Number,
+    #[serde(rename = "endDate")]
+    end_date: Text,
+    category: Text,
+    tags: Tags,
+    events: Events,
+    #[serde(rename = "clobTokenIds")]
+    clob_token_ids: StringList,
+    #[serde(rename = "negRisk")]
+    neg_risk: Flag,
+    #[serde(rename = "negRiskMarketID")]
+    neg_risk_market_id: Text,
+    #[serde(rename = "questionID")]
+    question_id: Text,
+    #[serde(rename = "orderPriceMinTickSize")]
+    tick_size: Number,
+    #[serde(rename = "orderMinSize")]
+    min_order_size: Number,
+    closed: Flag,
+    #[serde(rename = "umaResolutionStatus")]
+    uma_resolution_status: Text,
+    #[serde(rename = "closedTime")]
+    closed_time: Text,
+    #[serde(rename = "umaEndDate")]
+    uma_end_date: Text,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Num(f64),
+    Str(String),
+    #[default]
+    Missing,
+    Other(IgnoredAny),
+}
+
+impl Number {
+    /// The API encodes numbers either as numbers or as numeric strings; anything else is 0
+    fn value(&self) -> f64 {
+        match self {
+            Number::Num(n) => *n,
+            Number::Str(s) => s.parse().unwrap_or(0.0),
+            Number::Missing | Number::Other(_) => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum Text {
+    Str(String),
+    Num(serde_json::Number),
+    #[default]
+    Missing,
+    Other(IgnoredAny),
+}
+
+impl Text {
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Text::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Strings as they are, numbers (e.g. event ids) in decimal
+    fn to_string_lossy(&self) -> Option<String> {
+        match self {
+            Text::Str(s) => Some(s.clone()),
+            Text::Num(n) => Some(n.to_string()),
+            Text::Missing | Text::Other(_) => None,
+        }
+    }
+
+    fn non_empty(&self) -> Option<String> {
+        self.as_str().filter(|s| !s.is_empty()).map(str::to_string)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum Flag {
+    Bool(bool),
+    #[default]
+    Missing,
+    Other(IgnoredAny),
+}
+
+impl Flag {
+    fn value(&self) -> Option<bool> {
+        match self {
+            Flag::Bool(b) => Some(*b),
+            Flag::Missing | Flag::Other(_) => None,
+        }
+    }
+}
+
+/// A list of strings or numbers, either as a JSON array or (as Gamma sends `outcomes`,
+/// `outcomePrices` and `clobTokenIds`) a JSON array encoded inside a string
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum StringList {
+    List(Vec<Text>),
+    Encoded(String),
+    #[default]
+    Missing,
+    Other(IgnoredAny),
+}
+
+impl StringList {
+    fn items(&self) -> Option<Vec<String>> {
+        let items = match self {
+            StringList::List(items) => items.iter().filter_map(Text::to_string_lossy).collect(),
+            StringList::Encoded(raw) => serde_json::from_str::<Vec<Text>>(raw)
+                .ok()?
+                .iter()
+                .filter_map(Text::to_string_lossy)
+                .collect(),
+            StringList::Missing | StringList::Other(_) => return None,
+        };
+        Some(items)
+    }
+
+    fn numbers(&self) -> Option<Vec<f64>> {
+        Some(self.items()?.iter().filter_map(|s| s.parse().ok()).collect())
+    }
+}
+
+/// Tags come as strings or as objects with `label`/`slug`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Tag {
+    Name(String),
+    Object {
+        #[serde(default)]
+        label: Text,
+        #[serde(default)]
+        slug: Text,
+    },
+    Other(IgnoredAny),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum Tags {
+    List(Vec<Tag>),
+    #[default]
+    Missing,
+    Other(IgnoredAny),
+}
+
+impl Tags {
+    fn names(&self) -> impl Iterator<Item = String> + '_ {
+        let tags = match self {
+            Tags::List(tags) => tags.as_slice(),
+            Tags::Missing | Tags::Other(_) => &[],
+        };
+        tags.iter().flat_map(|tag| match tag {
+            Tag::Name(name) => vec![name.clone()],
+            Tag::Object { label, slug } => [label, slug].into_iter().filter_map(Text::non_empty).collect(),
+            Tag::Other(_) => Vec::new(),
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GammaEvent {
+    id: Text,
+    tags: Tags,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum Events {
+    List(Vec<GammaEvent>),
+    #[default]
+    Missing,
+    Other(IgnoredAny),
+}
+
+impl Events {
+    fn first(&self) -> Option<&GammaEvent> {
+        match self {
+            Events::List(events) => events.first(),
+            Events::Missing | Events::Other(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct GammaEventPayload {
+    title: Text,
+    markets: Vec<GammaMarket>,
+}
+
+impl GammaMarket {
+    /// A listed market; `None` without a question or an id
+    fn into_market(self) -> Option<Market> {
+        let question = self.question.as_str()?.to_string();
+        let id = match (&self.condition_id, &self.id) {
+            (Text::Str(id), _) | (Text::Missing, Text::Str(id)) => id.clone(),
+            _ => return None,
+        };
+        Some(self.build(id, question, None))
+    }
+
+    /// A market fetched by id, with its resolution state
+    fn into_market_with_resolution(self, id: &str) -> Market {
+        let question = self.question.as_str().unwrap_or("Unknown").to_string();
+        let resolution = self.resolution();
+        self.build(id.to_string(), question, Some(resolution))
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.value() == Some(true)
+    }
+
+    fn build(self, id: String, question: String, resolution: Option<MarketResolution>) -> Market {
+        let event = self.events.first();
+        let mut tags: Vec<String> = self.tags.names()
+            .chain(event.into_iter().flat_map(|e| e.tags.names()))
+            .collect();
+        tags.sort();
+        tags.dedup();
+
+        Market {
+            id,
+            question,
+            slug: self.slug.as_str().unwrap_or("").to_string(),
+            outcomes: self.outcomes.items().unwrap_or_else(default_outcomes),
+            outcome_prices: self.outcome_prices.numbers().unwrap_or_else(|| vec![0.5, 0.5]),
+            volume: self.volume.value(),
+            liquidity: self.liquidity.value(),
+            volume_24h: self.volume_24h.value(),
+            volume_1wk: self.volume_1wk.value(),
+            end_date: self.end_date.as_str().map(str::to_string),
+            active: true,
+            category: self.category.as_str().map(str::to_string),
+            tags,
+            token_ids: self.clob_token_ids.items().unwrap_or_default(),
+            resolution,
+            neg_risk: self.neg_risk.value().unwrap_or(false),
+            neg_risk_market_id: self.neg_risk_market_id.non_empty(),
+            question_id: self.question_id.non_empty(),
+            event_id: event.and_then(|e| e.id.to_string_lossy()),
+            tick_size: positive_or(self.tick_size.value(), execution::DEFAULT_TICK_SIZE),
+            min_order_size: positive_or(self.min_order_size.value(), execution::DEFAULT_MIN_ORDER_SIZE),
+        }
+    }
+
+    /// Derive resolution state from Gamma's `closed`, `umaResolutionStatus` and final `outcomePrices`
+    fn resolution(&self) -> MarketResolution {
+        let outcomes = self.outcomes.items().unwrap_or_else(default_outcomes);
+        let payouts = self.outcome_prices.numbers().unwrap_or_default();
+        let closed = self.is_closed();
+        let uma_status = self.uma_resolution_status.as_str().unwrap_or("").to_lowercase();
+        let resolved_at = match &self.closed_time {
+            Text::Missing => self.uma_end_date.as_str(),
+            closed_time => closed_time.as_str(),
+        }
+        .and_then(parse_gamma_time);
+
+        let winner = payouts.iter().position(|p| *p >= 0.99);
+        let fifty_fifty = payouts.len() >= 2 && payouts.iter().all(|p| (p - 0.5).abs() < 0.01);
+
+        let status = if uma_status.contains("disput") {
+            ResolutionStatus::Disputed
+        } else if !closed {
+            if uma_status.contains("propos") {
+                ResolutionStatus::Proposed
+            } else {
+                ResolutionStatus::Pending
+            }
+        } else if winner.is_some() {
+            ResolutionStatus::Resolved
+        } else if fifty_fifty {
+            ResolutionStatus::FiftyFifty
+        } else if uma_status.contains("resolved") {
+            ResolutionStatus::Invalid
+        } else {
+            ResolutionStatus::Pending
+        };
+
+        MarketResolution {
+            winning_outcome: match status {
+                ResolutionStatus::Resolved => winner.and_then(|i| outcomes.get(i).cloned()),
+                _ => None,
+            },
+            resolved_at: if matches!(status, ResolutionStatus::Pending | ResolutionStatus::Proposed) {
+                None
+            } else {
+                resolved_at
+            },
+            status,
+            outcomes,
+            payouts,
+        }
+    }
+}
+
+fn default_outcomes() -> Vec<String> {
+    vec!["Yes".to_string(), "No".to_string()]
+}
+
+fn positive_or(value: f64, default: f64) -> f64 {
+    if value > 0.0 { value } else { default }
+}
+
+/// Gamma timestamps come either as RFC3339 or as "2024-11-06 12:00:00+00"
+fn parse_gamma_time(raw: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(raw)
+        .or_else(|_| chrono::DateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%#z"))
+        .ok()
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
+
+/// Throughput of parsing one market listing
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ParseStats {
+    pub markets: u32,
+    pub bytes: u64,
+    pub elapsed_us: u64,
+}
+
+impl ParseStats {
+    pub fn markets_per_sec(&self) -> Option<f64> {
+        (self.elapsed_us > 0).then(|| self.markets as f64 * 1_000_000.0 / self.elapsed_us as f64)
+    }
+}
+
+/// Parse a `/markets` listing. A body that is not an array of objects (e.g. one entry is
+/// `null`) takes the slower path through `Value` so the good entries still come through.
+pub fn parse_markets(body: &[u8]) -> Result<(Vec<Market>, ParseStats)> {
+    let started = Instant::now();
+    let raw: Vec<GammaMarket> = match serde_json::from_slice(body) {
+        Ok(raw) => raw,
+        Err(_) => match serde_json::from_slice::<Value>(body)? {
+            Value::Array(items) => items
+                .into_iter()
+                .filter_map(|item| GammaMarket::deserialize(item).ok())
+                .collect(),
+            _ => Vec::new(),
+        },
+    };
+    let markets: Vec<Market> = raw.into_iter().filter_map(GammaMarket::into_market).collect();
+    let stats = ParseStats {
+        markets: markets.len() as u32,
+        bytes: body.len() as u64,
+        elapsed_us: started.elapsed().as_micros() as u64,
+    };
+    Ok((markets, stats))
+}
+
+/// Parse a single `/markets/{id}` response
+pub fn parse_market(body: &[u8], id: &str) -> Result<Market> {
+    let raw: GammaMarket = serde_json::from_slice(body)?;
+    Ok(raw.into_market_with_resolution(id))
+}
+
+/// Title and open markets of an `/events/{id}` response
+pub fn parse_event(body: &[u8], event_id: &str) -> Result<(String, Vec<Market>)> {
+    let event: GammaEventPayload = serde_json::from_slice(body)?;
+    let title = event.title.as_str().unwrap_or("Unknown").to_string();
+    let markets = event.markets
+        .into_iter()
+        .filter(|m| !m.is_closed())
+        .filter_map(GammaMarket::into_market)
+        .map(|mut m| {
+            m.event_id.get_or_insert_with(|| event_id.to_string());
+            m
+        })
+        .collect();
+    Ok((title, markets))
+}
+
+/// Typed parsing against the same listing parsed through an intermediate `Value` tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseBenchmark {
+    pub markets: u32,
+    pub bytes: u64,
+    pub rounds: u32,
+    pub typed_markets_per_sec: f64,
+    pub value_markets_per_sec: f64,
+    /// `typed_markets_per_sec / value_markets_per_sec`
+    pub speedup: f64,
+}
+
+/// Parse `body` `rounds` times both ways
+pub fn benchmark(body: &[u8], rounds: u32) -> Result<ParseBenchmark> {
+    let rounds = rounds.max(1);
+    let mut markets = 0;
+
+    let started = Instant::now();
+    for _ in 0..rounds {
+        markets = parse_markets(body)?.0.len();
+    }
+    let typed_secs = started.elapsed().as_secs_f64();
+
+    let started = Instant::now();
+    for _ in 0..rounds {
+        let tree: Value = serde_json::from_slice(body)?;
+        let parsed: Vec<Market> = tree.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|item| GammaMarket::deserialize(item).ok())
+            .filter_map(GammaMarket::into_market)
+            .collect();
+        std::hint::black_box(parsed);
+    }
+    let value_secs = started.elapsed().as_secs_f64();
+
+    let per_sec = |secs: f64| if secs > 0.0 { (markets as u64 * rounds as u64) as f64 / secs } else { 0.0 };
+    let typed = per_sec(typed_secs);
+    let value = per_sec(value_secs);
+    Ok(ParseBenchmark {
+        markets: markets as u32,
+        bytes: body.len() as u64,
+        rounds,
+        typed_markets_per_sec: typed,
+        value_markets_per_sec: value,
+        speedup: if value > 0.0 { typed / value } else { 0.0 },
+    })
+}
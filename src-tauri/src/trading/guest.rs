@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use super::db::HistoryPage;
+use super::execution::{ExecutionQuality, ExecutionReport};
+use super::fills::RestingOrder;
+use super::ledger::{LedgerEvent, LedgerEventKind};
+use super::market_maker::{MakerBook, Quote};
+use super::models::*;
+use super::risk::{CategoryUtilization, ExposureGroup, MarketPosition, PreflightRisk};
+use super::simulation::{Percentiles, SimulationResult};
+
+pub const GUEST_MODE_ERROR: &str = "Not available in guest mode";
+const MASK: &str = "••••";
+
+/// Presentation mode for screen-sharing: secrets are always hidden and mutating commands
+/// are refused; dollar amounts are hidden too when `mask_balances` is set
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct GuestMode {
+    pub enabled: bool,
+    pub mask_balances: bool,
+}
+
+impl GuestMode {
+    pub fn masks_balances(&self) -> bool {
+        self.enabled && self.mask_balances
+    }
+}
+
+/// Digest of the PIN that lifts guest mode; only the digest is kept in memory
+pub fn hash_pin(pin: &str) -> String {
+    hex::encode(Sha256::digest(pin.as_bytes()))
+}
+
+fn redact(secret: &str) -> String {
+    if secret.is_empty() { String::new() } else { MASK.to_string() }
+}
+
+pub fn redact_config(config: &BotConfig) -> BotConfig {
+    let mut config = BotConfig {
+        wallet_address: redact(&config.wallet_address),
+        ..config.clone()
+    };
+    for secret in secrets_mut(&mut config) {
+        *secret = redact(secret);
+    }
+    config
+}
+
+/// Every config field holding a credential. Proxy and RPC URLs are included since they
+/// can carry one.
+pub fn secrets_mut(config: &mut BotConfig) -> [&mut String; 8] {
+    [
+        &mut config.polymarket_api_key,
+        &mut config.polymarket_secret,
+        &mut config.polymarket_passphrase,
+        &mut config.claude_api_key,
+        &mut config.voyage_api_key,
+        &mut config.proxy_url,
+        &mut config.rpc_url,
+        &mut config.signer_rpc_url,
+    ]
+}
+
+/// Zero dollar amounts, keeping percentages and counts
+pub fn mask_stats(stats: &BotStats) -> BotStats {
+    BotStats {
+        current_balance: 0.0,
+        total_equity: 0.0,
+        mark_to_market_equity: 0.0,
+        model_equity: 0.0,
+        free_capital: 0.0,
+        initial_balance: 0.0,
+        total_pnl: 0.0,
+        net_deposits: 0.0,
+        api_costs: 0.0,
+        avg_bet: 0.0,
+        best_trade: 0.0,
+        worst_trade: 0.0,
+        daily_api_cost: 0.0,
+        ..stats.clone()
+    }
+}
+
+/// Rebase the balance curve to an index (100 = initial balance) so its shape stays visible
+pub fn mask_balance_history(points: Vec<BalancePoint>, initial_balance: f64) -> Vec<BalancePoint> {
+    points
+        .into_iter()
+        .map(|p| BalancePoint {
+            balance: if initial_balance > 0.0 { p.balance / initial_balance * 100.0 } else { 0.0 },
+            ..p
+        })
+        .collect()
+}
+
+pub fn mask_orders(orders: Vec<Order>) -> Vec<Order> {
+    orders
+        .into_iter()
+        .map(|o| Order {
+            size: 0.0,
+            filled_size: 0.0,
+            pnl: o.pnl.map(|_| 0.0),
+            exited_pnl: 0.0,
+            ..o
+        })
+        .collect()
+}
+
+/// Counts, shares and basis points stay; dollar totals are hidden
+pub fn mask_execution_quality(quality: ExecutionQuality) -> ExecutionQuality {
+    ExecutionQuality {
+        maker_volume: 0.0,
+        taker_volume: 0.0,
+        spread_captured: 0.0,
+        spread_paid: 0.0,
+        passive_savings: 0.0,
+        ..quality
+    }
+}
+
+/// The order's size and the simulated portfolio amounts are hidden; prices, fractions
+/// and the market's book stay
+pub fn mask_execution_report(report: ExecutionReport) -> ExecutionReport {
+    ExecutionReport {
+        order_size: 0.0,
+        preflight: report.preflight.map(|p| PreflightRisk {
+            equity: 0.0,
+            total_exposure: 0.0,
+            category_exposure: 0.0,
+            worst_case_loss: 0.0,
+            ..p
+        }),
+        ..report
+    }
+}
+
+pub fn mask_resting_orders(orders: Vec<RestingOrder>) -> Vec<RestingOrder> {
+    orders
+        .into_iter()
+        .map(|o| RestingOrder { remaining: 0.0, ..o })
+        .collect()
+}
+
+pub fn mask_maker_books(books: Vec<MakerBook>) -> Vec<MakerBook> {
+    let hide = |quote: Option<Quote>| quote.map(|q| Quote { size: 0.0, ..q });
+    books
+        .into_iter()
+        .map(|b| MakerBook {
+            bid: hide(b.bid),
+            ask: hide(b.ask),
+            inventory: 0.0,
+            cash: 0.0,
+            pnl: 0.0,
+            ..b
+        })
+        .collect()
+}
+
+pub fn mask_commentary(commentary: PortfolioCommentary) -> PortfolioCommentary {
+    PortfolioCommentary { commentary: mask_amounts(&commentary.commentary), ..commentary }
+}
+
+/// Rebase final balances to an index (100 = starting balance), as for the balance curve
+pub fn mask_simulation(result: SimulationResult, starting_balance: f64) -> SimulationResult {
+    let index = |balance: f64| if starting_balance > 0.0 { balance / starting_balance * 100.0 } else { 0.0 };
+    let finals = &result.final_balance;
+    SimulationResult {
+        final_balance: Percentiles {
+            p5: index(finals.p5),
+            p25: index(finals.p25),
+            p50: index(finals.p50),
+            p75: index(finals.p75),
+            p95: index(finals.p95),
+        },
+        ..result
+    }
+}
+
+pub fn mask_market_positions(positions: Vec<MarketPosition>) -> Vec<MarketPosition> {
+    positions
+        .into_iter()
+        .map(|p| MarketPosition { size: 0.0, shares: 0.0, ..p })
+        .collect()
+}
+
+pub fn mask_exposure_groups(groups: Vec<ExposureGroup>) -> Vec<ExposureGroup> {
+    groups
+        .into_iter()
+        .map(|g| ExposureGroup { exposure: 0.0, fraction: 0.0, ..g })
+        .collect()
+}
+
+pub fn mask_ledger(events: Vec<LedgerEvent>) -> Vec<LedgerEvent> {
+    events
+        .into_iter()
+        .map(|mut event| {
+            event.kind = match event.kind {
+                LedgerEventKind::OrderPlaced { order_id, market_id, price, .. } => {
+                    LedgerEventKind::OrderPlaced { order_id, market_id, price, size: 0.0 }
+                }
+                LedgerEventKind::Fill { order_id, .. } => LedgerEventKind::Fill { order_id, size: 0.0 },
+                LedgerEventKind::Resolution { order_id, market_id, counted, .. } => {
+                    LedgerEventKind::Resolution { order_id, market_id, pnl: 0.0, counted }
+                }
+                LedgerEventKind::Exit { order_id, market_id, fraction, .. } => {
+                    LedgerEventKind::Exit { order_id, market_id, pnl: 0.0, fraction }
+                }
+                LedgerEventKind::Conversion { event_id, .. } => {
+                    LedgerEventKind::Conversion { event_id, cost: 0.0, proceeds: 0.0 }
+                }
+                LedgerEventKind::Deposit { source, note, .. } => {
+                    LedgerEventKind::Deposit { amount: 0.0, source, note: mask_amounts(&note) }
+                }
+                LedgerEventKind::Withdrawal { source, note, .. } => {
+                    LedgerEventKind::Withdrawal { amount: 0.0, source, note: mask_amounts(&note) }
+                }
+            };
+            event
+        })
+        .collect()
+}
+
+/// Dollar-valued fields of history rows across tables
+const HISTORY_AMOUNT_FIELDS: [&str; 9] = [
+    "size", "filled_size", "pnl", "exited_pnl", "cost", "proceeds", "amount", "start_balance", "end_balance",
+];
+
+pub fn mask_history(mut page: HistoryPage) -> HistoryPage {
+    for row in page.rows.iter_mut() {
+        let Some(fields) = row.as_object_mut() else { continue };
+        for name in HISTORY_AMOUNT_FIELDS {
+            if let Some(value) = fields.get_mut(name).filter(|v| v.is_number()) {
+                *value = Value::from(0.0);
+            }
+        }
+        if let Some(Value::String(message)) = fields.get_mut("message") {
+            *message = mask_amounts(message);
+        }
+    }
+    page
+}
+
+pub fn mask_portfolio(portfolio: PortfolioSummary) -> PortfolioSummary {
+    PortfolioSummary {
+        positions: portfolio.positions
+            .into_iter()
+            .map(|p| Position {
+                size: 0.0,
+                initial_value: 0.0,
+                current_value: 0.0,
+                pnl: 0.0,
+                ..p
+            })
+            .collect(),
+        total_cost: 0.0,
+        total_value: 0.0,
+        unrealized_pnl: 0.0,
+        category_utilization: portfolio.category_utilization
+            .into_iter()
+            .map(|c| CategoryUtilization { exposure: 0.0, ..c })
+            .collect(),
+        ..portfolio
+    }
+}
+
+/// Replace dollar amounts such as "$12.50" or "-$1.2k" in free text
+pub fn mask_amounts(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        out.push(c);
+        if c == '$' && chars.peek().is_some_and(|n| n.is_ascii_digit()) {
+            while chars.peek().is_some_and(|n| n.is_ascii_digit() || matches!(n, '.' | ',' | 'k' | 'K' | 'M')) {
+                chars.next();
+            }
+            out.push_str(MASK);
+        }
+    }
+    out
+}
@@ -0,0 +1,300 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use super::config;
+use super::currency::PegStatus;
+use super::db::Database;
+use super::ratelimit::RateLimitStatus;
+
+/// Consecutive failures after which an API is reported red rather than yellow
+const FAILURES_FOR_RED: u32 = 3;
+/// Share of a rate-limit bucket below which an API shows Yellow
+const LOW_QUOTA: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum HealthLevel {
+    /// Component not in use (not configured, bot stopped, feature absent)
+    Inactive,
+    Green,
+    Yellow,
+    Red,
+}
+
+/// Stops calling an API after repeated failures instead of hammering it every cycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit, 0 to never open it
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub failure_threshold: u32,
+    /// How long an open circuit blocks calls before one probe is let through
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub cooldown_secs: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self { failure_threshold: 5, cooldown_secs: 300 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CircuitState {
+    #[default]
+    Closed,
+    /// Calls are skipped until the cooldown ends
+    Open,
+    /// Cooldown over; the next call is a probe that closes or reopens the circuit
+    HalfOpen,
+}
+
+/// Circuit changes for the activity log, drained by the engine after each cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerEvent {
+    Tripped { failures: u32, cooldown_secs: u32 },
+    /// A half-open probe failed
+    Reopened,
+    Recovered,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    pub level: HealthLevel,
+    pub detail: String,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+    #[serde(default)]
+    pub circuit: CircuitState,
+    /// End of the current cooldown while the circuit is open
+    #[serde(default)]
+    pub circuit_open_until: Option<DateTime<Utc>>,
+}
+
+impl ComponentHealth {
+    fn inactive(detail: &str) -> Self {
+        Self {
+            level: HealthLevel::Inactive,
+            detail: detail.to_string(),
+            last_success: None,
+            last_error: None,
+            consecutive_failures: 0,
+            circuit: CircuitState::Closed,
+            circuit_open_until: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthStatus {
+    /// Worst level across active components
+    pub overall: HealthLevel,
+    pub polymarket: ComponentHealth,
+    pub claude: ComponentHealth,
+    pub websocket: ComponentHealth,
+    pub cycle: ComponentHealth,
+    pub persistence: ComponentHealth,
+    /// Stablecoin price feed behind the depeg check
+    pub peg: ComponentHealth,
+    /// Error entries logged in the last hour
+    pub recent_errors: u32,
+    /// Order slices still waiting to be placed
+    pub pending_slices: u32,
+    /// Anthropic quota as of the latest response
+    #[serde(default)]
+    pub claude_rate_limits: Option<RateLimitStatus>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Outcome history of calls to one external API, and its circuit breaker
+#[derive(Debug, Clone, Default)]
+pub struct CallTracker {
+    last_success: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    consecutive_failures: u32,
+    /// Set while the circuit is open or half-open
+    open_until: Option<DateTime<Utc>>,
+    events: Vec<BreakerEvent>,
+}
+
+impl CallTracker {
+    pub fn record<T, E: std::fmt::Display>(&mut self, result: &Result<T, E>, breaker: &CircuitBreakerConfig) {
+        let now = Utc::now();
+        match result {
+            Ok(_) => {
+                self.last_success = Some(now);
+                self.consecutive_failures = 0;
+                if self.open_until.take().is_some() {
+                    self.events.push(BreakerEvent::Recovered);
+                }
+            }
+            Err(e) => {
+                self.last_error = Some(e.to_string());
+                self.consecutive_failures += 1;
+                let cooldown = Duration::seconds(breaker.cooldown_secs as i64);
+                if self.open_until.is_some() {
+                    self.open_until = Some(now + cooldown);
+                    self.events.push(BreakerEvent::Reopened);
+                } else if breaker.failure_threshold > 0 && self.consecutive_failures >= breaker.failure_threshold {
+                    self.open_until = Some(now + cooldown);
+                    self.events.push(BreakerEvent::Tripped {
+                        failures: self.consecutive_failures,
+                        cooldown_secs: breaker.cooldown_secs,
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn circuit(&self, now: DateTime<Utc>) -> CircuitState {
+        match self.open_until {
+            None => CircuitState::Closed,
+            Some(until) if now < until => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Whether a call may go out now; false only while the circuit is open
+    pub fn allows(&self) -> bool {
+        self.circuit(Utc::now()) != CircuitState::Open
+    }
+
+    pub fn take_events(&mut self) -> Vec<BreakerEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn health(&self, configured: bool, name: &str) -> ComponentHealth {
+        if !configured {
+            return ComponentHealth::inactive(&format!("{} not configured", name));
+        }
+
+        let now = Utc::now();
+        let circuit = self.circuit(now);
+        let (level, detail) = match (self.consecutive_failures, self.last_success) {
+            _ if circuit == CircuitState::Open => (
+                HealthLevel::Red,
+                format!(
+                    "Circuit open after {} failures, retrying in {}s",
+                    self.consecutive_failures,
+                    self.open_until.map(|u| (u - now).num_seconds()).unwrap_or(0)
+                ),
+            ),
+            _ if circuit == CircuitState::HalfOpen => (HealthLevel::Yellow, "Circuit half-open, next call probes".to_string()),
+            (0, Some(_)) => (HealthLevel::Green, "OK".to_string()),
+            (0, None) => (HealthLevel::Green, "No calls yet".to_string()),
+            (n, _) if n >= FAILURES_FOR_RED => (HealthLevel::Red, format!("{} consecutive failures", n)),
+            (n, _) => (HealthLevel::Yellow, format!("{} recent failure(s)", n)),
+        };
+        ComponentHealth {
+            level,
+            detail,
+            last_success: self.last_success,
+            last_error: self.last_error.clone(),
+            consecutive_failures: self.consecutive_failures,
+            circuit,
+            circuit_open_until: self.open_until.filter(|_| circuit == CircuitState::Open),
+        }
+    }
+}
+
+/// Downgrade an otherwise healthy API to Yellow while it is throttled or nearly out of quota
+pub fn with_rate_limits(mut health: ComponentHealth, limits: &RateLimitStatus, now: DateTime<Utc>) -> ComponentHealth {
+    if health.level != HealthLevel::Green {
+        return health;
+    }
+    if let Some(retry_at) = limits.retry_at.filter(|at| *at > now) {
+        health.level = HealthLevel::Yellow;
+        health.detail = format!("Rate limited, retrying in {}s", (retry_at - now).num_seconds());
+    } else if let Some(headroom) = limits.headroom().filter(|h| *h < LOW_QUOTA) {
+        health.level = HealthLevel::Yellow;
+        health.detail = format!("{:.0}% of rate limit left", headroom * 100.0);
+    }
+    health
+}
+
+/// Liveness of the scan loop: cycles are expected every `scan_interval_secs` while running
+pub fn cycle_health(is_running: bool, last_cycle: Option<DateTime<Utc>>, scan_interval_secs: u32) -> ComponentHealth {
+    if !is_running {
+        return ComponentHealth::inactive("Bot stopped");
+    }
+
+    let interval = Duration::seconds(scan_interval_secs.max(1) as i64);
+    let (level, detail) = match last_cycle {
+        None => (HealthLevel::Yellow, "Waiting for the first cycle".to_string()),
+        Some(last) => {
+            let age = Utc::now() - last;
+            if age <= interval * 2 {
+                (HealthLevel::Green, format!("Last cycle {}s ago", age.num_seconds()))
+            } else if age <= interval * 5 {
+                (HealthLevel::Yellow, format!("Cycle overdue, last {}s ago", age.num_seconds()))
+            } else {
+                (HealthLevel::Red, format!("No cycle for {}s", age.num_seconds()))
+            }
+        }
+    };
+    ComponentHealth {
+        level,
+        detail,
+        last_success: last_cycle,
+        last_error: None,
+        consecutive_failures: 0,
+        circuit: CircuitState::Closed,
+        circuit_open_until: None,
+    }
+}
+
+/// Price feed calls, turned red once the last good peg status is stale
+pub fn peg_health(tracker: &CallTracker, peg: Option<&PegStatus>, configured: bool) -> ComponentHealth {
+    let mut health = tracker.health(configured, "Price feed");
+    let now = Utc::now();
+    if let Some(peg) = peg.filter(|p| configured && p.is_stale(now)) {
+        health.level = HealthLevel::Red;
+        health.detail = format!(
+            "{} price is {}m old{}",
+            peg.currency.symbol(),
+            (now - peg.checked_at).num_minutes(),
+            if peg.paused { ", entries still paused" } else { "" }
+        );
+    }
+    health
+}
+
+/// Components this build does not have yet are reported inactive rather than omitted,
+/// so the UI layout stays stable as they are added
+pub fn websocket_health() -> ComponentHealth {
+    ComponentHealth::inactive("Market data is polled over REST")
+}
+
+pub fn persistence_health(db: Option<&Database>, last_error: Option<&str>) -> ComponentHealth {
+    let Some(db) = db else {
+        return ComponentHealth::inactive("History is kept in memory only");
+    };
+    match last_error {
+        Some(error) => ComponentHealth {
+            level: HealthLevel::Red,
+            detail: format!("Writes to {} are failing", db.path().display()),
+            last_success: None,
+            last_error: Some(error.to_string()),
+            consecutive_failures: 1,
+            circuit: CircuitState::Closed,
+            circuit_open_until: None,
+        },
+        None => ComponentHealth {
+            level: HealthLevel::Green,
+            detail: format!("{} history rows in {}", db.row_count(), db.path().display()),
+            last_success: None,
+            last_error: None,
+            consecutive_failures: 0,
+            circuit: CircuitState::Closed,
+            circuit_open_until: None,
+        },
+    }
+}
+
+pub fn overall(components: &[&ComponentHealth]) -> HealthLevel {
+    components
+        .iter()
+        .map(|c| c.level)
+        .filter(|l| *l != HealthLevel::Inactive)
+        .max()
+        .unwrap_or(HealthLevel::Green)
+}
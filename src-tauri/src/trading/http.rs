@@ -0,0 +1,50 @@
+use std::time::Duration;
+use anyhow::{Context, Result};
+use reqwest::{Client, Proxy, Response};
+
+/// A non-success HTTP status from an upstream API
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("HTTP {status}: {body}")]
+pub struct ApiError {
+    pub status: u16,
+    pub body: String,
+    /// Seconds from a `Retry-After` header, when present
+    pub retry_after_secs: Option<u64>,
+}
+
+/// Turn a non-success response into an `ApiError` carrying its status and body
+pub async fn check(resp: Response) -> Result<Response> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let retry_after_secs = resp.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok());
+    let body = resp.text().await.unwrap_or_default();
+    Err(ApiError { status: status.as_u16(), body: body.chars().take(500).collect(), retry_after_secs }.into())
+}
+
+/// Build an HTTP client with the given timeout, routed through `proxy_url` when set
+pub fn build_client(timeout_secs: u64, proxy_url: &str) -> Result<Client> {
+    let mut builder = Client::builder().timeout(Duration::from_secs(timeout_secs));
+
+    if !proxy_url.trim().is_empty() {
+        let proxy = Proxy::all(proxy_url.trim())
+            .with_context(|| format!("Invalid proxy URL: {}", proxy_url))?;
+        builder = builder.proxy(proxy);
+    }
+
+    builder.build().context("Failed to build HTTP client")
+}
+
+/// Normalize a configured base URL, falling back to the default when empty
+pub fn base_url(configured: &str, default: &str) -> String {
+    let url = configured.trim().trim_end_matches('/');
+    if url.is_empty() {
+        default.to_string()
+    } else {
+        url.to_string()
+    }
+}
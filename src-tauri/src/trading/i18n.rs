@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+/// A backend message as a stable key plus named, pre-formatted parameters.
+/// Rendered per locale on read; stored entries keep the key for exports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Message {
+    pub key: String,
+    pub params: BTreeMap<String, String>,
+}
+
+impl Message {
+    pub fn new(key: &str) -> Self {
+        Self { key: key.to_string(), params: BTreeMap::new() }
+    }
+
+    pub fn arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.params.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Fill the locale's template, falling back to English, then to the bare key
+    pub fn render(&self, locale: Locale) -> String {
+        let template = template(locale, &self.key)
+            .or_else(|| template(Locale::En, &self.key))
+            .unwrap_or(&self.key);
+
+        let mut out = template.to_string();
+        for (name, value) in &self.params {
+            out = out.replace(&format!("{{{}}}", name), value);
+        }
+        out
+    }
+}
+
+/// "+$1.23" for gains, "$-1.23" for losses, matching the feed's historical format
+pub fn signed_usd(amount: f64) -> String {
+    format!("{}${:.2}", if amount >= 0.0 { "+" } else { "" }, amount)
+}
+
+fn template(locale: Locale, key: &str) -> Option<&'static str> {
+    match locale {
+        Locale::En => en(key),
+        Locale::Es => es(key),
+    }
+}
+
+fn en(key: &str) -> Option<&'static str> {
+    Some(match key {
+        "config.updated" => "Configuration updated successfully",
+        "state.imported" => "Imported state from {exported_at}: {orders} orders, {events} ledger events",
+        "orders.orphaned" => "Closed {count} working orders left without fill state: filled parts kept, remainders cancelled",
+        "bot.testnet" => "🧪 Testnet environment - staging CLOB on Polygon Amoy",
+        "bot.started" => "🟢 Bot started - Survival Mode active",
+        "bot.started_watch_only" => "🟢 Bot started - Watch-only mode, no trades will be placed",
+        "bot.stopped" => "🔴 Bot stopped",
+        "cycle.scanning" => "Scanning markets... Cycle #{cycle}",
+        "cycle.processing" => "Processing {count} markets...",
+        "cycle.sampled" => "Analyzing {count} of {total} markets ({strategy} sampling)",
+        "cycle.error" => "Cycle error: {error}",
+        "markets.fetch_failed" => "Error fetching markets: {error}",
+        "edge.found" => "Edge: \"{market}\" > ${size} @ {edge} (fair {fair}, score {score})",
+        "analysis.failed" => "Analysis failed for \"{market}\": {error}",
+        "embedding.failed" => "Embedding failed: {error}",
+        "batch.submitted" => "Queued {count} markets for batch re-analysis",
+        "batch.merged" => "Merged {count} batch analyses into the prediction cache",
+        "batch.failed" => "Batch analysis failed: {error}",
+        "batch.skipped" => "Skipped {count} batch results that failed or could not be parsed",
+        "batch.expired" => "Abandoned a batch of {count} markets still unfinished after a day",
+        "import.done" => "Imported {imported} historical trades ({duplicates} already present)",
+        "watch.requires_wallet" => "Watch-only mode requires a wallet address",
+        "watch.summary" => "Watching {count} positions: value ${value}, PnL {pnl}",
+        "positions.fetch_failed" => "Error fetching positions: {error}",
+        "position.resolved" => "RESOLVED \"{market}\" ({outcome}) {pnl}",
+        "commentary.updated" => "Analyst report updated",
+        "commentary.failed" => "Analyst report failed: {error}",
+        "risk.downsized" => "Downsized to ${size}: {reason}",
+        "risk.skipped" => "Skipped \"{market}\": {reason}",
+        "order.below_minimum" => "Order skipped for \"{market}\": {reason}",
+        "order.price_band" => "Order skipped for \"{market}\": {reason}",
+        "order.guardrail" => "Order skipped for \"{market}\": {reason}",
+        "order.offer_pressure" => "Skipped \"{market}\": offer-side pressure {pressure}",
+        "order.placed" => "ORDER ${size} → \"{market}\"",
+        "order.sliced" => "ORDER ${size} → \"{market}\" sliced into {slices} x ${slice_size} (depth ${depth})",
+        "order.slice" => "SLICE {index}/{total} ${size} → \"{market}\"",
+        "order.resting" => "ORDER ${size} → \"{market}\" resting at {price}: ${filled} filled on entry, {queue} shares ahead",
+        "order.paper_fill" => "FILL ${size} → \"{market}\" (${filled} of ${total})",
+        "order.rest_expired" => "Resting order on \"{market}\" expired with ${filled} of ${total} filled; remainder cancelled",
+        "order.rest_cancelled" => "Resting order on \"{market}\" expired unfilled and was cancelled",
+        "order.amended" => "AMEND ${old_size} @ {old_price} → ${new_size} @ {new_price} \"{market}\"",
+        "order.amend_restored" => "AMEND rejected for \"{market}\": {error}; the original order was placed again",
+        "order.amend_failed" => "AMEND failed for \"{market}\": replacement rejected: {error}; placing the original again failed: {restore_error}",
+        "order.transition" => "\"{market}\": {from} → {to}",
+        "order.illegal_transition" => "Refused status change for \"{market}\": {from} → {to} is not allowed",
+        "negrisk.bought" => "BASKET {sets} {outcome} sets of \"{event}\" ({legs} outcomes) for {cost}: pays {payout}/set, edge {edge}/set",
+        "negrisk.converted" => "CONVERTED {sets} No sets of \"{event}\" into {proceeds} {pnl}",
+        "negrisk.no_capital" => "Skipped basket on \"{event}\": {cost} needed, {free} free",
+        "alert.created" => "Alert set: \"{market}\" {outcome} {condition} {threshold} (now {price})",
+        "alert.triggered" => "ALERT \"{market}\" {outcome} {condition} {threshold}: now {price}",
+        "conditional.parked" => "Parked \"{market}\" {outcome} until {trigger} (now {price})",
+        "conditional.triggered" => "Trigger hit for \"{market}\" {outcome}: {trigger} (now {price})",
+        "conditional.no_edge" => "Conditional \"{market}\" {outcome} not traded: edge {edge} at {price}",
+        "conditional.manual" => "Conditional \"{market}\" {outcome} not traded: auto trading is off",
+        "conditional.expired" => "Conditional \"{market}\" {outcome} expired before {trigger}",
+        "conditional.cancelled" => "Conditional \"{market}\" {outcome} cancelled",
+        "report.written" => "Report saved to {path}. {summary}",
+        "report.failed" => "Could not save report: {error}",
+        "prompt_packs.reloaded" => "Reloaded {count} prompt packs",
+        "peg.lost" => "{currency} depegged: ${price}, beyond the {threshold} threshold; {action}",
+        "peg.restored" => "{currency} back on peg at ${price}",
+        "wallet.approved" => "Exchange allowances already in place",
+        "wallet.approvals_sent" => "Sent {count} approval transactions (max fee {fee} POL)",
+        "wallet.approvals_built" => "Built {count} unsigned approval transactions (max fee {fee} POL); sign them in your wallet",
+        "wallet.approval_failed" => "Approvals stopped after {sent} of {total}: {error}",
+        "tally.peg_paused" => "Skipped {count} entries while the stablecoin is depegged",
+        "tally.circuit_open" => "Skipped {count} API calls while a circuit breaker is open",
+        "breaker.tripped" => "{service} failed {failures} times in a row; pausing calls for {cooldown}s",
+        "breaker.reopened" => "{service} probe failed; calls stay paused",
+        "breaker.recovered" => "{service} responding again; calls resumed",
+        "flatten.started" => "FLATTEN ALL: cancelling orders and closing every position",
+        "flatten.step" => "FLATTEN {action} \"{market}\": {detail}",
+        "flatten.step_failed" => "FLATTEN {action} failed for \"{market}\": {detail}",
+        "flatten.finished" => "FLATTEN done: {steps} steps, {failed} failed, {pnl} realized",
+        "claude.deferred" => "Claude rate limit low: deferring {count} analyses to a later cycle ({secs}s until quota resets)",
+        "mm.quoted" => "MM quoting \"{market}\" {bid} / {ask} (inventory {inventory})",
+        "mm.filled" => "MM {side} {size} @ {price} \"{market}\" (inventory {inventory})",
+        "mm.pulled" => "MM pulled quotes on \"{market}\": mid moved {moved}",
+        "exit.close" => "EXIT \"{market}\" @ {bid} {pnl}: fair {fair}, edge {edge}, edge/σ {ratio}, {hours}h left",
+        "exit.reduce" => "REDUCE {fraction} \"{market}\" @ {bid} {pnl}: fair {fair}, edge {edge}, edge/σ {ratio}, {hours}h left",
+        "exit.hold" => "HOLD \"{market}\" to resolution: fair {fair}, bid {bid}, edge/σ {ratio}, {hours}h left",
+        "thesis.review" => "Re-underwrote \"{market}\" {outcome}: fair {entry} → {fresh} ({drift}), price {price}; recommend {recommendation}",
+        "thesis.acted" => "Re-underwrote \"{market}\" {outcome}: fair {entry} → {fresh} ({drift}), price {price}; acted on {recommendation}",
+        "resolution.resolved" => "RESOLVED {pnl}",
+        "resolution.fifty_fifty" => "RESOLVED 50/50 {pnl}",
+        "resolution.invalid" => "RESOLVED invalid market - stake refunded",
+        "resolution.disputed" => "DISPUTED \"{market}\" - settlement on hold",
+        "funds.deposit" => "Deposit recorded: ${amount}",
+        "funds.withdrawal" => "Withdrawal recorded: ${amount}",
+        "funds.detected" => "Detected {count} wallet deposit/withdrawal(s)",
+        "funds.scan_behind" => "Transfer scan is {blocks} blocks behind; run it again to catch up",
+        "tally.below_score" => "Skipped {count} markets below the minimum score",
+        "tally.outranked" => "Skipped {count} markets outranked this cycle",
+        "tally.no_capital" => "Skipped {count} markets with no free capital left",
+        "tally.analysis_failed" => "Skipped {count} market analyses failed",
+        "tally.near_duplicate" => "Skipped {count} near-duplicate markets",
+        "tally.below_minimum" => "Skipped {count} orders below exchange minimum",
+        "tally.price_band" => "Skipped {count} orders outside the entry price band",
+        "tally.guardrail" => "Skipped {count} orders blocked by side guardrails",
+        "supervisor.restarting" => "Cycle task panicked: {reason} - restarting in {backoff}s ({restarts}/{max} this hour)",
+        "supervisor.halted" => "Cycle task panicked {count} times in the last hour, trading stopped: {reason}",
+        _ => return None,
+    })
+}
+
+fn es(key: &str) -> Option<&'static str> {
+    Some(match key {
+        "config.updated" => "Configuración actualizada correctamente",
+        "state.imported" => "Estado importado del {exported_at}: {orders} órdenes, {events} eventos del registro",
+        "orders.orphaned" => "Cerradas {count} órdenes activas sin estado de ejecución: se conserva lo ejecutado y se cancela el resto",
+        "bot.testnet" => "🧪 Entorno de pruebas - CLOB de staging en Polygon Amoy",
+        "bot.started" => "🟢 Bot iniciado - Modo supervivencia activo",
+        "bot.started_watch_only" => "🟢 Bot iniciado - Modo solo lectura, no se colocarán órdenes",
+        "bot.stopped" => "🔴 Bot detenido",
+        "cycle.scanning" => "Escaneando mercados... Ciclo #{cycle}",
+        "cycle.processing" => "Procesando {count} mercados...",
+        "cycle.sampled" => "Analizando {count} de {total} mercados (muestreo {strategy})",
+        "cycle.error" => "Error en el ciclo: {error}",
+        "markets.fetch_failed" => "Error al obtener mercados: {error}",
+        "edge.found" => "Ventaja: \"{market}\" > ${size} @ {edge} (justo {fair}, puntuación {score})",
+        "analysis.failed" => "Falló el análisis de \"{market}\": {error}",
+        "embedding.failed" => "Falló el embedding: {error}",
+        "batch.submitted" => "{count} mercados en cola para re-análisis por lotes",
+        "batch.merged" => "{count} análisis por lotes añadidos a la caché de predicciones",
+        "batch.failed" => "Falló el análisis por lotes: {error}",
+        "batch.skipped" => "Omitidos {count} resultados del lote fallidos o ilegibles",
+        "batch.expired" => "Abandonado un lote de {count} mercados sin terminar tras un día",
+        "import.done" => "Importadas {imported} operaciones históricas ({duplicates} ya presentes)",
+        "watch.requires_wallet" => "El modo solo lectura requiere una dirección de wallet",
+        "watch.summary" => "Vigilando {count} posiciones: valor ${value}, PnL {pnl}",
+        "positions.fetch_failed" => "Error al obtener posiciones: {error}",
+        "position.resolved" => "RESUELTO \"{market}\" ({outcome}) {pnl}",
+        "commentary.updated" => "Informe del analista actualizado",
+        "commentary.failed" => "Falló el informe del analista: {error}",
+        "risk.downsized" => "Reducido a ${size}: {reason}",
+        "risk.skipped" => "Omitido \"{market}\": {reason}",
+        "order.below_minimum" => "Orden omitida para \"{market}\": {reason}",
+        "order.price_band" => "Orden omitida para \"{market}\": {reason}",
+        "order.guardrail" => "Orden omitida para \"{market}\": {reason}",
+        "order.offer_pressure" => "Omitido \"{market}\": presión vendedora {pressure}",
+        "order.placed" => "ORDEN ${size} → \"{market}\"",
+        "order.sliced" => "ORDEN ${size} → \"{market}\" dividida en {slices} x ${slice_size} (profundidad ${depth})",
+        "order.slice" => "TRAMO {index}/{total} ${size} → \"{market}\"",
+        "order.resting" => "ORDEN ${size} → \"{market}\" en espera a {price}: ${filled} ejecutado al entrar, {queue} acciones por delante",
+        "order.paper_fill" => "EJECUCIÓN ${size} → \"{market}\" (${filled} de ${total})",
+        "order.rest_expired" => "La orden en espera de \"{market}\" caducó con ${filled} de ${total} ejecutado; resto cancelado",
+        "order.rest_cancelled" => "La orden en espera de \"{market}\" caducó sin ejecutarse y se canceló",
+        "order.amended" => "MODIFICADA ${old_size} @ {old_price} → ${new_size} @ {new_price} \"{market}\"",
+        "order.amend_restored" => "Modificación rechazada para \"{market}\": {error}; se volvió a colocar la orden original",
+        "order.amend_failed" => "Falló la modificación de \"{market}\": reemplazo rechazado: {error}; no se pudo volver a colocar la original: {restore_error}",
+        "order.transition" => "\"{market}\": {from} → {to}",
+        "order.illegal_transition" => "Cambio de estado rechazado para \"{market}\": {from} → {to} no está permitido",
+        "negrisk.bought" => "CESTA {sets} sets {outcome} de \"{event}\" ({legs} resultados) por {cost}: paga {payout}/set, ventaja {edge}/set",
+        "negrisk.converted" => "CONVERTIDOS {sets} sets No de \"{event}\" en {proceeds} {pnl}",
+        "negrisk.no_capital" => "Cesta omitida en \"{event}\": {cost} necesarios, {free} libres",
+        "alert.created" => "Alerta creada: \"{market}\" {outcome} {condition} {threshold} (ahora {price})",
+        "alert.triggered" => "ALERTA \"{market}\" {outcome} {condition} {threshold}: ahora {price}",
+        "conditional.parked" => "En espera \"{market}\" {outcome} hasta {trigger} (ahora {price})",
+        "conditional.triggered" => "Disparador alcanzado en \"{market}\" {outcome}: {trigger} (ahora {price})",
+        "conditional.no_edge" => "Condicional \"{market}\" {outcome} no ejecutada: edge {edge} a {price}",
+        "conditional.manual" => "Condicional \"{market}\" {outcome} no ejecutada: el trading automático está desactivado",
+        "conditional.expired" => "Condicional \"{market}\" {outcome} expirada antes de {trigger}",
+        "conditional.cancelled" => "Condicional \"{market}\" {outcome} cancelada",
+        "report.written" => "Informe guardado en {path}. {summary}",
+        "report.failed" => "No se pudo guardar el informe: {error}",
+        "prompt_packs.reloaded" => "{count} paquetes de prompts recargados",
+        "peg.lost" => "{currency} ha perdido la paridad: ${price}, más allá del umbral de {threshold}; {action}",
+        "peg.restored" => "{currency} recupera la paridad a ${price}",
+        "wallet.approved" => "Las autorizaciones del exchange ya están concedidas",
+        "wallet.approvals_sent" => "Enviadas {count} transacciones de autorización (comisión máx. {fee} POL)",
+        "wallet.approvals_built" => "Preparadas {count} transacciones de autorización sin firmar (comisión máx. {fee} POL); fírmalas en tu wallet",
+        "wallet.approval_failed" => "Autorizaciones detenidas tras {sent} de {total}: {error}",
+        "tally.peg_paused" => "Omitidas {count} entradas mientras la stablecoin está sin paridad",
+        "tally.circuit_open" => "Omitidas {count} llamadas a la API con un cortacircuitos abierto",
+        "breaker.tripped" => "{service} falló {failures} veces seguidas; llamadas en pausa durante {cooldown}s",
+        "breaker.reopened" => "Falló la prueba de {service}; las llamadas siguen en pausa",
+        "breaker.recovered" => "{service} vuelve a responder; llamadas reanudadas",
+        "flatten.started" => "CERRAR TODO: cancelando órdenes y cerrando todas las posiciones",
+        "flatten.step" => "CIERRE {action} \"{market}\": {detail}",
+        "flatten.step_failed" => "CIERRE {action} falló para \"{market}\": {detail}",
+        "flatten.finished" => "CIERRE completado: {steps} pasos, {failed} fallidos, {pnl} realizado",
+        "claude.deferred" => "Cuota de Claude baja: se aplazan {count} análisis a un ciclo posterior ({secs}s hasta que se renueve)",
+        "mm.quoted" => "MM cotizando \"{market}\" {bid} / {ask} (inventario {inventory})",
+        "mm.filled" => "MM {side} {size} @ {price} \"{market}\" (inventario {inventory})",
+        "mm.pulled" => "MM retiró cotizaciones en \"{market}\": el precio medio se movió {moved}",
+        "exit.close" => "SALIDA \"{market}\" @ {bid} {pnl}: justo {fair}, ventaja {edge}, ventaja/σ {ratio}, quedan {hours}h",
+        "exit.reduce" => "REDUCCIÓN {fraction} \"{market}\" @ {bid} {pnl}: justo {fair}, ventaja {edge}, ventaja/σ {ratio}, quedan {hours}h",
+        "exit.hold" => "MANTENER \"{market}\" hasta la resolución: justo {fair}, compra {bid}, ventaja/σ {ratio}, quedan {hours}h",
+        "thesis.review" => "Revaluado \"{market}\" {outcome}: justo {entry} → {fresh} ({drift}), precio {price}; se recomienda {recommendation}",
+        "thesis.acted" => "Revaluado \"{market}\" {outcome}: justo {entry} → {fresh} ({drift}), precio {price}; ejecutado {recommendation}",
+        "resolution.resolved" => "RESUELTO {pnl}",
+        "resolution.fifty_fifty" => "RESUELTO 50/50 {pnl}",
+        "resolution.invalid" => "RESUELTO mercado inválido - apuesta reembolsada",
+        "resolution.disputed" => "DISPUTADO \"{market}\" - liquidación en espera",
+        "funds.deposit" => "Depósito registrado: ${amount}",
+        "funds.withdrawal" => "Retiro registrado: ${amount}",
+        "funds.detected" => "Detectados {count} depósitos/retiros de la wallet",
+        "funds.scan_behind" => "El escaneo de transferencias va {blocks} bloques atrasado; ejecútalo de nuevo para ponerse al día",
+        "tally.below_score" => "Omitidos {count} mercados por debajo de la puntuación mínima",
+        "tally.outranked" => "Omitidos {count} mercados superados en este ciclo",
+        "tally.no_capital" => "Omitidos {count} mercados sin capital libre",
+        "tally.analysis_failed" => "Omitidos {count} análisis de mercado fallidos",
+        "tally.near_duplicate" => "Omitidos {count} mercados casi duplicados",
+        "tally.below_minimum" => "Omitidas {count} órdenes por debajo del mínimo del exchange",
+        "tally.price_band" => "Omitidas {count} órdenes fuera de la banda de precios de entrada",
+        "tally.guardrail" => "Omitidas {count} órdenes bloqueadas por las restricciones de lado",
+        "supervisor.restarting" => "La tarea del ciclo falló: {reason} - reinicio en {backoff}s ({restarts}/{max} esta hora)",
+        "supervisor.halted" => "La tarea del ciclo falló {count} veces en la última hora, trading detenido: {reason}",
+        _ => return None,
+    })
+}
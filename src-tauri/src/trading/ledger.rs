@@ -0,0 +1,226 @@
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LedgerEventKind {
+    OrderPlaced { order_id: String, market_id: String, price: f64, size: f64 },
+    Fill { order_id: String, size: f64 },
+    /// `counted` is false for refunded (invalid) markets, which move no win/loss counters
+    Resolution { order_id: String, market_id: String, pnl: f64, counted: bool },
+    /// Early sale of `fraction` of a position; only a full close counts as a trade
+    Exit { order_id: String, market_id: String, pnl: f64, fraction: f64 },
+    /// A full No set of a neg-risk event converted into collateral
+    Conversion { event_id: String, cost: f64, proceeds: f64 },
+    Deposit { amount: f64, source: FlowSource, note: String },
+    Withdrawal { amount: f64, source: FlowSource, note: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FlowSource {
+    Manual,
+    /// Detected from an on-chain collateral transfer
+    Transfer { tx_hash: String },
+}
+
+/// One deposit or withdrawal with the running net deposits after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapitalFlow {
+    pub timestamp: DateTime<Utc>,
+    /// Positive for deposits, negative for withdrawals
+    pub amount: f64,
+    pub source: FlowSource,
+    pub note: String,
+    pub net_deposits: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEvent {
+    /// Deterministic per-fact id, e.g. "resolution:<order id>"
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub kind: LedgerEventKind,
+}
+
+/// Balance and trade counters recomputed from the ledger
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LedgerTotals {
+    pub realized_pnl: f64,
+    /// Deposits minus withdrawals
+    pub net_deposits: f64,
+    pub wins: u32,
+    pub losses: u32,
+    pub total_trades: u32,
+    pub best_trade: f64,
+    pub worst_trade: f64,
+}
+
+/// Append-only record of trading events. Recording is idempotent by event id, so a
+/// replayed fill or resolution can never be counted twice.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ledger {
+    events: Vec<LedgerEvent>,
+    #[serde(skip)]
+    ids: HashSet<String>,
+}
+
+impl Ledger {
+    /// A ledger holding `events`, in time order
+    pub fn from_events(mut events: Vec<LedgerEvent>) -> Self {
+        events.sort_by_key(|e| e.timestamp);
+        Self { events, ids: HashSet::new() }
+    }
+
+    pub fn order_placed(&mut self, order_id: &str, market_id: &str, price: f64, size: f64) -> bool {
+        self.record(
+            format!("placed:{}", order_id),
+            LedgerEventKind::OrderPlaced {
+                order_id: order_id.to_string(),
+                market_id: market_id.to_string(),
+                price,
+                size,
+            },
+        )
+    }
+
+    pub fn fill(&mut self, order_id: &str, size: f64) -> bool {
+        self.record(
+            format!("fill:{}", order_id),
+            LedgerEventKind::Fill { order_id: order_id.to_string(), size },
+        )
+    }
+
+    /// One of several fills of an order that fills over time; `part` counts from 1
+    pub fn partial_fill(&mut self, order_id: &str, part: u32, size: f64) -> bool {
+        self.record(
+            format!("fill:{}:{}", order_id, part),
+            LedgerEventKind::Fill { order_id: order_id.to_string(), size },
+        )
+    }
+
+    pub fn resolution(&mut self, order_id: &str, market_id: &str, pnl: f64, counted: bool) -> bool {
+        self.record(
+            format!("resolution:{}", order_id),
+            LedgerEventKind::Resolution {
+                order_id: order_id.to_string(),
+                market_id: market_id.to_string(),
+                pnl,
+                counted,
+            },
+        )
+    }
+
+    /// At most one partial reduction and one full close are recorded per order
+    pub fn exit(&mut self, order_id: &str, market_id: &str, pnl: f64, fraction: f64) -> bool {
+        let prefix = if fraction >= 1.0 { "exit" } else { "reduce" };
+        self.record(
+            format!("{}:{}", prefix, order_id),
+            LedgerEventKind::Exit {
+                order_id: order_id.to_string(),
+                market_id: market_id.to_string(),
+                pnl,
+                fraction,
+            },
+        )
+    }
+
+    pub fn conversion(&mut self, id: String, event_id: &str, cost: f64, proceeds: f64) -> bool {
+        self.record(
+            id,
+            LedgerEventKind::Conversion { event_id: event_id.to_string(), cost, proceeds },
+        )
+    }
+
+    pub fn capital_flow(&mut self, id: String, amount: f64, source: FlowSource, note: &str, at: DateTime<Utc>) -> bool {
+        let note = note.to_string();
+        let kind = if amount >= 0.0 {
+            LedgerEventKind::Deposit { amount, source, note }
+        } else {
+            LedgerEventKind::Withdrawal { amount: -amount, source, note }
+        };
+        self.record_at(id, kind, at)
+    }
+
+    /// Append an event unless one with the same id exists; returns whether it was new
+    pub fn record(&mut self, id: String, kind: LedgerEventKind) -> bool {
+        self.record_at(id, kind, Utc::now())
+    }
+
+    pub fn record_at(&mut self, id: String, kind: LedgerEventKind, timestamp: DateTime<Utc>) -> bool {
+        if self.ids.is_empty() && !self.events.is_empty() {
+            self.ids = self.events.iter().map(|e| e.id.clone()).collect();
+        }
+        if !self.ids.insert(id.clone()) {
+            return false;
+        }
+        self.events.push(LedgerEvent { id, timestamp, kind });
+        true
+    }
+
+    pub fn events(&self) -> &[LedgerEvent] {
+        &self.events
+    }
+
+    /// Win/loss counters and best/worst trade judge a position by its whole PnL: a partial
+    /// exit is carried over to the settlement of the rest of the order
+    pub fn totals(&self) -> LedgerTotals {
+        let mut totals = LedgerTotals::default();
+        let mut reduced: HashMap<&str, f64> = HashMap::new();
+        for event in &self.events {
+            let (pnl, counted, order_id) = match &event.kind {
+                LedgerEventKind::Resolution { order_id, pnl, counted, .. } => (*pnl, *counted, Some(order_id)),
+                LedgerEventKind::Exit { order_id, pnl, fraction, .. } => (*pnl, *fraction >= 1.0, Some(order_id)),
+                LedgerEventKind::Conversion { cost, proceeds, .. } => (proceeds - cost, true, None),
+                LedgerEventKind::Deposit { amount, .. } => {
+                    totals.net_deposits += amount;
+                    continue;
+                }
+                LedgerEventKind::Withdrawal { amount, .. } => {
+                    totals.net_deposits -= amount;
+                    continue;
+                }
+                _ => continue,
+            };
+            totals.realized_pnl += pnl;
+            let earlier = order_id.and_then(|id| reduced.remove(id.as_str())).unwrap_or(0.0);
+            if !counted {
+                if let (LedgerEventKind::Exit { .. }, Some(id)) = (&event.kind, order_id) {
+                    reduced.insert(id.as_str(), earlier + pnl);
+                }
+                continue;
+            }
+            let pnl = pnl + earlier;
+            totals.total_trades += 1;
+            if pnl > 0.0 {
+                totals.wins += 1;
+                totals.best_trade = totals.best_trade.max(pnl);
+            } else {
+                totals.losses += 1;
+                totals.worst_trade = totals.worst_trade.min(pnl);
+            }
+        }
+        totals
+    }
+
+    /// Deposits and withdrawals in time order
+    pub fn capital_flows(&self) -> Vec<CapitalFlow> {
+        let mut flows: Vec<(DateTime<Utc>, f64, &FlowSource, &String)> = self.events
+            .iter()
+            .filter_map(|e| match &e.kind {
+                LedgerEventKind::Deposit { amount, source, note } => Some((e.timestamp, *amount, source, note)),
+                LedgerEventKind::Withdrawal { amount, source, note } => Some((e.timestamp, -amount, source, note)),
+                _ => None,
+            })
+            .collect();
+        flows.sort_by_key(|f| f.0);
+
+        let mut net_deposits = 0.0;
+        flows
+            .into_iter()
+            .map(|(timestamp, amount, source, note)| {
+                net_deposits += amount;
+                CapitalFlow { timestamp, amount, source: source.clone(), note: note.clone(), net_deposits }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::models::*;
+
+/// Status changes kept in memory for inspection, oldest dropped first
+pub const MAX_TRANSITIONS: usize = 500;
+
+impl OrderStatus {
+    /// Accepted and not yet completely filled
+    pub fn is_working(self) -> bool {
+        matches!(self, OrderStatus::Pending | OrderStatus::Open | OrderStatus::PartiallyFilled)
+    }
+
+    /// No further transitions are possible
+    pub fn is_terminal(self) -> bool {
+        matches!(self, OrderStatus::Resolved | OrderStatus::Cancelled | OrderStatus::Failed)
+    }
+
+    /// Pending → Open → PartiallyFilled → Filled → Resolved. Stages can be skipped
+    /// (a paper order fills on creation), but never revisited; a working order can be
+    /// cancelled or fail, and a filled one only resolves.
+    pub fn can_become(self, to: OrderStatus) -> bool {
+        use OrderStatus::*;
+        match self {
+            Pending => matches!(to, Open | PartiallyFilled | Filled | Cancelled | Failed),
+            Open => matches!(to, PartiallyFilled | Filled | Cancelled | Failed),
+            PartiallyFilled => matches!(to, Filled | Cancelled | Failed),
+            Filled => to == Resolved,
+            Resolved | Cancelled | Failed => false,
+        }
+    }
+}
+
+/// A status change applied by [`transition`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderTransition {
+    pub order_id: String,
+    pub market_name: String,
+    pub from: OrderStatus,
+    pub to: OrderStatus,
+    pub at: DateTime<Utc>,
+}
+
+/// A status change refused by [`OrderStatus::can_become`]; the order was left untouched
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Order {order_id} cannot go from {from:?} to {to:?}")]
+pub struct IllegalTransition {
+    pub order_id: String,
+    pub from: OrderStatus,
+    pub to: OrderStatus,
+}
+
+/// The only place an order's status changes once it exists
+pub fn transition(order: &mut Order, to: OrderStatus, at: DateTime<Utc>) -> Result<OrderTransition, IllegalTransition> {
+    let from = order.status;
+    if !from.can_become(to) {
+        return Err(IllegalTransition { order_id: order.id.clone(), from, to });
+    }
+    order.status = to;
+    order.status_changed_at = Some(at);
+    Ok(OrderTransition { order_id: order.id.clone(), market_name: order.market_name.clone(), from, to, at })
+}
+
+/// Add `size` dollars filled at `price` to the order's fills, moving it to PartiallyFilled or
+/// Filled and folding the price into its average. Returns `None` when a further partial fill
+/// leaves the status unchanged.
+pub fn fill(order: &mut Order, size: f64, price: f64, at: DateTime<Utc>) -> Result<Option<OrderTransition>, IllegalTransition> {
+    let filled = order.filled_size + size;
+    let to = if filled >= order.size - 1e-9 { OrderStatus::Filled } else { OrderStatus::PartiallyFilled };
+    let change = if order.status == to { None } else { Some(transition(order, to, at)?) };
+    if price > 0.0 {
+        let held = order.shares_for(order.filled_size);
+        order.fill_price = Some(filled / (held + size / price));
+    }
+    order.filled_size = filled;
+    Ok(change)
+}
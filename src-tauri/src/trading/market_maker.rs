@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use super::config;
+use super::engine::TradingEngine;
+use super::models::*;
+
+/// Two-sided quoting around the model's fair value for a handful of chosen markets.
+/// Quotes are simulated like every other order: a quote fills when the book trades through it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketMakerConfig {
+    pub enabled: bool,
+    /// Market ids to quote
+    pub markets: Vec<String>,
+    /// Distance of each quote from the (skewed) fair value
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub half_spread: f64,
+    /// Shares per quote
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub quote_size: f64,
+    /// Max shares held long or short; the side that would grow inventory is pulled at the limit
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub max_inventory: f64,
+    /// Fair value shift at full inventory, as a fraction of `half_spread`
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub inventory_skew: f64,
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub refresh_secs: u32,
+    /// Mid move between refreshes treated as news: quotes are pulled for the cooldown
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub news_move: f64,
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub news_cooldown_secs: u32,
+}
+
+impl Default for MarketMakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            markets: Vec::new(),
+            half_spread: 0.02,
+            quote_size: 10.0,
+            max_inventory: 100.0,
+            inventory_skew: 1.0,
+            refresh_secs: 30,
+            news_move: 0.05,
+            news_cooldown_secs: 300,
+        }
+    }
+}
+
+/// Requote every `refresh_secs` while the bot is running, so quotes keep their own cadence
+/// instead of waiting on the scan interval
+pub async fn quoter(engine: Arc<Mutex<TradingEngine>>) {
+    loop {
+        let refresh = engine.lock().await.config.market_maker.refresh_secs.max(1);
+        tokio::time::sleep(std::time::Duration::from_secs(refresh as u64)).await;
+        let mut eng = engine.lock().await;
+        if eng.is_running {
+            eng.refresh_quotes().await;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Quote {
+    pub price: f64,
+    pub size: f64,
+}
+
+/// Quoting state for one market, in units of the first outcome's shares
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MakerBook {
+    pub market_id: String,
+    pub market_name: String,
+    pub fair_value: Option<f64>,
+    pub bid: Option<Quote>,
+    pub ask: Option<Quote>,
+    /// Shares held; negative when short
+    pub inventory: f64,
+    pub cash: f64,
+    /// Cash plus inventory marked at the last mid
+    pub pnl: f64,
+    pub last_mid: Option<f64>,
+    pub last_refresh: Option<DateTime<Utc>>,
+    pub pulled_until: Option<DateTime<Utc>>,
+    pub fills: u32,
+}
+
+impl MakerBook {
+    pub fn is_pulled(&self, now: DateTime<Utc>) -> bool {
+        self.pulled_until.is_some_and(|t| now < t)
+    }
+}
+
+/// What happened to a book during one update, for the activity feed
+#[derive(Debug, Clone)]
+pub enum MakerEvent {
+    Filled { side: OrderSide, price: f64, size: f64 },
+    Pulled { moved: f64 },
+    Requoted,
+}
+
+/// Bid and ask around `fair`, shifted against current inventory and rounded outward to
+/// the tick. The side that would push inventory past the limit is left out.
+pub fn compute_quotes(fair: f64, inventory: f64, config: &MarketMakerConfig, tick: f64) -> (Option<Quote>, Option<Quote>) {
+    let fill = (inventory / config.max_inventory.max(f64::EPSILON)).clamp(-1.0, 1.0);
+    let center = fair - fill * config.inventory_skew * config.half_spread;
+
+    let bid_price = ((center - config.half_spread) / tick).floor() * tick;
+    let ask_price = ((center + config.half_spread) / tick).ceil() * tick;
+    let in_range = |p: f64| p >= tick && p <= 1.0 - tick;
+
+    let bid = (in_range(bid_price) && inventory + config.quote_size <= config.max_inventory)
+        .then_some(Quote { price: bid_price, size: config.quote_size });
+    let ask = (in_range(ask_price) && inventory - config.quote_size >= -config.max_inventory)
+        .then_some(Quote { price: ask_price, size: config.quote_size });
+    (bid, ask)
+}
+
+/// Apply one book snapshot: fill quotes the market traded through, pull on a news-sized
+/// move, and requote when the refresh interval has passed
+pub fn update(
+    book: &mut MakerBook,
+    snapshot: &OrderBook,
+    fair: Option<f64>,
+    tick: f64,
+    config: &MarketMakerConfig,
+    now: DateTime<Utc>,
+) -> Vec<MakerEvent> {
+    let mut events = Vec::new();
+    let best_bid = snapshot.bids.iter().map(|l| l.price).max_by(f64::total_cmp);
+    let best_ask = snapshot.asks.iter().map(|l| l.price).min_by(f64::total_cmp);
+
+    if let (Some(quote), Some(ask)) = (book.bid, best_ask) {
+        if ask <= quote.price {
+            book.inventory += quote.size;
+            book.cash -= quote.price * quote.size;
+            book.fills += 1;
+            book.bid = None;
+            events.push(MakerEvent::Filled { side: OrderSide::Buy, price: quote.price, size: quote.size });
+        }
+    }
+    if let (Some(quote), Some(bid)) = (book.ask, best_bid) {
+        if bid >= quote.price {
+            book.inventory -= quote.size;
+            book.cash += quote.price * quote.size;
+            book.fills += 1;
+            book.ask = None;
+            events.push(MakerEvent::Filled { side: OrderSide::Sell, price: quote.price, size: quote.size });
+        }
+    }
+
+    let mid = match (best_bid, best_ask) {
+        (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+        _ => None,
+    };
+    if let (Some(mid), Some(last)) = (mid, book.last_mid) {
+        if (mid - last).abs() >= config.news_move && !book.is_pulled(now) {
+            book.bid = None;
+            book.ask = None;
+            book.pulled_until = Some(now + Duration::seconds(config.news_cooldown_secs as i64));
+            events.push(MakerEvent::Pulled { moved: mid - last });
+        }
+    }
+    if mid.is_some() {
+        book.last_mid = mid;
+    }
+    book.pnl = book.cash + book.inventory * book.last_mid.unwrap_or(0.0);
+    book.fair_value = fair;
+
+    let due = book.last_refresh
+        .is_none_or(|t| now - t >= Duration::seconds(config.refresh_secs as i64));
+    if book.is_pulled(now) || !due {
+        return events;
+    }
+
+    (book.bid, book.ask) = match fair {
+        Some(fair) => compute_quotes(fair, book.inventory, config, tick),
+        None => (None, None),
+    };
+    book.last_refresh = Some(now);
+    events.push(MakerEvent::Requoted);
+    events
+}
+
+/// Books for the configured markets, created on first use
+#[derive(Debug, Clone, Default)]
+pub struct MarketMaker {
+    books: HashMap<String, MakerBook>,
+}
+
+impl MarketMaker {
+    pub fn book_mut(&mut self, market: &Market) -> &mut MakerBook {
+        self.books.entry(market.id.clone()).or_insert_with(|| MakerBook {
+            market_id: market.id.clone(),
+            market_name: market.question.clone(),
+            ..MakerBook::default()
+        })
+    }
+
+    pub fn books(&self) -> Vec<MakerBook> {
+        let mut books: Vec<MakerBook> = self.books.values().cloned().collect();
+        books.sort_by(|a, b| a.market_name.cmp(&b.market_name));
+        books
+    }
+}
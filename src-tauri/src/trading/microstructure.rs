@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use super::models::*;
+
+/// Book levels per side counted towards depth asymmetry
+const DEPTH_LEVELS: usize = 5;
+/// Edges within this fraction above the threshold count as marginal
+const MARGINAL_EDGE_BAND: f64 = 0.25;
+
+/// Order flow signals for one outcome token. All ratios are in [-1, 1] with
+/// positive values meaning buy-side support.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MicrostructureSignals {
+    /// Best bid size against best ask size
+    pub book_imbalance: f64,
+    /// Dollar depth on the top levels of each side
+    pub depth_asymmetry: f64,
+    /// Taker buy volume against taker sell volume in recent trades; `None` without trades
+    pub aggressor_ratio: Option<f64>,
+    pub trade_count: u32,
+}
+
+impl MicrostructureSignals {
+    /// `trades` should be recent fills of the same token
+    pub fn compute(book: &OrderBook, trades: &[MarketTrade]) -> Self {
+        let best_bid = book.bids.iter().max_by(|a, b| a.price.total_cmp(&b.price));
+        let best_ask = book.asks.iter().min_by(|a, b| a.price.total_cmp(&b.price));
+        let book_imbalance = ratio(
+            best_bid.map(|l| l.size).unwrap_or(0.0),
+            best_ask.map(|l| l.size).unwrap_or(0.0),
+        );
+
+        let depth_asymmetry = ratio(side_depth(&book.bids, true), side_depth(&book.asks, false));
+
+        let volume = |buys: bool| -> f64 {
+            trades.iter()
+                .filter(|t| matches!(t.side, OrderSide::Buy) == buys)
+                .map(|t| t.price * t.size)
+                .sum()
+        };
+        let (bought, sold) = (volume(true), volume(false));
+        let aggressor_ratio = if bought + sold > 0.0 { Some(ratio(bought, sold)) } else { None };
+
+        Self {
+            book_imbalance,
+            depth_asymmetry,
+            aggressor_ratio,
+            trade_count: trades.len() as u32,
+        }
+    }
+
+    /// Net selling pressure in [-1, 1]; the inverse of the weighted buy-side signals
+    pub fn offer_pressure(&self) -> f64 {
+        let (flow, flow_weight) = match self.aggressor_ratio {
+            Some(r) => (r, 0.4),
+            None => (0.0, 0.0),
+        };
+        let weight = 0.3 + 0.3 + flow_weight;
+        -(0.3 * self.book_imbalance + 0.3 * self.depth_asymmetry + flow_weight * flow) / weight
+    }
+
+    /// Execution gate for a buy: veto heavy offer-side pressure outright, and require
+    /// the book to at least not lean against us when the edge is marginal
+    pub fn veto_buy(&self, edge: f64, min_edge: f64, max_pressure: f64) -> bool {
+        let pressure = self.offer_pressure();
+        let marginal = edge < min_edge * (1.0 + MARGINAL_EDGE_BAND);
+        pressure >= max_pressure || (marginal && pressure > 0.0)
+    }
+}
+
+/// (a - b) / (a + b), or 0 when both are empty
+fn ratio(a: f64, b: f64) -> f64 {
+    if a + b > 0.0 { (a - b) / (a + b) } else { 0.0 }
+}
+
+fn side_depth(levels: &[BookLevel], bids: bool) -> f64 {
+    let mut sorted: Vec<&BookLevel> = levels.iter().collect();
+    if bids {
+        sorted.sort_by(|a, b| b.price.total_cmp(&a.price));
+    } else {
+        sorted.sort_by(|a, b| a.price.total_cmp(&b.price));
+    }
+    sorted.iter().take(DEPTH_LEVELS).map(|l| l.price * l.size).sum()
+}
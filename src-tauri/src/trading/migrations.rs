@@ -0,0 +1,146 @@
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use super::db::Table;
+
+/// One upgrade step for a history row, taking it from its position in [`ROW_MIGRATIONS`]
+/// to the next version
+pub struct RowMigration {
+    pub description: &'static str,
+    pub apply: fn(Table, &mut Value) -> Result<()>,
+}
+
+/// One upgrade step for a state archive
+pub struct ArchiveMigration {
+    pub description: &'static str,
+    pub apply: fn(&mut Value) -> Result<()>,
+}
+
+/// Step `i` upgrades rows from version `i` to `i + 1`. Rows written before versioning
+/// read as version 0. Append new steps; never edit or reorder released ones.
+pub const ROW_MIGRATIONS: &[RowMigration] = &[
+    RowMigration {
+        description: "record filled_size on orders written before fills were tracked",
+        apply: backfill_filled_size,
+    },
+];
+
+/// Step `i` upgrades archives from version `i + 1` to `i + 2`; the first archives were version 1
+pub const ARCHIVE_MIGRATIONS: &[ArchiveMigration] = &[];
+
+/// Version of the rows this build writes
+pub const ROW_VERSION: u32 = ROW_MIGRATIONS.len() as u32;
+
+/// Version of the state archives this build writes
+pub const ARCHIVE_VERSION: u32 = ARCHIVE_MIGRATIONS.len() as u32 + 1;
+
+/// Bring a row written at `version` up to [`ROW_VERSION`]. Rows from a newer build are
+/// refused, so an older app never rewrites data it does not understand.
+pub fn upgrade_row(table: Table, version: u32, row: &mut Value) -> Result<()> {
+    if version > ROW_VERSION {
+        bail!("{} row has schema version {}, newer than this app reads ({})", table.file_name(), version, ROW_VERSION);
+    }
+    for step in &ROW_MIGRATIONS[version as usize..] {
+        (step.apply)(table, row).with_context(|| format!("Migrating {} row: {}", table.file_name(), step.description))?;
+    }
+    Ok(())
+}
+
+/// Bring an archive written at `version` up to [`ARCHIVE_VERSION`], setting its version field
+pub fn upgrade_archive(version: u32, archive: &mut Value) -> Result<()> {
+    if version == 0 || version > ARCHIVE_VERSION {
+        bail!("Archive version {} is not supported; this app reads 1 to {}", version, ARCHIVE_VERSION);
+    }
+    for step in &ARCHIVE_MIGRATIONS[version as usize - 1..] {
+        (step.apply)(archive).with_context(|| format!("Migrating archive: {}", step.description))?;
+    }
+    archive["version"] = Value::from(ARCHIVE_VERSION);
+    Ok(())
+}
+
+/// Orders completed before `filled_size` existed read it as 0, which counts them as never
+/// filled. Sliced parents are left alone; their children carry the fills.
+fn backfill_filled_size(table: Table, row: &mut Value) -> Result<()> {
+    if table != Table::Orders {
+        return Ok(());
+    }
+    let completed = matches!(row.get("status").and_then(Value::as_str), Some("Filled" | "Resolved"));
+    let parent = row.get("child_ids").and_then(Value::as_array).is_some_and(|ids| !ids.is_empty());
+    let unfilled = row.get("filled_size").and_then(Value::as_f64).unwrap_or(0.0) == 0.0;
+    if completed && !parent && unfilled {
+        let size = row.get("size").and_then(Value::as_f64).context("Order without a size")?;
+        row["filled_size"] = Value::from(size);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::db::Database;
+    use serde_json::json;
+
+    fn legacy_order(status: &str) -> Value {
+        json!({ "id": "o1", "status": status, "size": 12.5, "child_ids": [] })
+    }
+
+    #[test]
+    fn unversioned_filled_order_gets_its_size_as_filled() {
+        let mut row = legacy_order("Filled");
+        upgrade_row(Table::Orders, 0, &mut row).unwrap();
+        assert_eq!(row["filled_size"], json!(12.5));
+    }
+
+    #[test]
+    fn cancelled_orders_and_parents_are_not_backfilled() {
+        let mut cancelled = legacy_order("Cancelled");
+        upgrade_row(Table::Orders, 0, &mut cancelled).unwrap();
+        assert!(cancelled.get("filled_size").is_none());
+
+        let mut parent = json!({ "id": "p1", "status": "Filled", "size": 30.0, "child_ids": ["c1"] });
+        upgrade_row(Table::Orders, 0, &mut parent).unwrap();
+        assert!(parent.get("filled_size").is_none());
+    }
+
+    #[test]
+    fn current_rows_are_unchanged() {
+        let mut row = legacy_order("Filled");
+        upgrade_row(Table::Orders, ROW_VERSION, &mut row).unwrap();
+        assert_eq!(row, legacy_order("Filled"));
+    }
+
+    #[test]
+    fn newer_rows_are_refused() {
+        let mut row = legacy_order("Filled");
+        assert!(upgrade_row(Table::Orders, ROW_VERSION + 1, &mut row).is_err());
+    }
+
+    #[test]
+    fn archive_versions_outside_the_known_range_are_refused() {
+        let mut archive = json!({ "version": ARCHIVE_VERSION });
+        upgrade_archive(ARCHIVE_VERSION, &mut archive).unwrap();
+        assert!(upgrade_archive(0, &mut archive).is_err());
+        assert!(upgrade_archive(ARCHIVE_VERSION + 1, &mut archive).is_err());
+    }
+
+    #[test]
+    fn opening_a_legacy_database_migrates_rows_and_keeps_a_backup() {
+        let dir = std::env::temp_dir().join(format!("migrations-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let legacy = json!({ "key": "o1", "written_at": "2024-01-01T00:00:00Z", "row": legacy_order("Resolved") });
+        std::fs::write(dir.join("orders.jsonl"), format!("{}\n", legacy)).unwrap();
+
+        let db = Database::open(&dir).unwrap();
+        let rows = db.export_rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].row["filled_size"], json!(12.5));
+        assert!(dir.join("orders.jsonl.v0.bak").exists());
+
+        // The rewritten file is at the current version, so reopening changes nothing
+        let rewritten = std::fs::read_to_string(dir.join("orders.jsonl")).unwrap();
+        let record: Value = serde_json::from_str(rewritten.lines().next().unwrap()).unwrap();
+        assert_eq!(record["version"], json!(ROW_VERSION));
+        assert_eq!(Database::open(&dir).unwrap().export_rows()[0].row, rows[0].row);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
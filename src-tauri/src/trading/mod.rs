@@ -1,4 +1,49 @@
 pub mod models;
+pub mod config;
+pub mod http;
+pub mod error;
+pub mod network;
+pub mod chain;
+pub mod wallet;
+pub mod currency;
+pub mod time;
+pub mod gamma;
 pub mod polymarket;
 pub mod claude;
+pub mod ratelimit;
+pub mod batch;
+pub mod db;
+pub mod migrations;
+pub mod features;
+pub mod microstructure;
+pub mod prompt;
+pub mod prompt_packs;
+pub mod embeddings;
+pub mod risk;
+pub mod scoring;
+pub mod sampling;
+pub mod execution;
+pub mod lifecycle;
+pub mod fills;
+pub mod exits;
+pub mod reunderwrite;
+pub mod flatten;
+pub mod market_maker;
+pub mod negrisk;
+pub mod backtest;
+pub mod simulation;
+pub mod notifications;
+pub mod alerts;
+pub mod reports;
+pub mod conditional;
+pub mod health;
+pub mod diagnostics;
+pub mod bundle;
+pub mod archive;
+pub mod guest;
+pub mod snapshot;
+pub mod i18n;
+pub mod ledger;
 pub mod engine;
+pub mod ab;
+pub mod supervisor;
@@ -1,7 +1,27 @@
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+use super::claude::CLAUDE_API_URL;
+use super::currency::CurrencyConfig;
+use super::config;
+use super::embeddings::EmbeddingProvider;
+use super::execution;
+use super::fills::PaperFillConfig;
+use super::health::CircuitBreakerConfig;
+use super::network::Environment;
+use super::notifications::NotificationConfig;
+use super::exits::ExitConfig;
+use super::market_maker::MarketMakerConfig;
+use super::negrisk::NegRiskConfig;
+use super::reports::ReportConfig;
+use super::reunderwrite::ReunderwriteConfig;
+use super::sampling::SamplingConfig;
+use super::scoring::ScoringConfig;
+use super::risk::{CategoryUtilization, RiskConfig};
+use super::time;
+
 // ─── Trading Models ───────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,8 +33,111 @@ pub struct Market {
     pub outcome_prices: Vec<f64>,
     pub volume: f64,
     pub liquidity: f64,
+    #[serde(default)]
+    pub volume_24h: f64,
+    #[serde(default)]
+    pub volume_1wk: f64,
     pub end_date: Option<String>,
     pub active: bool,
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Tag labels of the market and its event, used to route prompt packs
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// CLOB token ids, one per outcome in the same order as `outcomes`
+    #[serde(default)]
+    pub token_ids: Vec<String>,
+    #[serde(default)]
+    pub resolution: Option<MarketResolution>,
+    /// Outcome of a mutually exclusive (neg-risk) event
+    #[serde(default)]
+    pub neg_risk: bool,
+    #[serde(default)]
+    pub neg_risk_market_id: Option<String>,
+    #[serde(default)]
+    pub question_id: Option<String>,
+    #[serde(default)]
+    pub event_id: Option<String>,
+    /// Minimum price increment accepted by the CLOB
+    #[serde(default = "default_tick_size")]
+    pub tick_size: f64,
+    /// Minimum order size in shares
+    #[serde(default = "default_min_order_size")]
+    pub min_order_size: f64,
+}
+
+fn default_tick_size() -> f64 {
+    execution::DEFAULT_TICK_SIZE
+}
+
+fn default_min_order_size() -> f64 {
+    execution::DEFAULT_MIN_ORDER_SIZE
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ResolutionStatus {
+    /// Market still open, or closed without a proposed outcome yet
+    Pending,
+    /// Outcome proposed on UMA, still inside the challenge window
+    Proposed,
+    /// Proposal challenged; settlement is on hold until the dispute is voted
+    Disputed,
+    /// One outcome won and pays out 1.0
+    Resolved,
+    /// Resolved 50/50, every outcome pays out 0.5
+    FiftyFifty,
+    /// Closed without a usable payout vector; stakes are treated as refunded
+    Invalid,
+}
+
+/// Authoritative resolution data for a market, from Gamma / UMA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketResolution {
+    pub status: ResolutionStatus,
+    pub winning_outcome: Option<String>,
+    pub outcomes: Vec<String>,
+    /// Final payout per share for each outcome
+    pub payouts: Vec<f64>,
+    #[serde(default, deserialize_with = "time::deserialize_option")]
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl MarketResolution {
+    pub fn is_settled(&self) -> bool {
+        matches!(
+            self.status,
+            ResolutionStatus::Resolved | ResolutionStatus::FiftyFifty | ResolutionStatus::Invalid
+        )
+    }
+
+    /// Payout per share for an outcome, `None` when the market cannot be settled yet
+    pub fn payout_for(&self, outcome: &str) -> Option<f64> {
+        match self.status {
+            ResolutionStatus::Resolved | ResolutionStatus::FiftyFifty => {
+                let idx = self.outcomes.iter().position(|o| o.eq_ignore_ascii_case(outcome))?;
+                self.payouts.get(idx).copied()
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub timestamp: DateTime<Utc>,
+    pub price: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,9 +150,77 @@ pub struct Order {
     pub price: f64,
     pub size: f64,
     pub status: OrderStatus,
-    pub created_at: String,
-    pub resolved_at: Option<String>,
+    #[serde(deserialize_with = "time::deserialize")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, deserialize_with = "time::deserialize_option")]
+    pub resolved_at: Option<DateTime<Utc>>,
     pub pnl: Option<f64>,
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Set on child slices of a larger order
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// Set on parent orders that were sliced; the children carry the actual fills
+    #[serde(default)]
+    pub child_ids: Vec<String>,
+    #[serde(default)]
+    pub filled_size: f64,
+    /// Average price paid per share over the fills so far; `None` until something fills
+    #[serde(default)]
+    pub fill_price: Option<f64>,
+    #[serde(default)]
+    pub resolution: Option<MarketResolution>,
+    /// Exchange-side identifier for orders imported from the account history
+    #[serde(default)]
+    pub external_id: Option<String>,
+    /// CLOB token of the traded outcome
+    #[serde(default)]
+    pub token_id: Option<String>,
+    /// Id of the resting order on the CLOB, for orders placed live
+    #[serde(default)]
+    pub clob_order_id: Option<String>,
+    /// Order this one was amended from
+    #[serde(default)]
+    pub replaces: Option<String>,
+    /// Order that amended and cancelled this one
+    #[serde(default)]
+    pub replaced_by: Option<String>,
+    /// PnL already realized by selling part of the position before resolution
+    #[serde(default)]
+    pub exited_pnl: f64,
+    /// When the status last changed; `None` while it still has the one it was created with
+    #[serde(default, deserialize_with = "time::deserialize_option")]
+    pub status_changed_at: Option<DateTime<Utc>>,
+}
+
+impl Order {
+    pub fn is_parent(&self) -> bool {
+        !self.child_ids.is_empty()
+    }
+
+    /// Imported from the account history: a record of a real trade, not a position the bot
+    /// sizes, settles, exits or flattens
+    pub fn is_imported(&self) -> bool {
+        self.external_id.is_some()
+    }
+
+    /// Price paid per share: the fills' average, or the limit before anything filled
+    pub fn entry_price(&self) -> f64 {
+        self.fill_price.unwrap_or(self.price)
+    }
+
+    /// Shares bought with `cost` of the filled dollars
+    pub fn shares_for(&self, cost: f64) -> f64 {
+        let price = self.entry_price();
+        if price > 0.0 { cost / price } else { 0.0 }
+    }
+
+    /// Shares of the whole order: the filled part at the fills' average, the rest at the limit
+    pub fn shares(&self) -> f64 {
+        let unfilled = (self.size - self.filled_size).max(0.0);
+        let resting = if self.price > 0.0 { unfilled / self.price } else { 0.0 };
+        self.shares_for(self.filled_size) + resting
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,37 +229,124 @@ pub enum OrderSide {
     Sell,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Changed only through `lifecycle::transition`, which enforces the legal order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderStatus {
+    /// Created, not yet accepted by the exchange
     Pending,
+    /// Resting on the book, or a sliced order waiting for its first slice
+    Open,
+    PartiallyFilled,
     Filled,
     Resolved,
     Cancelled,
     Failed,
 }
 
+/// An open position held by the account, as reported by the Polymarket data API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    pub market_id: String,
+    pub asset_id: String,
+    pub market_name: String,
+    pub outcome: String,
+    pub size: f64,
+    pub avg_price: f64,
+    pub current_price: f64,
+    pub initial_value: f64,
+    pub current_value: f64,
+    pub pnl: f64,
+    pub pnl_pct: f64,
+    pub redeemable: bool,
+    pub end_date: Option<String>,
+    /// Traded on the neg-risk exchange, which orders must be signed for
+    #[serde(default)]
+    pub neg_risk: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortfolioSummary {
+    pub positions: Vec<Position>,
+    pub total_cost: f64,
+    pub total_value: f64,
+    pub unrealized_pnl: f64,
+    pub resolved_count: u32,
+    pub category_utilization: Vec<CategoryUtilization>,
+}
+
+/// A recent fill on a market's public tape; `side` is the taker's side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketTrade {
+    pub asset: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub size: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A historical fill from the Polymarket data API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoricalTrade {
+    pub external_id: String,
+    pub market_id: String,
+    pub market_name: String,
+    pub side: OrderSide,
+    pub outcome: String,
+    pub price: f64,
+    /// Shares traded
+    pub size: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub fetched: u32,
+    pub imported: u32,
+    pub duplicates: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeResult {
     pub order_id: String,
     pub market_name: String,
     pub pnl: f64,
     pub status: String,
-    pub timestamp: String,
+    #[serde(deserialize_with = "time::deserialize")]
+    pub timestamp: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BalancePoint {
-    pub timestamp: String,
+    #[serde(deserialize_with = "time::deserialize")]
+    pub timestamp: DateTime<Utc>,
+    /// Wall-clock time in the configured display timezone, filled on read
+    #[serde(default)]
+    pub local_time: String,
     pub balance: f64,
     pub label: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BotStats {
+    /// Realized balance: open positions count at cost until they resolve or are sold
     pub current_balance: f64,
+    /// Balance including stake locked in open positions, valued at cost
+    pub total_equity: f64,
+    /// Balance with open positions at the last seen market price; at cost until one is seen
+    #[serde(default)]
+    pub mark_to_market_equity: f64,
+    /// Balance with open positions at the model's fair value, i.e. their expected payout
+    #[serde(default)]
+    pub model_equity: f64,
+    /// Equity minus open exposure and reserved amounts; what new trades are sized from
+    pub free_capital: f64,
     pub initial_balance: f64,
+    /// Trading PnL, excluding deposits and withdrawals
     pub total_pnl: f64,
     pub total_pnl_pct: String,
+    /// Deposits minus withdrawals since start
+    #[serde(default)]
+    pub net_deposits: f64,
     pub api_costs: f64,
     pub win_rate: f64,
     pub wins: u32,
@@ -85,13 +363,57 @@ pub struct BotStats {
     pub uptime: String,
     pub cycle: u32,
     pub pid: u32,
+    /// Symbol of the currency every amount above is in
+    #[serde(default)]
+    pub currency: String,
+}
+
+impl BotStats {
+    /// Total PnL as a percentage of the capital put in: the initial balance plus net deposits
+    pub fn pnl_percent(&self) -> f64 {
+        let capital = self.initial_balance + self.net_deposits;
+        if capital > 0.0 { self.total_pnl / capital * 100.0 } else { 0.0 }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActivityEntry {
-    pub timestamp: String,
+    /// Monotonic sequence number, used to find entries added since a point in time
+    #[serde(default)]
+    pub seq: u64,
+    #[serde(deserialize_with = "time::deserialize")]
+    pub timestamp: DateTime<Utc>,
+    /// Wall-clock time in the configured display timezone, filled on read
+    #[serde(default)]
+    pub local_time: String,
+    /// English text; re-rendered in the active locale on read when `key` is set
     pub message: String,
     pub entry_type: ActivityType,
+    #[serde(default)]
+    pub level: LogLevel,
+    /// Stable message key for localization and exports, absent for free-form entries
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LogLevel {
+    Debug,
+    #[default]
+    Info,
+    Important,
+}
+
+impl LogLevel {
+    /// Level used when an activity is recorded without an explicit one
+    pub fn default_for(entry_type: &ActivityType) -> Self {
+        match entry_type {
+            ActivityType::Info | ActivityType::Inference => LogLevel::Info,
+            _ => LogLevel::Important,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,19 +430,99 @@ pub enum ActivityType {
 // ─── Configuration Models ─────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BotConfig {
     pub polymarket_api_key: String,
     pub polymarket_secret: String,
     pub polymarket_passphrase: String,
     pub claude_api_key: String,
     pub claude_model: String,
+    #[serde(deserialize_with = "config::lenient_f64")]
     pub initial_balance: f64,
+    #[serde(deserialize_with = "config::lenient_f64")]
     pub max_bet_size: f64,
+    #[serde(deserialize_with = "config::lenient_f64")]
     pub min_edge_threshold: f64,
+    #[serde(deserialize_with = "config::lenient_u32")]
     pub max_concurrent_orders: u32,
+    #[serde(deserialize_with = "config::lenient_u32")]
     pub scan_interval_secs: u32,
     pub auto_trading: bool,
     pub survival_mode: bool,
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub max_prompt_tokens: u32,
+    /// HTTP(S) or SOCKS5 proxy URL applied to all outgoing requests, empty for none
+    pub proxy_url: String,
+    /// Endpoint overrides; empty uses the selected environment's default
+    pub polymarket_clob_url: String,
+    pub polymarket_gamma_url: String,
+    pub claude_api_url: String,
+    /// IANA timezone name used for displayed times, e.g. "America/Bogota"
+    pub display_timezone: String,
+    /// Monitor an existing account's positions without placing any trades
+    pub watch_only: bool,
+    /// Polymarket proxy wallet address whose positions are monitored
+    pub wallet_address: String,
+    pub polymarket_data_url: String,
+    /// How often to generate an AI analyst report on the portfolio, 0 to disable
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub commentary_interval_secs: u32,
+    pub risk: RiskConfig,
+    pub market_maker: MarketMakerConfig,
+    pub exits: ExitConfig,
+    /// Periodic re-analysis of held positions against their entry thesis
+    pub reunderwrite: ReunderwriteConfig,
+    pub neg_risk: NegRiskConfig,
+    pub scoring: ScoringConfig,
+    /// Which scanned markets are analyzed each cycle
+    pub sampling: SamplingConfig,
+    /// Upper bound on child orders when slicing a trade larger than top-of-book depth, 1 disables slicing
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub max_order_slices: u32,
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub slice_interval_secs: u32,
+    pub paper_fills: PaperFillConfig,
+    /// Minimum level shown in the UI feed
+    pub feed_log_level: LogLevel,
+    /// Minimum level kept in the activity log at all
+    pub persist_log_level: LogLevel,
+    /// Send the analysis instructions and input reference as a cacheable prefix to cut
+    /// repeated input costs. Off sends only the short base prompt, uncached.
+    pub prompt_caching: bool,
+    pub notifications: NotificationConfig,
+    pub reports: ReportConfig,
+    /// Currency of record and stablecoin depeg monitoring
+    pub currency: CurrencyConfig,
+    /// Pauses calls to Polymarket or Claude after repeated failures
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Production or testnet (staging CLOB + Polygon Amoy)
+    pub environment: Environment,
+    pub rpc_url: String,
+    /// Wallet JSON-RPC endpoint that signs `eth_sendTransaction` and CLOB orders (e.g. Frame);
+    /// when empty, on-chain actions such as token approvals are returned unsigned and live
+    /// sells cannot be placed
+    pub signer_rpc_url: String,
+    pub embedding_provider: EmbeddingProvider,
+    pub voyage_api_key: String,
+    /// Cosine similarity above which two markets are treated as near-duplicates
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub duplicate_similarity: f64,
+    /// Offer-side pressure (-1..1) at or above which buys are vetoed
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub max_offer_pressure: f64,
+    /// Entries are only taken at prices inside [min_entry_price, max_entry_price], whatever the
+    /// edge. Legs of neg-risk baskets are exempt.
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub min_entry_price: f64,
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub max_entry_price: f64,
+    /// Per-category limits on which outcome is bought and at what prices, on top of the band
+    pub side_guardrails: Vec<execution::SideGuardrail>,
+    /// Re-analyze scanned markets outside the live shortlist through the Batch API
+    pub batch_analysis: bool,
+    /// Age after which a cached prediction is queued for batch re-analysis
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub batch_max_age_hours: u32,
 }
 
 impl Default for BotConfig {
@@ -138,6 +540,45 @@ impl Default for BotConfig {
             scan_interval_secs: 60,
             auto_trading: false,
             survival_mode: true,
+            max_prompt_tokens: 1500,
+            proxy_url: String::new(),
+            polymarket_clob_url: String::new(),
+            polymarket_gamma_url: String::new(),
+            claude_api_url: CLAUDE_API_URL.to_string(),
+            display_timezone: "UTC".to_string(),
+            watch_only: false,
+            wallet_address: String::new(),
+            polymarket_data_url: String::new(),
+            commentary_interval_secs: 0,
+            risk: RiskConfig::default(),
+            market_maker: MarketMakerConfig::default(),
+            exits: ExitConfig::default(),
+            reunderwrite: ReunderwriteConfig::default(),
+            neg_risk: NegRiskConfig::default(),
+            scoring: ScoringConfig::default(),
+            sampling: SamplingConfig::default(),
+            max_order_slices: 5,
+            slice_interval_secs: 30,
+            paper_fills: PaperFillConfig::default(),
+            feed_log_level: LogLevel::Info,
+            persist_log_level: LogLevel::Debug,
+            prompt_caching: true,
+            notifications: NotificationConfig::default(),
+            reports: ReportConfig::default(),
+            currency: CurrencyConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            environment: Environment::Production,
+            rpc_url: String::new(),
+            signer_rpc_url: String::new(),
+            embedding_provider: EmbeddingProvider::Local,
+            voyage_api_key: String::new(),
+            duplicate_similarity: 0.92,
+            max_offer_pressure: 0.5,
+            min_entry_price: 0.08,
+            max_entry_price: 0.92,
+            side_guardrails: Vec::new(),
+            batch_analysis: false,
+            batch_max_age_hours: 24,
         }
     }
 }
@@ -154,6 +595,37 @@ pub struct AIPrediction {
     pub reasoning: String,
     pub recommended_size: f64,
     pub fair_price: f64,
+    /// Prompt pack the analysis ran with; `None` for the generic prompt
+    #[serde(default)]
+    pub prompt_pack: Option<String>,
+}
+
+/// Prompt size recorded per analyzed market, for cost analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptUsage {
+    pub market_id: String,
+    pub market_name: String,
+    pub estimated_tokens: u32,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    #[serde(default)]
+    pub cache_write_tokens: u32,
+    #[serde(default)]
+    pub cache_read_tokens: u32,
+    pub truncated: bool,
+    #[serde(deserialize_with = "time::deserialize")]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Periodic narrative report on the portfolio, separate from per-market predictions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioCommentary {
+    #[serde(deserialize_with = "time::deserialize")]
+    pub timestamp: DateTime<Utc>,
+    pub commentary: String,
+    pub positions_count: u32,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -167,7 +639,30 @@ pub struct ClaudeRequest {
     pub model: String,
     pub max_tokens: u32,
     pub messages: Vec<ClaudeMessage>,
-    pub system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<Vec<ClaudeSystemBlock>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeSystemBlock {
+    #[serde(rename = "type")]
+    pub block_type: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
+}
+
+/// Marks a prompt prefix as cacheable by Anthropic's prompt caching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheControl {
+    #[serde(rename = "type")]
+    pub cache_type: String,
+}
+
+impl CacheControl {
+    pub fn ephemeral() -> Self {
+        Self { cache_type: "ephemeral".to_string() }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -187,4 +682,8 @@ pub struct ClaudeContent {
 pub struct ClaudeUsage {
     pub input_tokens: u32,
     pub output_tokens: u32,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u32,
+    #[serde(default)]
+    pub cache_read_input_tokens: u32,
 }
@@ -0,0 +1,239 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::config;
+use super::models::*;
+
+/// keccak256("convertPositions(bytes32,uint256,uint256)")[..4]
+pub const CONVERT_POSITIONS_SELECTOR: &str = "c64748c4";
+
+/// Baskets across the mutually exclusive outcomes of a neg-risk event. Exactly one
+/// outcome resolves Yes, so a full set of Yes shares pays 1 and a full set of No shares
+/// pays N - 1; the NegRiskAdapter converts the No set into that collateral right away.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NegRiskConfig {
+    pub enabled: bool,
+    /// Minimum profit per set at the best asks before a basket is bought
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub min_edge: f64,
+    /// Max USDC committed to one basket
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub max_stake: f64,
+    /// Events priced per cycle; each costs two book fetches per outcome
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub max_events: u32,
+}
+
+impl Default for NegRiskConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_edge: 0.01,
+            max_stake: 50.0,
+            max_events: 5,
+        }
+    }
+}
+
+/// Every outcome market of one neg-risk event
+#[derive(Debug, Clone)]
+pub struct NegRiskEvent {
+    pub event_id: String,
+    pub title: String,
+    pub neg_risk_market_id: String,
+    pub markets: Vec<Market>,
+}
+
+impl NegRiskEvent {
+    /// `None` unless the markets are at least two outcomes of the same neg-risk market
+    pub fn new(event_id: &str, title: String, markets: Vec<Market>) -> Option<Self> {
+        let neg_risk_market_id = markets.first()?.neg_risk_market_id.clone()?;
+        let consistent = markets.iter()
+            .all(|m| m.neg_risk && m.neg_risk_market_id.as_deref() == Some(neg_risk_market_id.as_str()));
+        (consistent && markets.len() >= 2).then(|| Self {
+            event_id: event_id.to_string(),
+            title,
+            neg_risk_market_id,
+            markets,
+        })
+    }
+}
+
+/// Distinct neg-risk event ids among the scanned markets, in scan order
+pub fn scanned_events(markets: &[Market]) -> Vec<String> {
+    let mut events: Vec<String> = Vec::new();
+    for id in markets.iter().filter(|m| m.neg_risk).filter_map(|m| m.event_id.as_ref()) {
+        if !events.contains(id) {
+            events.push(id.clone());
+        }
+    }
+    events
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BasketKind {
+    /// Buy Yes on every outcome and hold: one leg pays 1 at resolution
+    YesSet,
+    /// Buy No on every outcome and convert the set into N - 1 collateral at once
+    NoConversion,
+}
+
+impl BasketKind {
+    pub fn outcome(&self) -> &'static str {
+        match self {
+            BasketKind::YesSet => "Yes",
+            BasketKind::NoConversion => "No",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BasketLeg {
+    pub market_id: String,
+    pub question: String,
+    pub token_id: Option<String>,
+    /// Position of the outcome inside the neg-risk market, from the question id
+    pub question_index: Option<u8>,
+    /// Best ask
+    pub price: f64,
+    /// Shares offered at the best ask
+    pub available: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegRiskOpportunity {
+    pub event_id: String,
+    pub title: String,
+    pub neg_risk_market_id: String,
+    pub kind: BasketKind,
+    pub legs: Vec<BasketLeg>,
+    pub cost_per_set: f64,
+    pub payout_per_set: f64,
+    pub edge_per_set: f64,
+    /// Sets bought, limited by the thinnest leg and `max_stake`
+    pub sets: f64,
+    pub detected_at: DateTime<Utc>,
+    /// Adapter call for an external signer, set once a No set has been bought
+    pub conversion: Option<ConversionTx>,
+}
+
+impl NegRiskOpportunity {
+    pub fn cost(&self) -> f64 {
+        self.cost_per_set * self.sets
+    }
+
+    pub fn profit(&self) -> f64 {
+        self.edge_per_set * self.sets
+    }
+}
+
+/// Outcome position encoded in the last byte of a neg-risk question id
+pub fn question_index(question_id: &str) -> Option<u8> {
+    let hex = question_id.trim_start_matches("0x");
+    u8::from_str_radix(hex.get(hex.len().checked_sub(2)?..)?, 16).ok()
+}
+
+/// Bitmask of the legs' question indices, as `convertPositions` expects
+pub fn index_set(legs: &[BasketLeg]) -> Option<u128> {
+    legs.iter().try_fold(0u128, |set, leg| Some(set | 1u128.checked_shl(leg.question_index? as u32)?))
+}
+
+/// Price one basket from the best ask of each outcome's token (`asks` follows
+/// `event.markets`). `None` when a leg has no ask or the edge is below `min_edge`.
+pub fn price_basket(
+    event: &NegRiskEvent,
+    kind: BasketKind,
+    asks: &[Option<BookLevel>],
+    config: &NegRiskConfig,
+    now: DateTime<Utc>,
+) -> Option<NegRiskOpportunity> {
+    let mut legs = Vec::with_capacity(event.markets.len());
+    for (market, ask) in event.markets.iter().zip(asks) {
+        let ask = ask.as_ref().filter(|a| a.price > 0.0 && a.size > 0.0)?;
+        let token_index = market.outcomes.iter().position(|o| o.eq_ignore_ascii_case(kind.outcome()));
+        legs.push(BasketLeg {
+            market_id: market.id.clone(),
+            question: market.question.clone(),
+            token_id: token_index.and_then(|i| market.token_ids.get(i).cloned()),
+            question_index: market.question_id.as_deref().and_then(question_index),
+            price: ask.price,
+            available: ask.size,
+        });
+    }
+    if legs.len() != event.markets.len() {
+        return None;
+    }
+
+    let cost_per_set: f64 = legs.iter().map(|l| l.price).sum();
+    let payout_per_set = match kind {
+        BasketKind::YesSet => 1.0,
+        BasketKind::NoConversion => (legs.len() - 1) as f64,
+    };
+    let edge_per_set = payout_per_set - cost_per_set;
+    if edge_per_set < config.min_edge {
+        return None;
+    }
+
+    let thinnest = legs.iter().map(|l| l.available).fold(f64::INFINITY, f64::min);
+    let sets = thinnest.min(config.max_stake / cost_per_set).floor();
+    let min_shares = event.markets.iter().map(|m| m.min_order_size).fold(0.0, f64::max);
+    if sets < min_shares.max(1.0) {
+        return None;
+    }
+
+    Some(NegRiskOpportunity {
+        event_id: event.event_id.clone(),
+        title: event.title.clone(),
+        neg_risk_market_id: event.neg_risk_market_id.clone(),
+        kind,
+        legs,
+        cost_per_set,
+        payout_per_set,
+        edge_per_set,
+        sets,
+        detected_at: now,
+        conversion: None,
+    })
+}
+
+/// The more profitable of the Yes and No baskets, if either clears `min_edge`
+pub fn find_opportunity(
+    event: &NegRiskEvent,
+    yes_asks: &[Option<BookLevel>],
+    no_asks: &[Option<BookLevel>],
+    config: &NegRiskConfig,
+    now: DateTime<Utc>,
+) -> Option<NegRiskOpportunity> {
+    let yes = price_basket(event, BasketKind::YesSet, yes_asks, config, now);
+    let no = price_basket(event, BasketKind::NoConversion, no_asks, config, now);
+    match (yes, no) {
+        (Some(yes), Some(no)) => Some(if no.profit() > yes.profit() { no } else { yes }),
+        (yes, no) => yes.or(no),
+    }
+}
+
+/// Unsigned `convertPositions` call on the NegRiskAdapter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionTx {
+    pub to: String,
+    pub chain_id: u64,
+    /// Hex calldata, 0x-prefixed
+    pub data: String,
+}
+
+/// ABI-encode `convertPositions(marketId, indexSet, amount)`; `amount` is in shares,
+/// which use the collateral's six decimals
+pub fn encode_convert_positions(neg_risk_market_id: &str, index_set: u128, amount: f64) -> Option<String> {
+    let market_id = neg_risk_market_id.trim_start_matches("0x");
+    if market_id.len() != 64 || !market_id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let units = (amount * 1_000_000.0).round() as u128;
+    Some(format!(
+        "0x{}{}{:064x}{:064x}",
+        CONVERT_POSITIONS_SELECTOR,
+        market_id.to_lowercase(),
+        index_set,
+        units
+    ))
+}
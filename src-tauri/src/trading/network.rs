@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use super::http;
+use super::models::BotConfig;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Environment {
+    #[default]
+    Production,
+    /// Polymarket staging CLOB settled on the Polygon Amoy testnet
+    Testnet,
+}
+
+/// Endpoints and on-chain addresses for one environment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Network {
+    pub environment: Environment,
+    pub clob_url: String,
+    pub gamma_url: String,
+    pub data_url: String,
+    pub rpc_url: String,
+    pub chain_id: u64,
+    pub exchange_address: String,
+    pub neg_risk_exchange_address: String,
+    /// Converts No positions across the outcomes of a neg-risk market
+    pub neg_risk_adapter: String,
+    pub collateral_token: String,
+    pub conditional_tokens: String,
+}
+
+impl Environment {
+    pub fn network(&self) -> Network {
+        match self {
+            Environment::Production => Network {
+                environment: *self,
+                clob_url: "https://clob.polymarket.com".to_string(),
+                gamma_url: "https://gamma-api.polymarket.com".to_string(),
+                data_url: "https://data-api.polymarket.com".to_string(),
+                rpc_url: "https://polygon-rpc.com".to_string(),
+                chain_id: 137,
+                exchange_address: "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E".to_string(),
+                neg_risk_exchange_address: "0xC5d563A36AE78145C45a50134d48A1215220f80a".to_string(),
+                neg_risk_adapter: "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296".to_string(),
+                collateral_token: "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174".to_string(),
+                conditional_tokens: "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045".to_string(),
+            },
+            // Market metadata is only published by the production Gamma/data APIs,
+            // so the testnet shares them while orders and settlement go to staging/Amoy
+            Environment::Testnet => Network {
+                environment: *self,
+                clob_url: "https://clob-staging.polymarket.com".to_string(),
+                gamma_url: "https://gamma-api.polymarket.com".to_string(),
+                data_url: "https://data-api.polymarket.com".to_string(),
+                rpc_url: "https://rpc-amoy.polygon.technology".to_string(),
+                chain_id: 80002,
+                exchange_address: "0xdFE02Eb6733538f8Ea35D585af8DE5958AD99E40".to_string(),
+                neg_risk_exchange_address: "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296".to_string(),
+                neg_risk_adapter: "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296".to_string(),
+                collateral_token: "0x9c4e1703476e875070ee25b56a58b008cfb8fa78".to_string(),
+                conditional_tokens: "0x69308FB512518e39F9b16112fA8d994F4e2Bf8bB".to_string(),
+            },
+        }
+    }
+}
+
+/// The configured environment's network with any user endpoint overrides applied
+pub fn resolve(config: &BotConfig) -> Network {
+    let mut network = config.environment.network();
+    network.clob_url = http::base_url(&config.polymarket_clob_url, &network.clob_url);
+    network.gamma_url = http::base_url(&config.polymarket_gamma_url, &network.gamma_url);
+    network.data_url = http::base_url(&config.polymarket_data_url, &network.data_url);
+    network.rpc_url = http::base_url(&config.rpc_url, &network.rpc_url);
+    network
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NotificationKind {
+    OrderPlaced,
+    Resolution,
+    RiskHalt,
+    ApiFailure,
+    PriceAlert,
+    Report,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Opt-in switches per notification type; everything is off by default
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    pub order_placed: bool,
+    pub resolutions: bool,
+    /// Only notify resolutions whose absolute PnL is at least this many dollars
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub resolution_pnl_threshold: f64,
+    pub risk_halts: bool,
+    pub api_failures: bool,
+    pub price_alerts: bool,
+    /// Send a summary when a daily or weekly report is written
+    pub reports: bool,
+}
+
+impl NotificationConfig {
+    fn allows(&self, kind: NotificationKind) -> bool {
+        match kind {
+            NotificationKind::OrderPlaced => self.order_placed,
+            NotificationKind::Resolution => self.resolutions,
+            NotificationKind::RiskHalt => self.risk_halts,
+            NotificationKind::ApiFailure => self.api_failures,
+            NotificationKind::PriceAlert => self.price_alerts,
+            NotificationKind::Report => self.reports,
+        }
+    }
+}
+
+/// A delivery channel for notifications (desktop, webhook, ...)
+pub trait Notifier: Send + Sync {
+    fn notify(&self, notification: &Notification);
+}
+
+/// Filters events against the user's opt-ins and fans them out to every registered sink
+#[derive(Default)]
+pub struct NotificationManager {
+    config: NotificationConfig,
+    sinks: Vec<Box<dyn Notifier>>,
+}
+
+impl NotificationManager {
+    pub fn set_config(&mut self, config: NotificationConfig) {
+        self.config = config;
+    }
+
+    pub fn register(&mut self, sink: Box<dyn Notifier>) {
+        self.sinks.push(sink);
+    }
+
+    pub fn notify(&self, kind: NotificationKind, title: &str, body: &str) {
+        if !self.config.allows(kind) || self.sinks.is_empty() {
+            return;
+        }
+
+        let notification = Notification {
+            kind,
+            title: title.to_string(),
+            body: body.to_string(),
+            timestamp: Utc::now(),
+        };
+        for sink in &self.sinks {
+            sink.notify(&notification);
+        }
+    }
+
+    /// Notify a resolution only when its PnL clears the configured threshold
+    pub fn notify_resolution(&self, pnl: f64, body: &str) {
+        if pnl.abs() >= self.config.resolution_pnl_threshold {
+            let title = if pnl >= 0.0 { "Position won" } else { "Position lost" };
+            self.notify(NotificationKind::Resolution, title, body);
+        }
+    }
+}
@@ -1,145 +1,176 @@
-use anyhow::Result;
-use reqwest::Client;
+use anyhow::{Context, Result};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, RequestBuilder};
 use serde_json::Value;
+use sha2::Sha256;
 use super::models::*;
 
-const POLYMARKET_API_BASE: &str = "https://clob.polymarket.com";
-const POLYMARKET_GAMMA_BASE: &str = "https://gamma-api.polymarket.com";
+use super::gamma::{self, ParseStats};
+use super::negrisk::{self, ConversionTx};
+use super::http;
+use super::network::{self, Network};
+
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// A CLOB order waiting for the wallet's EIP-712 signature
+#[derive(Debug, Clone)]
+pub struct UnsignedOrder {
+    pub side: &'static str,
+    /// The `Order` message as signed
+    pub order: Value,
+    /// `eth_signTypedData_v4` payload
+    pub typed_data: Value,
+}
 
 pub struct PolymarketClient {
     client: Client,
+    clob_base: String,
+    gamma_base: String,
+    data_base: String,
+    network: Network,
+    /// Wallet the API key was derived for, sent as `POLY_ADDRESS`
+    address: String,
     api_key: String,
     secret: String,
     passphrase: String,
 }
 
 impl PolymarketClient {
-    pub fn new(api_key: &str, secret: &str, passphrase: &str) -> Self {
-        Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .expect("Failed to build HTTP client"),
-            api_key: api_key.to_string(),
-            secret: secret.to_string(),
-            passphrase: passphrase.to_string(),
-        }
+    pub fn new(config: &BotConfig) -> Result<Self> {
+        let network = network::resolve(config);
+        Ok(Self {
+            client: http::build_client(30, &config.proxy_url)?,
+            clob_base: network.clob_url.clone(),
+            gamma_base: network.gamma_url.clone(),
+            data_base: network.data_url.clone(),
+            network,
+            address: config.wallet_address.clone(),
+            api_key: config.polymarket_api_key.clone(),
+            secret: config.polymarket_secret.clone(),
+            passphrase: config.polymarket_passphrase.clone(),
+        })
     }
 
     /// Fetch active markets from Polymarket
     pub async fn get_markets(&self, limit: u32, offset: u32) -> Result<Vec<Market>> {
+        Ok(self.get_markets_with_stats(limit, offset).await?.0)
+    }
+
+    /// Fetch active markets along with how fast the listing parsed
+    pub async fn get_markets_with_stats(&self, limit: u32, offset: u32) -> Result<(Vec<Market>, ParseStats)> {
+        let body = self.get_markets_body(limit, offset).await?;
+        gamma::parse_markets(&body)
+    }
+
+    /// Raw `/markets` listing, e.g. to benchmark parsing
+    pub async fn get_markets_body(&self, limit: u32, offset: u32) -> Result<Vec<u8>> {
         let url = format!(
             "{}/markets?limit={}&offset={}&active=true&closed=false",
-            POLYMARKET_GAMMA_BASE, limit, offset
+            self.gamma_base, limit, offset
         );
 
         let resp = self.client.get(&url).send().await?;
-        let body: Value = resp.json().await?;
-
-        let markets = if let Some(arr) = body.as_array() {
-            arr.iter()
-                .filter_map(|m| {
-                    let question = m.get("question")?.as_str()?.to_string();
-                    let id = m.get("condition_id").or(m.get("id"))?.as_str()?.to_string();
-
-                    let outcomes: Vec<String> = m.get("outcomes")
-                        .and_then(|o| serde_json::from_value(o.clone()).ok())
-                        .unwrap_or_else(|| vec!["Yes".to_string(), "No".to_string()]);
-
-                    let outcome_prices: Vec<f64> = m.get("outcomePrices")
-                        .and_then(|p| {
-                            if let Some(arr) = p.as_array() {
-                                Some(arr.iter().filter_map(|v| {
-                                    v.as_str().and_then(|s| s.parse::<f64>().ok())
-                                        .or_else(|| v.as_f64())
-                                }).collect())
-                            } else {
-                                None
-                            }
-                        })
-                        .unwrap_or_else(|| vec![0.5, 0.5]);
-
-                    let volume = m.get("volume")
-                        .and_then(|v| v.as_str().and_then(|s| s.parse::<f64>().ok()).or(v.as_f64()))
-                        .unwrap_or(0.0);
-
-                    let liquidity = m.get("liquidity")
-                        .and_then(|v| v.as_str().and_then(|s| s.parse::<f64>().ok()).or(v.as_f64()))
-                        .unwrap_or(0.0);
-
-                    Some(Market {
-                        id,
-                        question,
-                        slug: m.get("slug").and_then(|s| s.as_str()).unwrap_or("").to_string(),
-                        outcomes,
-                        outcome_prices,
-                        volume,
-                        liquidity,
-                        end_date: m.get("endDate").and_then(|d| d.as_str()).map(|s| s.to_string()),
-                        active: true,
-                    })
-                })
-                .collect()
-        } else {
-            vec![]
-        };
-
-        Ok(markets)
+        let resp = http::check(resp).await?;
+        Ok(resp.bytes().await?.to_vec())
     }
 
     /// Get specific market details
     pub async fn get_market(&self, condition_id: &str) -> Result<Option<Market>> {
-        let url = format!("{}/markets/{}", POLYMARKET_GAMMA_BASE, condition_id);
+        let url = format!("{}/markets/{}", self.gamma_base, condition_id);
         let resp = self.client.get(&url).send().await?;
 
         if !resp.status().is_success() {
             return Ok(None);
         }
 
-        let m: Value = resp.json().await?;
+        let body = resp.bytes().await?;
+        Ok(Some(gamma::parse_market(&body, condition_id)?))
+    }
 
-        let question = m.get("question").and_then(|q| q.as_str()).unwrap_or("Unknown").to_string();
-        let id = condition_id.to_string();
+    /// Title and open markets of a Gamma event, e.g. every outcome of a neg-risk event
+    pub async fn get_event_markets(&self, event_id: &str) -> Result<(String, Vec<Market>)> {
+        let url = format!("{}/events/{}", self.gamma_base, event_id);
+        let resp = self.client.get(&url).send().await?;
+        let resp = http::check(resp).await?;
+        let body = resp.bytes().await?;
+        gamma::parse_event(&body, event_id)
+    }
 
-        let outcomes: Vec<String> = m.get("outcomes")
-            .and_then(|o| serde_json::from_value(o.clone()).ok())
-            .unwrap_or_else(|| vec!["Yes".to_string(), "No".to_string()]);
+    /// Unsigned NegRiskAdapter call converting `amount` No shares of each question in
+    /// `index_set` into (k - 1) * amount collateral plus Yes on the questions left out
+    pub fn conversion_tx(&self, neg_risk_market_id: &str, index_set: u128, amount: f64) -> Result<ConversionTx> {
+        let data = negrisk::encode_convert_positions(neg_risk_market_id, index_set, amount)
+            .with_context(|| format!("Invalid neg-risk market id {}", neg_risk_market_id))?;
+        Ok(ConversionTx {
+            to: self.network.neg_risk_adapter.clone(),
+            chain_id: self.network.chain_id,
+            data,
+        })
+    }
+
+    /// Get the authoritative resolution state of a market
+    pub async fn get_market_resolution(&self, condition_id: &str) -> Result<Option<MarketResolution>> {
+        Ok(self.get_market(condition_id).await?.and_then(|m| m.resolution))
+    }
+
+    /// Hourly price history of a token over the last week
+    pub async fn get_price_history(&self, token_id: &str) -> Result<Vec<PricePoint>> {
+        let url = format!(
+            "{}/prices-history?market={}&interval=1w&fidelity=60",
+            self.clob_base, token_id
+        );
+        let resp = self.client.get(&url).send().await?;
+        let resp = http::check(resp).await?;
+        let body: Value = resp.json().await?;
 
-        let outcome_prices: Vec<f64> = m.get("outcomePrices")
-            .and_then(|p| {
-                if let Some(arr) = p.as_array() {
-                    Some(arr.iter().filter_map(|v| {
-                        v.as_str().and_then(|s| s.parse::<f64>().ok()).or_else(|| v.as_f64())
-                    }).collect())
-                } else {
-                    None
-                }
+        let points = body.get("history")
+            .and_then(|h| h.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|p| {
+                        let t = p.get("t")?.as_i64()?;
+                        Some(PricePoint {
+                            timestamp: chrono::DateTime::from_timestamp(t, 0)?,
+                            price: number_field(p, "p"),
+                        })
+                    })
+                    .collect()
             })
-            .unwrap_or_else(|| vec![0.5, 0.5]);
+            .unwrap_or_default();
 
-        Ok(Some(Market {
-            id,
-            question,
-            slug: m.get("slug").and_then(|s| s.as_str()).unwrap_or("").to_string(),
-            outcomes,
-            outcome_prices,
-            volume: m.get("volume").and_then(|v| v.as_f64()).unwrap_or(0.0),
-            liquidity: m.get("liquidity").and_then(|v| v.as_f64()).unwrap_or(0.0),
-            end_date: m.get("endDate").and_then(|d| d.as_str()).map(|s| s.to_string()),
-            active: true,
-        }))
+        Ok(points)
     }
 
     /// Get orderbook for a token
-    pub async fn get_orderbook(&self, token_id: &str) -> Result<Value> {
-        let url = format!("{}/book?token_id={}", POLYMARKET_API_BASE, token_id);
+    pub async fn get_orderbook(&self, token_id: &str) -> Result<OrderBook> {
+        let url = format!("{}/book?token_id={}", self.clob_base, token_id);
         let resp = self.client
             .get(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .send()
             .await?;
+        let resp = http::check(resp).await?;
         let body: Value = resp.json().await?;
-        Ok(body)
+
+        let levels = |key: &str| -> Vec<BookLevel> {
+            body.get(key)
+                .and_then(|l| l.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .map(|l| BookLevel {
+                            price: number_field(l, "price"),
+                            size: number_field(l, "size"),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        Ok(OrderBook {
+            bids: levels("bids"),
+            asks: levels("asks"),
+        })
     }
 
     /// Place an order on Polymarket CLOB
@@ -160,7 +191,7 @@ impl PolymarketClient {
             "expiration": 0,
         });
 
-        let url = format!("{}/order", POLYMARKET_API_BASE);
+        let url = format!("{}/order", self.clob_base);
         let resp = self.client
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
@@ -168,41 +199,325 @@ impl PolymarketClient {
             .json(&order_payload)
             .send()
             .await?;
+        let resp = http::check(resp).await?;
 
         let body: Value = resp.json().await?;
         Ok(body)
     }
 
-    /// Get current positions
-    pub async fn get_positions(&self) -> Result<Value> {
-        let url = format!("{}/positions", POLYMARKET_API_BASE);
-        let resp = self.client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .send()
-            .await?;
+    /// Build a GTC limit order from the configured wallet for it to sign, with signature
+    /// type 0 (the wallet signs for itself). Shares are rounded down to cents, and both
+    /// amounts are in 6-decimal base units.
+    pub fn build_order(&self, token_id: &str, side: &OrderSide, price: f64, shares: f64, neg_risk: bool) -> UnsignedOrder {
+        let cents = (shares * 100.0).floor();
+        let share_units = (cents * 10_000.0) as u128;
+        let value_units = ((cents / 100.0 * price * 10_000.0).floor() * 100.0) as u128;
+        let (maker_amount, taker_amount, side_index, side_name) = match side {
+            OrderSide::Buy => (value_units, share_units, 0, "BUY"),
+            OrderSide::Sell => (share_units, value_units, 1, "SELL"),
+        };
+        let exchange = if neg_risk { &self.network.neg_risk_exchange_address } else { &self.network.exchange_address };
+        let order = serde_json::json!({
+            "salt": rand::random::<u32>(),
+            "maker": self.address,
+            "signer": self.address,
+            "taker": ZERO_ADDRESS,
+            "tokenId": token_id,
+            "makerAmount": maker_amount.to_string(),
+            "takerAmount": taker_amount.to_string(),
+            "expiration": "0",
+            "nonce": "0",
+            "feeRateBps": "0",
+            "side": side_index,
+            "signatureType": 0,
+        });
+        let field = |name: &str, kind: &str| serde_json::json!({ "name": name, "type": kind });
+        let typed_data = serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    field("name", "string"),
+                    field("version", "string"),
+                    field("chainId", "uint256"),
+                    field("verifyingContract", "address"),
+                ],
+                "Order": [
+                    field("salt", "uint256"),
+                    field("maker", "address"),
+                    field("signer", "address"),
+                    field("taker", "address"),
+                    field("tokenId", "uint256"),
+                    field("makerAmount", "uint256"),
+                    field("takerAmount", "uint256"),
+                    field("expiration", "uint256"),
+                    field("nonce", "uint256"),
+                    field("feeRateBps", "uint256"),
+                    field("side", "uint8"),
+                    field("signatureType", "uint8"),
+                ],
+            },
+            "primaryType": "Order",
+            "domain": {
+                "name": "Polymarket CTF Exchange",
+                "version": "1",
+                "chainId": self.network.chain_id,
+                "verifyingContract": exchange,
+            },
+            "message": order,
+        });
+        UnsignedOrder { side: side_name, order, typed_data }
+    }
+
+    /// Post an order signed by the wallet; returns the CLOB's order id
+    pub async fn post_order(&self, unsigned: &UnsignedOrder, signature: &str) -> Result<String> {
+        let mut order = unsigned.order.clone();
+        order["side"] = Value::from(unsigned.side);
+        order["signature"] = Value::from(signature);
+        let body = serde_json::json!({ "order": order, "owner": self.api_key, "orderType": "GTC" }).to_string();
+        let path = "/order";
+        let request = self.client
+            .post(format!("{}{}", self.clob_base, path))
+            .header("Content-Type", "application/json")
+            .body(body.clone());
+        let resp = self.signed(request, "POST", path, &body)?.send().await?;
+        let placed: Value = http::check(resp).await?.json().await?;
+        if placed.get("success").and_then(|s| s.as_bool()) == Some(false) {
+            let error = placed.get("errorMsg").and_then(|e| e.as_str()).unwrap_or("no reason given");
+            anyhow::bail!("Order rejected: {}", error);
+        }
+        placed.get("orderID")
+            .and_then(|id| id.as_str())
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_string())
+            .context("Order response missing orderID")
+    }
+
+    /// Cancel a resting order, failing unless the CLOB confirms the cancellation
+    pub async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        let body = serde_json::json!({ "orderID": order_id }).to_string();
+        let request = self.client
+            .delete(format!("{}/order", self.clob_base))
+            .header("Content-Type", "application/json")
+            .body(body.clone());
+        let resp = self.signed(request, "DELETE", "/order", &body)?.send().await?;
+        let resp = http::check(resp).await?;
         let body: Value = resp.json().await?;
-        Ok(body)
+
+        let canceled = body.get("canceled")
+            .and_then(|c| c.as_array())
+            .is_some_and(|ids| ids.iter().any(|id| id.as_str() == Some(order_id)));
+        if !canceled {
+            let reason = body.get("not_canceled")
+                .and_then(|n| n.get(order_id))
+                .and_then(|r| r.as_str())
+                .unwrap_or("not confirmed");
+            anyhow::bail!("Cancel of {} rejected: {}", order_id, reason);
+        }
+        Ok(())
     }
 
-    /// Get balance info
-    pub async fn get_balance(&self) -> Result<f64> {
-        let url = format!("{}/balance", POLYMARKET_API_BASE);
-        let resp = self.client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .send()
-            .await?;
+    /// Get current positions held by a wallet
+    pub async fn get_positions(&self, user: &str) -> Result<Vec<Position>> {
+        let url = format!("{}/positions?user={}&sizeThreshold=0", self.data_base, user);
+        let resp = self.client.get(&url).send().await?;
+        let resp = http::check(resp).await?;
+        let body: Value = resp.json().await?;
+
+        let positions = body.as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|p| {
+                        Some(Position {
+                            market_id: p.get("conditionId")?.as_str()?.to_string(),
+                            asset_id: p.get("asset").and_then(|a| a.as_str()).unwrap_or("").to_string(),
+                            market_name: p.get("title").and_then(|t| t.as_str()).unwrap_or("Unknown").to_string(),
+                            outcome: p.get("outcome").and_then(|o| o.as_str()).unwrap_or("").to_string(),
+                            size: number_field(p, "size"),
+                            avg_price: number_field(p, "avgPrice"),
+                            current_price: number_field(p, "curPrice"),
+                            initial_value: number_field(p, "initialValue"),
+                            current_value: number_field(p, "currentValue"),
+                            pnl: number_field(p, "cashPnl"),
+                            pnl_pct: number_field(p, "percentPnl"),
+                            redeemable: p.get("redeemable").and_then(|r| r.as_bool()).unwrap_or(false),
+                            end_date: p.get("endDate").and_then(|d| d.as_str()).map(|s| s.to_string()),
+                            neg_risk: p.get("negativeRisk").and_then(|r| r.as_bool()).unwrap_or(false),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(positions)
+    }
 
+    /// Get a page of historical trades for a wallet, newest first
+    pub async fn get_trades(&self, user: &str, limit: u32, offset: u32) -> Result<Vec<HistoricalTrade>> {
+        let url = format!(
+            "{}/trades?user={}&limit={}&offset={}",
+            self.data_base, user, limit, offset
+        );
+        let resp = self.client.get(&url).send().await?;
+        let resp = http::check(resp).await?;
         let body: Value = resp.json().await?;
-        let balance = body.get("balance")
-            .and_then(|b| b.as_f64())
-            .unwrap_or(0.0);
 
-        Ok(balance)
+        let trades = body.as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|t| {
+                        let tx_hash = t.get("transactionHash")?.as_str()?;
+                        let asset = t.get("asset").and_then(|a| a.as_str()).unwrap_or("");
+                        let side = match t.get("side").and_then(|s| s.as_str()) {
+                            Some("SELL") => OrderSide::Sell,
+                            _ => OrderSide::Buy,
+                        };
+                        let timestamp = t.get("timestamp")
+                            .and_then(|ts| ts.as_i64())
+                            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                            .unwrap_or_else(chrono::Utc::now);
+
+                        Some(HistoricalTrade {
+                            external_id: format!("{}:{}:{:?}", tx_hash, asset, side),
+                            market_id: t.get("conditionId")?.as_str()?.to_string(),
+                            market_name: t.get("title").and_then(|n| n.as_str()).unwrap_or("Unknown").to_string(),
+                            side,
+                            outcome: t.get("outcome").and_then(|o| o.as_str()).unwrap_or("").to_string(),
+                            price: number_field(t, "price"),
+                            size: number_field(t, "size"),
+                            timestamp,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(trades)
     }
 
+    /// Most recent public trades in a market across both outcomes, newest first
+    pub async fn get_market_trades(&self, condition_id: &str, limit: u32) -> Result<Vec<MarketTrade>> {
+        let url = format!("{}/trades?market={}&limit={}", self.data_base, condition_id, limit);
+        let resp = self.client.get(&url).send().await?;
+        let resp = http::check(resp).await?;
+        let body: Value = resp.json().await?;
+
+        let trades = body.as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|t| {
+                        Some(MarketTrade {
+                            asset: t.get("asset")?.as_str()?.to_string(),
+                            side: match t.get("side").and_then(|s| s.as_str()) {
+                                Some("SELL") => OrderSide::Sell,
+                                _ => OrderSide::Buy,
+                            },
+                            price: number_field(t, "price"),
+                            size: number_field(t, "size"),
+                            timestamp: t.get("timestamp")
+                                .and_then(|ts| ts.as_i64())
+                                .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                                .unwrap_or_else(chrono::Utc::now),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(trades)
+    }
+
+    /// CLOB server time, for clock skew checks
+    pub async fn server_time(&self) -> Result<chrono::DateTime<chrono::Utc>> {
+        let resp = self.client.get(format!("{}/time", self.clob_base)).send().await?;
+        let body = http::check(resp).await?.text().await?;
+        body.trim()
+            .parse::<i64>()
+            .ok()
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .with_context(|| format!("Unexpected server time {:?}", body))
+    }
+
+    /// Validate the API credentials with an authenticated request that changes nothing:
+    /// listing the API keys of the account
+    pub async fn check_credentials(&self) -> Result<()> {
+        let path = "/auth/api-keys";
+        let request = self.client.get(format!("{}{}", self.clob_base, path));
+        let resp = self.signed(request, "GET", path, "")?.send().await?;
+        http::check(resp).await?;
+        Ok(())
+    }
+
+    /// Add the CLOB's L2 headers: the signature is the HMAC-SHA256 of timestamp, method,
+    /// path and body, keyed by the base64 API secret and sent url-safe base64 encoded
+    fn signed(&self, request: RequestBuilder, method: &str, path: &str, body: &str) -> Result<RequestBuilder> {
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        let secret = base64_decode(&self.secret).context("API secret is not valid base64")?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(&secret).context("Invalid API secret")?;
+        mac.update(format!("{}{}{}{}", timestamp, method, path, body).as_bytes());
+        let signature = base64_url_encode(&mac.finalize().into_bytes());
+        Ok(request
+            .header("POLY_ADDRESS", &self.address)
+            .header("POLY_API_KEY", &self.api_key)
+            .header("POLY_PASSPHRASE", &self.passphrase)
+            .header("POLY_SIGNATURE", signature)
+            .header("POLY_TIMESTAMP", timestamp))
+    }
+
+    /// Environment, endpoints and contract addresses this client talks to
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    /// Everything the L2 headers need is entered
     pub fn is_configured(&self) -> bool {
-        !self.api_key.is_empty() && !self.secret.is_empty()
+        ![&self.address, &self.api_key, &self.secret, &self.passphrase].iter().any(|v| v.is_empty())
+    }
+}
+
+/// Read a numeric field that the API may encode either as a number or a string
+fn number_field(value: &Value, key: &str) -> f64 {
+    value.get(key)
+        .and_then(|v| v.as_str().and_then(|s| s.parse::<f64>().ok()).or(v.as_f64()))
+        .unwrap_or(0.0)
+}
+
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Padded url-safe base64
+fn base64_url_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_URL[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode standard or url-safe base64, padded or not
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in text.trim().trim_end_matches('=').bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        acc = acc << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
     }
+    Some(out)
 }
@@ -0,0 +1,287 @@
+use anyhow::{bail, Result};
+use super::features::MarketFeatures;
+use super::models::*;
+use super::prompt_packs::PromptPacks;
+
+/// Rough characters-per-token ratio for English prose sent to Claude
+const CHARS_PER_TOKEN: f64 = 4.0;
+const MAX_QUESTION_CHARS: usize = 300;
+const MAX_OUTCOMES: usize = 8;
+const ELLIPSIS: &str = "…";
+
+pub const SYSTEM_PROMPT: &str = r#"You are an expert prediction market analyst and quantitative trader.
+Your task is to analyze prediction markets and determine:
+1. The TRUE probability of each outcome based on available information
+2. Whether there is an EDGE (difference between fair price and market price)
+3. Your confidence level in the prediction
+4. Recommended position size based on Kelly Criterion
+
+Respond in strict JSON format:
+{
+    "predicted_outcome": "Yes" or "No",
+    "fair_price": 0.XX,
+    "confidence": 0.XX,
+    "edge": 0.XX,
+    "reasoning": "Brief explanation",
+    "recommended_size_pct": 0.XX
+}
+
+Only recommend trades where edge > 0.05 (5%). Be conservative with sizing.
+Consider base rates, current events, and market efficiency."#;
+
+/// Static reference sent after `SYSTEM_PROMPT` when prompt caching is on. Together they
+/// make the cacheable prefix, so this must keep the pair above the minimum cacheable length.
+pub const ANALYSIS_REFERENCE: &str = r#"## Market description
+
+Each request describes one market in these lines:
+- "Market": the question being traded, possibly shortened in the middle with "…" to fit the prompt budget. The start and end of the question are always kept, so read both before deciding what the market resolves on.
+- "Outcomes": the tradable outcomes, comma separated. Binary markets list "Yes" and "No". Markets with many outcomes list the highest-priced ones; "(+N more)" marks outcomes left out for space.
+- "Current Prices": the last price of each listed outcome, in the same order, between 0 and 1. A price is the market's implied probability of that outcome, and also what one share of it costs. A winning share pays 1, a losing share pays 0. "…" stands for the prices of omitted outcomes.
+- "Volume": dollars traded over the market's life. Thin volume means the price may be stale or easy to move.
+- "Liquidity": dollars resting on the order book. Low liquidity means a trade may fill well away from the quoted price.
+- "End Date": when the market is scheduled to close, or "Not set". Markets can resolve earlier if the event is decided early.
+
+An optional "Features" line adds signals computed from the price history, separated by "|":
+- "7d change": price change of the first outcome over the last seven days.
+- "daily vol": standard deviation of daily price changes, a measure of how noisy the price is.
+- "drift": average price change per day fitted over the recent history.
+- "24h volume Nx weekly avg": the last day's volume against the weekly daily average; well above 1 means unusual activity, often news.
+- "Nd to resolution": days left until the end date.
+Treat these as context on how the market has moved, not as evidence of the outcome on their own. Fast moves on heavy volume usually follow information; drift on thin volume often does not.
+
+## Output fields
+
+- "predicted_outcome": the outcome you would buy, spelled exactly as listed under "Outcomes".
+- "fair_price": your probability that the predicted outcome wins, between 0 and 1. This is the most important number: it is used as the limit price of the order, so never state a price you would not pay.
+- "confidence": how much you trust your own estimate, between 0 and 1. Use low values when the question is ambiguous, the facts are unknown to you, or the event is far off.
+- "edge": fair_price minus the current price of the predicted outcome. It is positive only when the outcome is underpriced. Report 0 when you see no mispricing, rather than picking a side.
+- "reasoning": one or two sentences naming the main facts behind the estimate. Mention the resolution criteria when they decide the answer.
+- "recommended_size_pct": fraction of the bankroll to stake, between 0 and 1, following the sizing rules below.
+
+## Calibration
+
+- Start from the base rate for events of this kind, then adjust for specific evidence. Say which way the evidence moves you.
+- Market prices aggregate many traders. Assume the price is right unless you can name a concrete reason it is wrong, such as a fact the market has not priced, a misread of the resolution rules, or a price that has not caught up with recent news.
+- Prices near 0 or 1 are usually right. Claiming an edge against a 0.95 favourite needs strong evidence; claiming one on a 0.03 long shot needs more.
+- Your knowledge has a cutoff. For events that depend on news after it, lower your confidence and keep fair_price close to the market.
+- Read the question literally. Markets resolve on their stated criteria and source, not on what the question seems to intend.
+- Keep probabilities coherent: for a binary market the No price is one minus the Yes price, so a fair price for Yes implies one for No.
+
+## Sizing
+
+- Kelly fraction for buying a share at price p with win probability q is (q - p) / (1 - p). Recommend at most a quarter of it, and less when confidence is low.
+- Recommend 0 when edge is below 0.05, when the market is illiquid, or when the resolution is unclear.
+- Never recommend more than 0.10 of the bankroll on a single market, however large the edge looks.
+- Long-dated markets tie up capital: prefer smaller sizes when resolution is months away.
+
+Return only the JSON object, with no text before or after it."#;
+
+pub const COMMENTARY_SYSTEM_PROMPT: &str = r#"You are a risk-focused portfolio analyst for a prediction market trader.
+You receive the trader's current positions and recent price moves.
+Write a short narrative report (at most 250 words) in plain text covering:
+1. Overall portfolio posture and concentration
+2. The main risks to the current positions
+3. Positions worth exiting or reducing, and why
+Be direct and specific. Do not output JSON."#;
+
+/// A fully assembled prompt ready to be sent to Claude
+#[derive(Debug, Clone)]
+pub struct BuiltPrompt {
+    /// Instructions shared by every analysis, sent first and marked cacheable; `None` with
+    /// prompt caching off
+    pub cached_prefix: Option<String>,
+    /// The rest of the system prompt; may be empty when the prefix carries it all
+    pub system: String,
+    pub user: String,
+    /// Excludes the cached prefix, which is billed at a fraction of the input rate once cached
+    pub estimated_tokens: u32,
+    pub truncated: bool,
+    /// Prompt pack routed for the market, if any
+    pub pack: Option<String>,
+}
+
+pub struct PromptBuilder {
+    max_input_tokens: u32,
+    prompt_caching: bool,
+    packs: PromptPacks,
+}
+
+impl PromptBuilder {
+    pub fn new(max_input_tokens: u32, prompt_caching: bool) -> Self {
+        Self { max_input_tokens, prompt_caching, packs: PromptPacks::builtin() }
+    }
+
+    pub fn set_packs(&mut self, packs: PromptPacks) {
+        self.packs = packs;
+    }
+
+    /// Name of the prompt pack the market is routed to
+    pub fn pack_for(&self, market: &Market) -> Option<String> {
+        self.packs.route(market).map(|p| p.name.clone())
+    }
+
+    /// Cacheable prefix and the rest of the system prompt. With caching on, the base prompt
+    /// and the input reference form the prefix and the routed pack's guidance follows it;
+    /// with caching off, the reference is left out and the guidance follows the base prompt.
+    fn system_prompt(&self, market: &Market) -> (Option<String>, String) {
+        let guidance = self.packs.route(market).map(|p| p.guidance.as_str());
+        if self.prompt_caching {
+            let prefix = format!("{}\n\n{}", SYSTEM_PROMPT, ANALYSIS_REFERENCE);
+            return (Some(prefix), guidance.unwrap_or_default().to_string());
+        }
+        match guidance {
+            Some(guidance) => (None, format!("{}\n\n{}", SYSTEM_PROMPT, guidance)),
+            None => (None, SYSTEM_PROMPT.to_string()),
+        }
+    }
+
+    /// Build the analysis prompt for a market, eliding fields to fit the token budget.
+    /// The cached prefix does not count against the budget.
+    pub fn build_market_prompt(&self, market: &Market, features: Option<&MarketFeatures>) -> Result<BuiltPrompt> {
+        let (cached_prefix, system) = self.system_prompt(market);
+        let system_tokens = estimate_tokens(&system);
+        let mut truncated = false;
+
+        // First pass: cap the question length and the number of listed outcomes
+        let mut question_chars = MAX_QUESTION_CHARS;
+        let mut max_outcomes = MAX_OUTCOMES;
+
+        loop {
+            let (mut market_info, was_truncated) = format_market_info(market, question_chars, max_outcomes);
+            if let Some(features) = features.filter(|f| !f.is_empty()) {
+                market_info.push('\n');
+                market_info.push_str(&features.to_prompt_line());
+            }
+            let user = format!("Analyze this prediction market and provide your assessment:\n\n{}", market_info);
+            let estimated_tokens = system_tokens + estimate_tokens(&user);
+
+            if estimated_tokens <= self.max_input_tokens {
+                return Ok(BuiltPrompt {
+                    cached_prefix,
+                    system,
+                    user,
+                    estimated_tokens,
+                    truncated: truncated || was_truncated,
+                    pack: self.pack_for(market),
+                });
+            }
+
+            // Still over budget: shrink outcomes first, then the question
+            truncated = true;
+            if max_outcomes > 2 {
+                max_outcomes -= 1;
+            } else if question_chars > 60 {
+                question_chars = question_chars * 3 / 4;
+            } else {
+                bail!(
+                    "Prompt for market {} needs ~{} tokens, over the limit of {}",
+                    market.id,
+                    estimated_tokens,
+                    self.max_input_tokens
+                );
+            }
+        }
+    }
+}
+
+/// Estimate the token count of a piece of text
+pub fn estimate_tokens(text: &str) -> u32 {
+    (text.chars().count() as f64 / CHARS_PER_TOKEN).ceil() as u32
+}
+
+fn format_market_info(market: &Market, question_chars: usize, max_outcomes: usize) -> (String, bool) {
+    let (question, question_truncated) = elide_middle(&market.question, question_chars);
+
+    // Keep the highest-priced outcomes, they matter most for the analysis
+    let mut ranked: Vec<(usize, &String)> = market.outcomes.iter().enumerate().collect();
+    ranked.sort_by(|a, b| {
+        let pa = market.outcome_prices.get(a.0).copied().unwrap_or(0.0);
+        let pb = market.outcome_prices.get(b.0).copied().unwrap_or(0.0);
+        pb.partial_cmp(&pa).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let outcomes_truncated = ranked.len() > max_outcomes;
+    let omitted = ranked.len().saturating_sub(max_outcomes);
+    ranked.truncate(max_outcomes);
+    ranked.sort_by_key(|(i, _)| *i);
+
+    let mut outcomes: Vec<String> = ranked.iter().map(|(_, o)| o.to_string()).collect();
+    let mut prices: Vec<String> = ranked
+        .iter()
+        .map(|(i, _)| format!("{:.3}", market.outcome_prices.get(*i).copied().unwrap_or(0.0)))
+        .collect();
+    if omitted > 0 {
+        outcomes.push(format!("(+{} more)", omitted));
+        prices.push(ELLIPSIS.to_string());
+    }
+
+    let info = format!(
+        "Market: {}\nOutcomes: {}\nCurrent Prices: {}\nVolume: ${:.0}\nLiquidity: ${:.0}\nEnd Date: {}",
+        question,
+        outcomes.join(", "),
+        prices.join(", "),
+        market.volume,
+        market.liquidity,
+        market.end_date.as_deref().unwrap_or("Not set")
+    );
+
+    (info, question_truncated || outcomes_truncated)
+}
+
+/// Shorten text to `max_chars`, keeping the start and end which carry the most meaning
+fn elide_middle(text: &str, max_chars: usize) -> (String, bool) {
+    let len = text.chars().count();
+    if len <= max_chars {
+        return (text.to_string(), false);
+    }
+
+    let keep = max_chars.saturating_sub(1);
+    let head = keep * 2 / 3;
+    let tail = keep - head;
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(len - tail).collect();
+    (format!("{}{}{}", start.trim_end(), ELLIPSIS, end.trim_start()), true)
+}
+
+/// Build the portfolio snapshot sent to Claude for the periodic analyst report
+pub fn build_commentary_prompt(positions: &[Position], open_orders: &[Order], markets: &[Market]) -> String {
+    let mut lines = Vec::new();
+
+    for p in positions {
+        lines.push(format!(
+            "- {} | {} | size {:.2} | entry {:.3} → now {:.3} | PnL ${:.2}{}",
+            elide_middle(&p.market_name, 120).0,
+            p.outcome,
+            p.size,
+            p.avg_price,
+            p.current_price,
+            p.pnl,
+            if p.redeemable { " | RESOLVED" } else { "" }
+        ));
+    }
+
+    for o in open_orders {
+        // Mark against the latest scanned price when the market is still in the feed
+        let current = markets
+            .iter()
+            .find(|m| m.id == o.market_id)
+            .and_then(|m| {
+                let idx = m.outcomes.iter().position(|out| out == &o.outcome)?;
+                m.outcome_prices.get(idx).copied()
+            });
+        lines.push(format!(
+            "- {} | {} | ${:.2} @ {:.3} → now {}",
+            elide_middle(&o.market_name, 120).0,
+            o.outcome,
+            o.size,
+            o.price,
+            current.map(|c| format!("{:.3}", c)).unwrap_or_else(|| "n/a".to_string())
+        ));
+    }
+
+    if lines.is_empty() {
+        lines.push("- No open positions".to_string());
+    }
+
+    format!("Current portfolio:\n{}\n\nWrite the analyst report.", lines.join("\n"))
+}
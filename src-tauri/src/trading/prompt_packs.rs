@@ -0,0 +1,155 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use super::models::*;
+
+/// Extension of prompt-pack files in the packs directory
+const PACK_EXTENSION: &str = "txt";
+/// Separates the header lines from the guidance in a pack file
+const HEADER_END: &str = "---";
+
+/// Seeded into an empty packs directory; users edit or add files from there
+const BUILTIN_PACKS: [(&str, &str, &str); 3] = [
+    (
+        "sports",
+        "sports, soccer, football, nfl, nba, mlb, nhl, tennis, golf, mma, ufc, boxing, f1, cricket",
+        "Domain guidance (sports):
+- Anchor on bookmaker consensus and power ratings; prediction markets rarely beat sharp lines.
+- Weigh injuries, rest, travel, home advantage and lineup news, which move prices late.
+- Draws, overtime and postponement rules decide how the market resolves; read them.
+- Favor no trade when the edge comes only from a hunch about a single player or team.",
+    ),
+    (
+        "crypto",
+        "crypto, bitcoin, ethereum, solana, btc, eth, defi, nft",
+        "Domain guidance (crypto):
+- Price-threshold markets are options: estimate from current price, time left and realized volatility.
+- Note the exact price source and timestamp the market resolves on.
+- Treat ETF, listing and regulatory news as already priced unless timing is certain.
+- Crypto markets are momentum-driven and fat-tailed; keep confidence and size modest.",
+    ),
+    (
+        "politics",
+        "politics, elections, us-politics, geopolitics, congress, trump, world-politics",
+        "Domain guidance (politics):
+- Start from polling averages and historical base rates, not single polls or headlines.
+- Account for incumbency, turnout models and the resolution source (certified vs called results).
+- Legislative and legal outcomes depend on calendars and procedure; check what must happen by the end date.
+- Partisan enthusiasm skews these markets; look for overpriced long shots.",
+    ),
+];
+
+/// Category-specific guidance appended to the base system prompt, so the response format
+/// stays the same for every pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptPack {
+    pub name: String,
+    /// Market categories or tags routed to this pack, lowercase
+    pub tags: Vec<String>,
+    pub guidance: String,
+    /// File the pack was loaded from; `None` for the built-in packs
+    pub path: Option<PathBuf>,
+}
+
+impl PromptPack {
+    fn matches(&self, label: &str) -> bool {
+        let label = label.trim().to_lowercase();
+        self.tags.contains(&label)
+    }
+}
+
+/// Loaded prompt packs, routed by market category and tags
+#[derive(Debug, Clone, Default)]
+pub struct PromptPacks {
+    dir: Option<PathBuf>,
+    packs: Vec<PromptPack>,
+}
+
+impl PromptPacks {
+    /// The packs shipped with the app, used until a packs directory is loaded
+    pub fn builtin() -> Self {
+        let packs = BUILTIN_PACKS
+            .iter()
+            .map(|(name, tags, guidance)| PromptPack {
+                name: name.to_string(),
+                tags: parse_tags(tags),
+                guidance: guidance.to_string(),
+                path: None,
+            })
+            .collect();
+        Self { dir: None, packs }
+    }
+
+    /// Read every pack file in `dir`, seeding it with the built-in packs when it has none.
+    /// A file is a `tags: a, b, c` line, a `---` line, then the guidance text.
+    pub fn load(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("Creating {}", dir.display()))?;
+        if pack_files(dir)?.is_empty() {
+            for (name, tags, guidance) in BUILTIN_PACKS {
+                let path = dir.join(format!("{}.{}", name, PACK_EXTENSION));
+                fs::write(&path, format!("tags: {}\n{}\n{}\n", tags, HEADER_END, guidance))
+                    .with_context(|| format!("Writing {}", path.display()))?;
+            }
+        }
+
+        let mut packs = Vec::new();
+        for path in pack_files(dir)? {
+            let text = fs::read_to_string(&path)
+                .with_context(|| format!("Reading {}", path.display()))?
+                .replace("\r\n", "\n");
+            let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+            packs.push(parse_pack(name, &text, path)?);
+        }
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Self { dir: Some(dir.to_path_buf()), packs })
+    }
+
+    /// Re-read the directory the packs came from, after the user edited them
+    pub fn reload(&self) -> Result<Self> {
+        match self.dir {
+            Some(ref dir) => Self::load(dir),
+            None => Ok(self.clone()),
+        }
+    }
+
+    pub fn packs(&self) -> &[PromptPack] {
+        &self.packs
+    }
+
+    /// First pack whose tags include the market's category or one of its tags
+    pub fn route(&self, market: &Market) -> Option<&PromptPack> {
+        let labels: Vec<&str> = market.category.iter().chain(market.tags.iter()).map(|s| s.as_str()).collect();
+        self.packs.iter().find(|pack| labels.iter().any(|label| pack.matches(label)))
+    }
+}
+
+fn pack_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("Listing {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == PACK_EXTENSION) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn parse_pack(name: String, text: &str, path: PathBuf) -> Result<PromptPack> {
+    let (header, guidance) = text
+        .split_once(&format!("\n{}\n", HEADER_END))
+        .with_context(|| format!("{}: missing '{}' line after the header", path.display(), HEADER_END))?;
+    let tags = header
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("tags:"))
+        .map(parse_tags)
+        .unwrap_or_default();
+    if tags.is_empty() {
+        anyhow::bail!("{}: no 'tags:' line, so no market would be routed to it", path.display());
+    }
+    Ok(PromptPack { name, tags, guidance: guidance.trim().to_string(), path: Some(path) })
+}
+
+fn parse_tags(line: &str) -> Vec<String> {
+    line.split(',').map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect()
+}
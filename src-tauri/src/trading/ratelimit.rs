@@ -0,0 +1,106 @@
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// One Anthropic rate-limit bucket, as of the latest response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateBucket {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// When the bucket is fully replenished
+    pub reset_at: Option<DateTime<Utc>>,
+}
+
+impl RateBucket {
+    fn parse(headers: &HeaderMap, name: &str) -> Self {
+        let header = |field: &str| {
+            headers
+                .get(format!("anthropic-ratelimit-{}-{}", name, field))
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+        };
+        Self {
+            limit: header("limit").and_then(|v| v.parse().ok()),
+            remaining: header("remaining").and_then(|v| v.parse().ok()),
+            reset_at: header("reset")
+                .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+                .map(|t| t.with_timezone(&Utc)),
+        }
+    }
+
+    /// Time until `needed` units are available, when the bucket is short of them
+    fn wait_for(&self, needed: u64, now: DateTime<Utc>) -> Option<Duration> {
+        let remaining = self.remaining?;
+        if remaining >= needed {
+            return None;
+        }
+        // Without a reset time there is nothing to wait for; the next response will tell more
+        let wait = self.reset_at? - now;
+        (wait > Duration::zero()).then_some(wait)
+    }
+
+    fn fraction_left(&self) -> Option<f64> {
+        match (self.remaining, self.limit) {
+            (Some(remaining), Some(limit)) if limit > 0 => Some(remaining as f64 / limit as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Quota left on the Anthropic API, read from the headers of every response
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RateLimitStatus {
+    pub requests: RateBucket,
+    pub input_tokens: RateBucket,
+    pub output_tokens: RateBucket,
+    /// Set from `retry-after` on a throttled response; no request is sent before it
+    pub retry_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl RateLimitStatus {
+    /// Take the buckets from a response, keeping the previous values of any header it omits
+    pub fn update(&mut self, headers: &HeaderMap, now: DateTime<Utc>) {
+        let merge = |old: RateBucket, new: RateBucket| RateBucket {
+            limit: new.limit.or(old.limit),
+            remaining: new.remaining.or(old.remaining),
+            reset_at: new.reset_at.or(old.reset_at),
+        };
+        let requests = RateBucket::parse(headers, "requests");
+        let input_tokens = RateBucket::parse(headers, "input-tokens");
+        let output_tokens = RateBucket::parse(headers, "output-tokens");
+        if requests == RateBucket::default() && input_tokens == RateBucket::default() && output_tokens == RateBucket::default() {
+            // Not an Anthropic response (e.g. a proxy error page)
+            return;
+        }
+        self.requests = merge(self.requests, requests);
+        self.input_tokens = merge(self.input_tokens, input_tokens);
+        self.output_tokens = merge(self.output_tokens, output_tokens);
+        self.retry_at = headers
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .map(|secs| now + Duration::seconds(secs));
+        self.updated_at = Some(now);
+    }
+
+    /// How long to hold a request of about `input_tokens` in and `output_tokens` out so it
+    /// is not throttled; `None` when it can go now
+    pub fn pacing_delay(&self, input_tokens: u64, output_tokens: u64, now: DateTime<Utc>) -> Option<Duration> {
+        let waits = [
+            self.retry_at.map(|at| at - now).filter(|d| *d > Duration::zero()),
+            self.requests.wait_for(1, now),
+            self.input_tokens.wait_for(input_tokens, now),
+            self.output_tokens.wait_for(output_tokens, now),
+        ];
+        waits.into_iter().flatten().max()
+    }
+
+    /// Smallest share of any bucket still available, for the health view
+    pub fn headroom(&self) -> Option<f64> {
+        [self.requests, self.input_tokens, self.output_tokens]
+            .iter()
+            .filter_map(RateBucket::fraction_left)
+            .min_by(|a, b| a.total_cmp(b))
+    }
+}
@@ -0,0 +1,269 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use super::engine::TradingEngine;
+
+/// How often the scheduler looks for a finished period without a report
+pub const REPORT_CHECK_SECS: u64 = 300;
+/// Notable events listed per report, newest first
+pub const MAX_NOTABLE_EVENTS: usize = 20;
+/// Days of cycle cost samples kept: the previous ISO week plus scheduler slack
+pub const COST_RETENTION_DAYS: i64 = 15;
+
+/// Which performance reports are written automatically to the app data dir
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportConfig {
+    pub daily: bool,
+    pub weekly: bool,
+}
+
+/// Report periods are UTC days and ISO weeks (Monday to Monday)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReportPeriod {
+    Daily,
+    Weekly,
+}
+
+impl ReportPeriod {
+    /// Start of the period containing `now`
+    pub fn start(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let midnight = now.date_naive().and_time(NaiveTime::MIN).and_utc();
+        match self {
+            ReportPeriod::Daily => midnight,
+            ReportPeriod::Weekly => midnight - ChronoDuration::days(now.weekday().num_days_from_monday() as i64),
+        }
+    }
+
+    /// Bounds of the last complete period before `now`
+    pub fn previous(self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let to = self.start(now);
+        let from = self.start(to - ChronoDuration::seconds(1));
+        (from, to)
+    }
+
+    /// File stem naming the period starting at `from`, e.g. "daily-2026-10-13" or "weekly-2026-W41"
+    pub fn file_stem(self, from: DateTime<Utc>) -> String {
+        match self {
+            ReportPeriod::Daily => format!("daily-{}", from.format("%Y-%m-%d")),
+            ReportPeriod::Weekly => {
+                let week = from.iso_week();
+                format!("weekly-{}-W{:02}", week.year(), week.week())
+            }
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => "Daily report",
+            ReportPeriod::Weekly => "Weekly report",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportTrade {
+    pub market: String,
+    pub pnl: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryCalibration {
+    pub category: String,
+    /// 1 - Brier / 0.25, see `scoring::category_calibration`
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotableEvent {
+    pub timestamp: DateTime<Utc>,
+    pub message: String,
+}
+
+/// Performance over one period, built from the ledger, balance history and activity log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceReport {
+    pub period: ReportPeriod,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub start_balance: f64,
+    pub end_balance: f64,
+    /// Resolutions, exits and conversions settled in the period
+    pub realized_pnl: f64,
+    pub trades_placed: u32,
+    pub volume: f64,
+    pub wins: u32,
+    pub losses: u32,
+    pub best_trade: Option<ReportTrade>,
+    pub worst_trade: Option<ReportTrade>,
+    /// Estimated AI spend in the period, from cost samples taken each cycle
+    pub api_costs: f64,
+    pub signals_recorded: u32,
+    /// Per-category calibration over every settled signal, best first
+    pub calibration: Vec<CategoryCalibration>,
+    pub notable_events: Vec<NotableEvent>,
+    /// Markdown file written for this report, when it was saved
+    pub path: Option<PathBuf>,
+}
+
+impl PerformanceReport {
+    pub fn win_rate(&self) -> Option<f64> {
+        let settled = self.wins + self.losses;
+        (settled > 0).then(|| self.wins as f64 / settled as f64)
+    }
+
+    /// One-paragraph summary for notifications
+    pub fn summary(&self) -> String {
+        let win_rate = self.win_rate().map(|w| format!("{:.0}%", w * 100.0)).unwrap_or_else(|| "-".to_string());
+        format!(
+            "{}: PnL {:+.2}, balance {:.2} → {:.2}, {} trades, win rate {}, AI costs ${:.2}",
+            self.period_label(),
+            self.realized_pnl,
+            self.start_balance,
+            self.end_balance,
+            self.trades_placed,
+            win_rate,
+            self.api_costs,
+        )
+    }
+
+    fn period_label(&self) -> String {
+        match self.period {
+            ReportPeriod::Daily => self.from.format("%Y-%m-%d").to_string(),
+            ReportPeriod::Weekly => format!("{} – {}", self.from.format("%Y-%m-%d"), (self.to - ChronoDuration::days(1)).format("%Y-%m-%d")),
+        }
+    }
+
+    fn rows(&self) -> Vec<(&'static str, String)> {
+        let trade = |t: &Option<ReportTrade>| {
+            t.as_ref().map(|t| format!("{:+.2} ({})", t.pnl, t.market)).unwrap_or_else(|| "-".to_string())
+        };
+        vec![
+            ("Start balance", format!("${:.2}", self.start_balance)),
+            ("End balance", format!("${:.2}", self.end_balance)),
+            ("Realized PnL", format!("{:+.2}", self.realized_pnl)),
+            ("Trades placed", self.trades_placed.to_string()),
+            ("Volume", format!("${:.2}", self.volume)),
+            ("Wins / losses", format!("{} / {}", self.wins, self.losses)),
+            ("Win rate", self.win_rate().map(|w| format!("{:.1}%", w * 100.0)).unwrap_or_else(|| "-".to_string())),
+            ("Best trade", trade(&self.best_trade)),
+            ("Worst trade", trade(&self.worst_trade)),
+            ("AI costs", format!("${:.2}", self.api_costs)),
+            ("Signals recorded", self.signals_recorded.to_string()),
+        ]
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {} — {}\n\n", self.period.title(), self.period_label());
+        out.push_str("| Metric | Value |\n|---|---|\n");
+        for (name, value) in self.rows() {
+            out.push_str(&format!("| {} | {} |\n", name, value.replace('|', "\\|")));
+        }
+
+        out.push_str("\n## Calibration\n\n");
+        if self.calibration.is_empty() {
+            out.push_str("Not enough settled signals yet.\n");
+        } else {
+            out.push_str("| Category | Score |\n|---|---|\n");
+            for c in &self.calibration {
+                out.push_str(&format!("| {} | {:.2} |\n", c.category, c.score));
+            }
+        }
+
+        out.push_str("\n## Notable events\n\n");
+        if self.notable_events.is_empty() {
+            out.push_str("None.\n");
+        }
+        for event in &self.notable_events {
+            out.push_str(&format!("- {} — {}\n", event.timestamp.format("%Y-%m-%d %H:%M"), event.message));
+        }
+
+        out.push_str(&format!("\n_Generated {} UTC_\n", self.generated_at.format("%Y-%m-%d %H:%M")));
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let title = format!("{} — {}", self.period.title(), self.period_label());
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{0}</title>\
+             <style>body{{font-family:sans-serif;max-width:48em;margin:2em auto}}\
+             table{{border-collapse:collapse}}td,th{{border:1px solid #ccc;padding:.3em .6em;text-align:left}}</style>\
+             </head><body>\n<h1>{0}</h1>\n<table>\n",
+            escape_html(&title)
+        );
+        for (name, value) in self.rows() {
+            out.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", name, escape_html(&value)));
+        }
+        out.push_str("</table>\n<h2>Calibration</h2>\n");
+        if self.calibration.is_empty() {
+            out.push_str("<p>Not enough settled signals yet.</p>\n");
+        } else {
+            out.push_str("<table>\n<tr><th>Category</th><th>Score</th></tr>\n");
+            for c in &self.calibration {
+                out.push_str(&format!("<tr><td>{}</td><td>{:.2}</td></tr>\n", escape_html(&c.category), c.score));
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("<h2>Notable events</h2>\n<ul>\n");
+        for event in &self.notable_events {
+            out.push_str(&format!(
+                "<li>{} — {}</li>\n",
+                event.timestamp.format("%Y-%m-%d %H:%M"),
+                escape_html(&event.message)
+            ));
+        }
+        out.push_str(&format!(
+            "</ul>\n<p><em>Generated {} UTC</em></p>\n</body></html>\n",
+            self.generated_at.format("%Y-%m-%d %H:%M")
+        ));
+        out
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Markdown path of the report for the period starting at `from`
+pub fn report_path(dir: &Path, period: ReportPeriod, from: DateTime<Utc>) -> PathBuf {
+    dir.join(format!("{}.md", period.file_stem(from)))
+}
+
+/// Write the report as Markdown and HTML side by side; returns the Markdown path
+pub fn write(dir: &Path, report: &PerformanceReport) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("Creating {}", dir.display()))?;
+    let path = report_path(dir, report.period, report.from);
+    fs::write(&path, report.to_markdown()).with_context(|| format!("Writing {}", path.display()))?;
+    let html = path.with_extension("html");
+    fs::write(&html, report.to_html()).with_context(|| format!("Writing {}", html.display()))?;
+    Ok(path)
+}
+
+/// Write each enabled report once its period has ended, for the lifetime of the app.
+/// A period that already has a file is skipped, so restarts never duplicate reports.
+pub async fn scheduler(engine: Arc<Mutex<TradingEngine>>, dir: PathBuf) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(REPORT_CHECK_SECS)).await;
+        let config = engine.lock().await.config.reports.clone();
+        let now = Utc::now();
+        let due = [(ReportPeriod::Daily, config.daily), (ReportPeriod::Weekly, config.weekly)];
+        for (period, enabled) in due {
+            let (from, to) = period.previous(now);
+            if !enabled || report_path(&dir, period, from).exists() {
+                continue;
+            }
+            let mut report = engine.lock().await.build_report(period, from, to);
+            let written = write(&dir, &report);
+            if let Ok(ref path) = written {
+                report.path = Some(path.clone());
+            }
+            engine.lock().await.report_written(&report, written.map(|_| ()));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::config;
+use super::models::*;
+
+/// Reviews kept per position, oldest dropped first
+pub const MAX_REVIEWS_PER_POSITION: usize = 50;
+
+/// Periodic re-analysis of markets already held, against the thesis they were entered on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReunderwriteConfig {
+    pub enabled: bool,
+    /// Hours between reviews of the same position
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub interval_hours: u32,
+    /// Positions re-analyzed per cycle at most, bounding the extra AI cost
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub max_per_cycle: u32,
+    /// Recommend exiting once the fair value has fallen this far below the entry thesis
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub exit_drift: f64,
+    /// Recommend adding when the fresh fair value beats the market price by at least this
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub add_edge: f64,
+    /// Act on add and exit recommendations instead of only logging them. Adds still go
+    /// through auto trading, the risk limits and the execution gate.
+    pub auto_act: bool,
+}
+
+impl Default for ReunderwriteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: 12,
+            max_per_cycle: 2,
+            exit_drift: 0.10,
+            add_edge: 0.08,
+            auto_act: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Recommendation {
+    Hold,
+    Add,
+    Exit,
+}
+
+/// One re-analysis of a held position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThesisReview {
+    pub market_id: String,
+    pub market_name: String,
+    pub outcome: String,
+    /// Fair value of the held outcome when the position was opened
+    pub entry_fair: f64,
+    pub fresh_fair: f64,
+    /// `fresh_fair - entry_fair`; negative when the thesis weakened
+    pub drift: f64,
+    pub market_price: f64,
+    /// `fresh_fair - market_price`
+    pub edge: f64,
+    pub recommendation: Recommendation,
+    /// The recommendation was carried out
+    pub acted: bool,
+    pub reasoning: String,
+    pub reviewed_at: DateTime<Utc>,
+}
+
+/// Thesis history is kept per market and outcome
+pub fn position_key(market_id: &str, outcome: &str) -> String {
+    format!("{}:{}", market_id, outcome)
+}
+
+/// Exit when the edge is gone or the thesis drifted too far down; add when the edge is
+/// wide; otherwise hold
+pub fn recommend(entry_fair: f64, fresh_fair: f64, market_price: f64, config: &ReunderwriteConfig) -> Recommendation {
+    let edge = fresh_fair - market_price;
+    if edge <= 0.0 || fresh_fair - entry_fair <= -config.exit_drift {
+        Recommendation::Exit
+    } else if edge >= config.add_edge {
+        Recommendation::Add
+    } else {
+        Recommendation::Hold
+    }
+}
+
+/// Probability the prediction assigns to `outcome` of a binary market
+pub fn fair_for(prediction: &AIPrediction, outcome: &str) -> f64 {
+    if prediction.predicted_outcome == outcome {
+        prediction.fair_price
+    } else {
+        1.0 - prediction.fair_price
+    }
+}
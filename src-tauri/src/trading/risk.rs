@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use super::config;
+use super::models::*;
+
+pub const UNCATEGORIZED: &str = "other";
+
+/// Risk limits enforced by the position manager before any order is placed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskConfig {
+    /// Max fraction of equity allowed in open positions per category, e.g. {"politics": 0.2}
+    pub category_caps: HashMap<String, f64>,
+    /// Cash kept aside (e.g. for API costs) and never used for sizing
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub cash_reserve: f64,
+    /// Max dollars held in one market across all entries, 0 for no limit
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub max_market_exposure: f64,
+    /// Entries allowed on top of the first one in a market
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub max_add_ons: u32,
+    /// An add-on must be priced at least this much below the previous entry
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub min_add_on_improvement: f64,
+    /// Max fraction of equity lost if every open position and the new order lose, 0 for no limit
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub max_worst_case_loss: f64,
+    /// Max fraction of equity in markets whose questions are similar to the new one's,
+    /// including its own market, 0 for no limit
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub max_correlated_exposure: f64,
+    /// Question embedding similarity at which two markets count as correlated
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub correlation_similarity: f64,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            category_caps: HashMap::new(),
+            cash_reserve: 0.0,
+            max_market_exposure: 0.0,
+            max_add_ons: 2,
+            min_add_on_improvement: 0.02,
+            max_worst_case_loss: 0.0,
+            max_correlated_exposure: 0.0,
+            correlation_similarity: 0.80,
+        }
+    }
+}
+
+/// Outcome of running a proposed trade through the risk checks
+#[derive(Debug, Clone, PartialEq)]
+pub enum SizeDecision {
+    Approved(f64),
+    Downsized { size: f64, reason: String },
+    Rejected(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryUtilization {
+    pub category: String,
+    pub exposure: f64,
+    pub cap: Option<f64>,
+    /// Exposure as a fraction of the category cap, when one is configured
+    pub utilization: Option<f64>,
+}
+
+/// Portfolio as it would stand once a proposed order fills, checked before placing it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightRisk {
+    pub equity: f64,
+    /// Open exposure including the new order
+    pub total_exposure: f64,
+    pub category: String,
+    pub category_exposure: f64,
+    /// The order's category as a fraction of total exposure
+    pub category_concentration: f64,
+    /// Stake lost if every open position and the new order resolve against us
+    pub worst_case_loss: f64,
+    /// `worst_case_loss` as a fraction of equity
+    pub worst_case_fraction: f64,
+    /// Configured limit on `worst_case_fraction`, when one is set
+    pub limit: Option<f64>,
+}
+
+impl SizeDecision {
+    /// Run a further check on the size left by this one, keeping the earlier downsizing
+    /// reason when the later check approves
+    pub fn and_then(self, check: impl FnOnce(f64) -> SizeDecision) -> SizeDecision {
+        match self {
+            SizeDecision::Approved(size) => check(size),
+            SizeDecision::Downsized { size, reason } => match check(size) {
+                SizeDecision::Approved(size) => SizeDecision::Downsized { size, reason },
+                later => later,
+            },
+            rejected => rejected,
+        }
+    }
+}
+
+/// Open positions whose market questions are similar enough to move together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExposureGroup {
+    pub market_ids: Vec<String>,
+    pub market_names: Vec<String>,
+    pub exposure: f64,
+    /// `exposure` as a fraction of equity
+    pub fraction: f64,
+}
+
+impl PreflightRisk {
+    pub fn blocked(&self) -> bool {
+        self.limit.is_some_and(|limit| self.worst_case_fraction > limit + 1e-9)
+    }
+}
+
+/// Open entries in one market aggregated into a single position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketPosition {
+    pub market_id: String,
+    pub market_name: String,
+    pub outcome: String,
+    pub category: Option<String>,
+    /// Top-level orders making up the position; sliced orders count once
+    pub entries: u32,
+    /// Dollars committed, including unfilled slices
+    pub size: f64,
+    pub shares: f64,
+    /// Share-weighted entry price
+    pub avg_price: f64,
+    /// Fill price of the latest entry where known, its limit price otherwise
+    pub last_entry_price: f64,
+    pub order_ids: Vec<String>,
+}
+
+/// Normalized category key used for cap lookups
+pub fn category_key(category: Option<&str>) -> String {
+    category
+        .map(|c| c.trim().to_lowercase())
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| UNCATEGORIZED.to_string())
+}
+
+pub struct PositionManager<'a> {
+    risk: &'a RiskConfig,
+    orders: &'a [Order],
+    equity: f64,
+    /// Fill prices by order id, used for the last entry price of a position
+    fill_prices: Option<&'a HashMap<String, f64>>,
+}
+
+impl<'a> PositionManager<'a> {
+    pub fn new(risk: &'a RiskConfig, orders: &'a [Order], equity: f64) -> Self {
+        Self { risk, orders, equity, fill_prices: None }
+    }
+
+    pub fn with_fill_prices(mut self, fill_prices: &'a HashMap<String, f64>) -> Self {
+        self.fill_prices = Some(fill_prices);
+        self
+    }
+
+    /// Open exposure grouped by category: filled, unresolved orders plus the
+    /// still-unfilled remainder of sliced parent orders and resting paper orders
+    pub fn category_exposure(&self) -> HashMap<String, f64> {
+        let mut exposure = HashMap::new();
+        for order in self.bot_orders() {
+            let open = committed(order);
+            if open > 0.0 {
+                *exposure.entry(category_key(order.category.as_deref())).or_insert(0.0) += open;
+            }
+        }
+        exposure
+    }
+
+    /// Open exposure per market, counted like `category_exposure`
+    fn market_exposure(&self) -> HashMap<&'a str, f64> {
+        let mut exposure = HashMap::new();
+        for order in self.bot_orders() {
+            let open = committed(order);
+            if open > 0.0 {
+                *exposure.entry(order.market_id.as_str()).or_insert(0.0) += open;
+            }
+        }
+        exposure
+    }
+
+    /// Check a proposed order against the limit on markets correlated with its own, where
+    /// `correlated` decides whether a held market is similar to the new one
+    pub fn check_correlated_exposure(&self, correlated: impl Fn(&str) -> bool, size: f64) -> SizeDecision {
+        if self.risk.max_correlated_exposure <= 0.0 {
+            return SizeDecision::Approved(size);
+        }
+        let limit = self.risk.max_correlated_exposure * self.equity;
+        let current: f64 = self.market_exposure()
+            .into_iter()
+            .filter(|(market_id, _)| correlated(market_id))
+            .map(|(_, exposure)| exposure)
+            .sum();
+        let room = (limit - current).max(0.0);
+
+        if size <= room {
+            SizeDecision::Approved(size)
+        } else if room >= 1.0 {
+            SizeDecision::Downsized {
+                size: room,
+                reason: format!("correlated markets cap {:.0}% leaves ${:.2}", self.risk.max_correlated_exposure * 100.0, room),
+            }
+        } else {
+            SizeDecision::Rejected(format!(
+                "correlated markets hold ${:.2}, at cap {:.0}% of equity",
+                current,
+                self.risk.max_correlated_exposure * 100.0
+            ))
+        }
+    }
+
+    /// Open positions grouped with every other position they are similar to, largest first
+    pub fn exposure_groups(&self, similar: impl Fn(&str, &str) -> bool) -> Vec<ExposureGroup> {
+        let exposure = self.market_exposure();
+        let mut groups: Vec<ExposureGroup> = Vec::new();
+        for position in self.market_positions() {
+            if groups.iter().any(|g| g.market_ids.contains(&position.market_id)) {
+                continue;
+            }
+            let amount = exposure.get(position.market_id.as_str()).copied().unwrap_or(0.0);
+            match groups.iter_mut().find(|g| g.market_ids.iter().any(|id| similar(id, &position.market_id))) {
+                Some(group) => {
+                    group.market_ids.push(position.market_id);
+                    group.market_names.push(position.market_name);
+                    group.exposure += amount;
+                }
+                None => groups.push(ExposureGroup {
+                    market_ids: vec![position.market_id],
+                    market_names: vec![position.market_name],
+                    exposure: amount,
+                    fraction: 0.0,
+                }),
+            }
+        }
+        for group in &mut groups {
+            group.fraction = if self.equity > 0.0 { group.exposure / self.equity } else { 0.0 };
+        }
+        groups.sort_by(|a, b| b.exposure.total_cmp(&a.exposure));
+        groups
+    }
+
+    /// Stake currently locked in filled, unresolved orders, including the filled part
+    /// of orders still resting
+    pub fn open_exposure(&self) -> f64 {
+        self.bot_orders()
+            .filter(|o| !o.is_parent())
+            .map(|o| match o.status {
+                OrderStatus::Filled => o.size,
+                status if status.is_working() => o.filled_size,
+                _ => 0.0,
+            })
+            .sum()
+    }
+
+    /// Capital committed but not yet filled (remaining slices and resting remainders) plus
+    /// the configured cash reserve
+    pub fn reserved(&self) -> f64 {
+        let unfilled: f64 = self.bot_orders()
+            .filter(|o| o.status.is_working() && (o.is_parent() || o.parent_id.is_none()))
+            .map(|o| (o.size - o.filled_size).max(0.0))
+            .sum();
+        unfilled + self.risk.cash_reserve.max(0.0)
+    }
+
+    /// Orders the bot placed; imported account history is not part of its book
+    fn bot_orders(&self) -> impl Iterator<Item = &'a Order> {
+        self.orders.iter().filter(|o| !o.is_imported())
+    }
+
+    /// Collateral available for new positions
+    pub fn free_capital(&self) -> f64 {
+        (self.equity - self.open_exposure() - self.reserved()).max(0.0)
+    }
+
+    fn cap_for(&self, category: &str) -> Option<f64> {
+        self.risk.category_caps
+            .iter()
+            .find(|(k, _)| k.trim().to_lowercase() == category)
+            .map(|(_, v)| *v)
+    }
+
+    /// Check a proposed order against the category cap, downsizing it to fit when possible
+    pub fn check_category_cap(&self, category: Option<&str>, size: f64) -> SizeDecision {
+        let key = category_key(category);
+        let Some(cap) = self.cap_for(&key) else {
+            return SizeDecision::Approved(size);
+        };
+
+        let limit = cap * self.equity;
+        let current = self.category_exposure().get(&key).copied().unwrap_or(0.0);
+        let room = (limit - current).max(0.0);
+
+        if size <= room {
+            SizeDecision::Approved(size)
+        } else if room >= 1.0 {
+            SizeDecision::Downsized {
+                size: room,
+                reason: format!("{} cap {:.0}% leaves ${:.2}", key, cap * 100.0, room),
+            }
+        } else {
+            SizeDecision::Rejected(format!(
+                "{} exposure ${:.2} at cap {:.0}% of equity",
+                key,
+                current,
+                cap * 100.0
+            ))
+        }
+    }
+
+    /// Open orders aggregated per market, in order of first entry. Child slices are
+    /// covered by their parent, and cancelled, failed or resolved orders are left out.
+    pub fn market_positions(&self) -> Vec<MarketPosition> {
+        let mut positions: Vec<MarketPosition> = Vec::new();
+        let open = self.bot_orders().filter(|o| {
+            o.parent_id.is_none() && (o.status == OrderStatus::Filled || o.status.is_working())
+        });
+        for order in open {
+            let shares = order.shares();
+            let entry_price = order.fill_price
+                .or_else(|| self.fill_prices.and_then(|prices| prices.get(&order.id).copied()))
+                .unwrap_or(order.price);
+            match positions.iter_mut().find(|p| p.market_id == order.market_id && p.outcome == order.outcome) {
+                Some(position) => {
+                    position.entries += 1;
+                    position.size += order.size;
+                    position.shares += shares;
+                    position.last_entry_price = entry_price;
+                    position.order_ids.push(order.id.clone());
+                }
+                None => positions.push(MarketPosition {
+                    market_id: order.market_id.clone(),
+                    market_name: order.market_name.clone(),
+                    outcome: order.outcome.clone(),
+                    category: order.category.clone(),
+                    entries: 1,
+                    size: order.size,
+                    shares,
+                    avg_price: 0.0,
+                    last_entry_price: entry_price,
+                    order_ids: vec![order.id.clone()],
+                }),
+            }
+        }
+        for position in &mut positions {
+            position.avg_price = if position.shares > 0.0 { position.size / position.shares } else { 0.0 };
+        }
+        positions
+    }
+
+    /// Check a proposed entry against the market's existing position: the opposite outcome
+    /// is never bought, add-ons are limited in number, and the total stays under the
+    /// per-market limit
+    pub fn check_market_position(&self, market_id: &str, outcome: &str, size: f64) -> SizeDecision {
+        let positions = self.market_positions();
+        let held: Vec<&MarketPosition> = positions.iter().filter(|p| p.market_id == market_id).collect();
+        if let Some(other) = held.iter().find(|p| p.outcome != outcome) {
+            return SizeDecision::Rejected(format!("already holds {} in this market", other.outcome));
+        }
+
+        let position = held.first();
+        if let Some(position) = position {
+            if position.entries > self.risk.max_add_ons {
+                return SizeDecision::Rejected(format!(
+                    "{} entries already, max {} add-ons",
+                    position.entries, self.risk.max_add_ons
+                ));
+            }
+        }
+
+        if self.risk.max_market_exposure <= 0.0 {
+            return SizeDecision::Approved(size);
+        }
+        let room = (self.risk.max_market_exposure - position.map(|p| p.size).unwrap_or(0.0)).max(0.0);
+        if size <= room {
+            SizeDecision::Approved(size)
+        } else if room >= 1.0 {
+            SizeDecision::Downsized {
+                size: room,
+                reason: format!("market limit ${:.2} leaves ${:.2}", self.risk.max_market_exposure, room),
+            }
+        } else {
+            SizeDecision::Rejected(format!("market position at its ${:.2} limit", self.risk.max_market_exposure))
+        }
+    }
+
+    /// An add-on must enter at least `min_add_on_improvement` below the price the last
+    /// entry into the same outcome paid. `entry` is what this order would pay, the best ask.
+    pub fn check_add_on_price(&self, market_id: &str, outcome: &str, entry: f64) -> Result<(), String> {
+        let positions = self.market_positions();
+        let Some(position) = positions.iter().find(|p| p.market_id == market_id && p.outcome == outcome) else {
+            return Ok(());
+        };
+        let required = position.last_entry_price - self.risk.min_add_on_improvement;
+        if entry > required + 1e-9 {
+            return Err(format!(
+                "add-on at {:.3} needs {:.3} or better after the {:.3} entry",
+                entry, required, position.last_entry_price
+            ));
+        }
+        Ok(())
+    }
+
+    /// Simulate the portfolio after a proposed order fills. Entries pay nothing when they
+    /// lose, so the worst case is losing every open stake plus this one.
+    pub fn preflight(&self, category: Option<&str>, size: f64) -> PreflightRisk {
+        let key = category_key(category);
+        let exposure = self.category_exposure();
+        let total_exposure = exposure.values().sum::<f64>() + size;
+        let category_exposure = exposure.get(&key).copied().unwrap_or(0.0) + size;
+        let fraction = |amount: f64| if self.equity > 0.0 { amount / self.equity } else { f64::INFINITY };
+        PreflightRisk {
+            equity: self.equity,
+            total_exposure,
+            category: key,
+            category_exposure,
+            category_concentration: if total_exposure > 0.0 { category_exposure / total_exposure } else { 0.0 },
+            worst_case_loss: total_exposure,
+            worst_case_fraction: fraction(total_exposure),
+            limit: (self.risk.max_worst_case_loss > 0.0).then_some(self.risk.max_worst_case_loss),
+        }
+    }
+
+    /// Current exposure and cap utilization for every category with exposure or a cap
+    pub fn category_utilization(&self) -> Vec<CategoryUtilization> {
+        let mut exposure = self.category_exposure();
+        for key in self.risk.category_caps.keys() {
+            exposure.entry(key.trim().to_lowercase()).or_insert(0.0);
+        }
+
+        let mut rows: Vec<CategoryUtilization> = exposure
+            .into_iter()
+            .map(|(category, exposure)| {
+                let cap = self.cap_for(&category);
+                let limit = cap.map(|c| c * self.equity);
+                CategoryUtilization {
+                    utilization: limit.filter(|l| *l > 0.0).map(|l| exposure / l),
+                    category,
+                    exposure,
+                    cap,
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| a.category.cmp(&b.category));
+        rows
+    }
+}
+
+/// Dollars an order holds open: the full stake of filled or working entries, and the
+/// unfilled remainder of working sliced parents
+fn committed(order: &Order) -> f64 {
+    if order.is_parent() {
+        if order.status.is_working() { order.size - order.filled_size } else { 0.0 }
+    } else if order.status == OrderStatus::Filled || order.status.is_working() {
+        order.size
+    } else {
+        0.0
+    }
+}
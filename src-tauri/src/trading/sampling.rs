@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use super::config;
+use super::models::*;
+
+/// How the markets sent for AI analysis are picked from the scanned universe each cycle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplingStrategy {
+    /// The first markets in scan order
+    #[default]
+    Top,
+    /// The markets analyzed longest ago, never-analyzed ones first, so the whole universe is covered in turn
+    RoundRobin,
+    /// Random draw weighted by 24h volume
+    VolumeWeighted,
+    /// Largest price change since the previous scan first
+    RecentlyMoved,
+    /// Watchlisted markets first, the rest in scan order
+    WatchlistFirst,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplingConfig {
+    pub strategy: SamplingStrategy,
+    /// Markets analyzed per cycle
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub markets_per_cycle: u32,
+    /// Market ids preferred by the watchlist strategy, in priority order
+    pub watchlist: Vec<String>,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self { strategy: SamplingStrategy::Top, markets_per_cycle: 10, watchlist: Vec::new() }
+    }
+}
+
+/// Memory the stateful strategies need between cycles
+#[derive(Debug, Default)]
+pub struct Sampler {
+    /// Cycle each market was last sampled in
+    last_sampled: HashMap<String, u32>,
+    /// First outcome price at the previous scan
+    last_prices: HashMap<String, f64>,
+}
+
+impl Sampler {
+    /// Pick up to `markets_per_cycle` markets for cycle `cycle`, remembering what was
+    /// sampled and the prices seen
+    pub fn sample<'m>(&mut self, markets: Vec<&'m Market>, config: &SamplingConfig, cycle: u32) -> Vec<&'m Market> {
+        let n = config.markets_per_cycle as usize;
+        let mut ranked = markets;
+        match config.strategy {
+            SamplingStrategy::Top => {}
+            SamplingStrategy::RoundRobin => {
+                // Stable sort keeps scan order among markets sampled in the same cycle
+                ranked.sort_by_key(|m| self.last_sampled.get(&m.id).map_or(0, |c| *c as u64 + 1));
+            }
+            SamplingStrategy::VolumeWeighted => {
+                // Weighted sampling without replacement: key u^(1/w), largest keys win
+                let mut rng = rand::thread_rng();
+                let mut keyed: Vec<(f64, &Market)> = ranked
+                    .into_iter()
+                    .map(|m| {
+                        let weight = m.volume_24h.max(1.0);
+                        (rng.gen::<f64>().powf(1.0 / weight), m)
+                    })
+                    .collect();
+                keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+                ranked = keyed.into_iter().map(|(_, m)| m).collect();
+            }
+            SamplingStrategy::RecentlyMoved => {
+                let moved = |m: &Market| match (m.outcome_prices.first(), self.last_prices.get(&m.id)) {
+                    (Some(now), Some(before)) => (now - before).abs(),
+                    _ => 0.0,
+                };
+                ranked.sort_by(|a, b| moved(b).total_cmp(&moved(a)));
+            }
+            SamplingStrategy::WatchlistFirst => {
+                let priority = |m: &Market| config.watchlist.iter().position(|id| *id == m.id).unwrap_or(usize::MAX);
+                ranked.sort_by_key(|m| priority(m));
+            }
+        }
+
+        for market in &ranked {
+            if let Some(&price) = market.outcome_prices.first() {
+                self.last_prices.insert(market.id.clone(), price);
+            }
+        }
+        ranked.truncate(n);
+        for market in &ranked {
+            self.last_sampled.insert(market.id.clone(), cycle);
+        }
+        ranked
+    }
+}
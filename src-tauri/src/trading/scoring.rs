@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::backtest::Signal;
+use super::config;
+use super::models::*;
+use super::risk;
+use super::time;
+
+/// Settled signals a category needs before its own calibration is trusted
+const MIN_CALIBRATION_SAMPLES: usize = 10;
+/// Component value used when the input is unknown
+const NEUTRAL: f64 = 0.5;
+
+/// Composite trade score: a weighted mean of components scaled to [0, 1]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub edge_weight: f64,
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub confidence_weight: f64,
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub liquidity_weight: f64,
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub time_weight: f64,
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub calibration_weight: f64,
+    /// Edge that earns the full edge component
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub edge_scale: f64,
+    /// Liquidity that earns the full liquidity component (log scale)
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub liquidity_scale: f64,
+    /// Markets resolving later than this get no time component
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub horizon_days: u32,
+    /// Candidates scoring below this are not traded
+    #[serde(deserialize_with = "config::lenient_f64")]
+    pub min_score: f64,
+    /// Trades taken per cycle, best score first
+    #[serde(deserialize_with = "config::lenient_u32")]
+    pub top_k: u32,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            edge_weight: 0.4,
+            confidence_weight: 0.2,
+            liquidity_weight: 0.15,
+            time_weight: 0.1,
+            calibration_weight: 0.15,
+            edge_scale: 0.2,
+            liquidity_scale: 50_000.0,
+            horizon_days: 30,
+            min_score: 0.45,
+            top_k: 3,
+        }
+    }
+}
+
+impl ScoringConfig {
+    pub fn total_weight(&self) -> f64 {
+        self.edge_weight + self.confidence_weight + self.liquidity_weight + self.time_weight + self.calibration_weight
+    }
+}
+
+/// A candidate's score with the components behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateScore {
+    pub market_id: String,
+    pub market_name: String,
+    pub score: f64,
+    pub edge: f64,
+    pub confidence: f64,
+    pub liquidity: f64,
+    pub time: f64,
+    pub calibration: f64,
+    /// Picked for a trade this cycle
+    pub selected: bool,
+}
+
+/// Calibration of one category under one prompt pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackCalibration {
+    pub category: String,
+    /// `None` for the generic prompt
+    pub prompt_pack: Option<String>,
+    pub samples: usize,
+    pub brier: f64,
+    pub score: f64,
+}
+
+/// Squared error of a settled, non-refunded signal's fair price
+fn settled_error(signal: &Signal) -> Option<f64> {
+    let settled = signal.resolution.as_ref()
+        .is_some_and(|r| r.is_settled() && r.status != ResolutionStatus::Invalid);
+    let payout = signal.payout().filter(|_| settled)?;
+    Some((signal.fair_price - payout).powi(2))
+}
+
+/// 1 - Brier / 0.25, so 0 is no better than always saying 50% and 1 is perfect
+fn calibration_score(brier: f64) -> f64 {
+    (1.0 - brier / 0.25).clamp(0.0, 1.0)
+}
+
+/// How well the model's fair prices predicted settled outcomes per category
+pub fn category_calibration(signals: &[Signal]) -> HashMap<String, f64> {
+    let mut errors: HashMap<String, Vec<f64>> = HashMap::new();
+    for signal in signals {
+        let Some(error) = settled_error(signal) else { continue };
+        errors
+            .entry(risk::category_key(signal.category.as_deref()))
+            .or_default()
+            .push(error);
+    }
+
+    errors
+        .into_iter()
+        .filter(|(_, e)| e.len() >= MIN_CALIBRATION_SAMPLES)
+        .map(|(category, e)| {
+            let brier = e.iter().sum::<f64>() / e.len() as f64;
+            (category, calibration_score(brier))
+        })
+        .collect()
+}
+
+/// Calibration per category and prompt pack, so a specialized pack can be compared with
+/// the generic prompt on the same category. Every group is listed with its sample count.
+pub fn pack_calibration(signals: &[Signal]) -> Vec<PackCalibration> {
+    let mut errors: HashMap<(String, Option<String>), Vec<f64>> = HashMap::new();
+    for signal in signals {
+        let Some(error) = settled_error(signal) else { continue };
+        errors
+            .entry((risk::category_key(signal.category.as_deref()), signal.prompt_pack.clone()))
+            .or_default()
+            .push(error);
+    }
+
+    let mut calibration: Vec<PackCalibration> = errors
+        .into_iter()
+        .map(|((category, prompt_pack), e)| {
+            let brier = e.iter().sum::<f64>() / e.len() as f64;
+            PackCalibration { category, prompt_pack, samples: e.len(), brier, score: calibration_score(brier) }
+        })
+        .collect();
+    calibration.sort_by(|a, b| a.category.cmp(&b.category).then_with(|| a.prompt_pack.cmp(&b.prompt_pack)));
+    calibration
+}
+
+pub fn score(
+    market: &Market,
+    prediction: &AIPrediction,
+    calibration: &HashMap<String, f64>,
+    config: &ScoringConfig,
+    now: DateTime<Utc>,
+) -> CandidateScore {
+    let edge = (prediction.edge / config.edge_scale.max(f64::EPSILON)).clamp(0.0, 1.0);
+    let confidence = prediction.confidence.clamp(0.0, 1.0);
+    let liquidity = ((1.0 + market.liquidity.max(0.0)).ln() / (1.0 + config.liquidity_scale.max(1.0)).ln()).clamp(0.0, 1.0);
+    let time = market.end_date
+        .as_deref()
+        .and_then(time::parse_timestamp)
+        .map(|end| {
+            let days = (end - now).num_minutes() as f64 / (60.0 * 24.0);
+            1.0 - (days / config.horizon_days.max(1) as f64).clamp(0.0, 1.0)
+        })
+        .unwrap_or(NEUTRAL);
+    let calibration = calibration
+        .get(&risk::category_key(market.category.as_deref()))
+        .copied()
+        .unwrap_or(NEUTRAL);
+
+    let weighted = config.edge_weight * edge
+        + config.confidence_weight * confidence
+        + config.liquidity_weight * liquidity
+        + config.time_weight * time
+        + config.calibration_weight * calibration;
+
+    CandidateScore {
+        market_id: market.id.clone(),
+        market_name: market.question.clone(),
+        score: weighted / config.total_weight().max(f64::EPSILON),
+        edge,
+        confidence,
+        liquidity,
+        time,
+        calibration,
+        selected: false,
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::{bail, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+pub const MAX_PATHS: u32 = 100_000;
+pub const MAX_TRADES_PER_PATH: u32 = 5_000;
+
+/// Track record the simulation is calibrated from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MeasuredPerformance {
+    pub settled_trades: u32,
+    pub win_rate: f64,
+    pub avg_edge: f64,
+    /// Average entry price of settled trades
+    pub avg_price: f64,
+}
+
+/// Monte Carlo settings; unset performance inputs fall back to the measured values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationParams {
+    pub paths: u32,
+    pub trades_per_path: u32,
+    pub win_rate: Option<f64>,
+    pub avg_edge: Option<f64>,
+    pub avg_price: Option<f64>,
+    /// Fraction of the full Kelly stake bet on each trade
+    pub kelly_fraction: f64,
+    /// A path is ruined once equity falls to this fraction of the starting balance
+    pub ruin_fraction: f64,
+    /// Fixed seed for reproducible runs
+    pub seed: Option<u64>,
+}
+
+impl Default for SimulationParams {
+    fn default() -> Self {
+        Self {
+            paths: 10_000,
+            trades_per_path: 200,
+            win_rate: None,
+            avg_edge: None,
+            avg_price: None,
+            kelly_fraction: 0.25,
+            ruin_fraction: 0.2,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Percentiles {
+    pub p5: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p95: f64,
+}
+
+impl Percentiles {
+    fn of(values: &mut [f64]) -> Self {
+        values.sort_by(|a, b| a.total_cmp(b));
+        let at = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+        Self { p5: at(0.05), p25: at(0.25), p50: at(0.5), p75: at(0.75), p95: at(0.95) }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationResult {
+    pub paths: u32,
+    pub trades_per_path: u32,
+    pub win_rate: f64,
+    pub avg_edge: f64,
+    pub avg_price: f64,
+    /// Fraction of equity staked per trade under the sizing policy
+    pub stake_fraction: f64,
+    pub final_balance: Percentiles,
+    pub max_drawdown: Percentiles,
+    pub expected_max_drawdown: f64,
+    pub risk_of_ruin: f64,
+}
+
+/// Simulate equity paths of repeated binary bets sized by fractional Kelly, capped at `max_bet`
+pub fn simulate(
+    params: &SimulationParams,
+    measured: &MeasuredPerformance,
+    starting_balance: f64,
+    max_bet: f64,
+) -> Result<SimulationResult> {
+    let win_rate = params.win_rate.unwrap_or(measured.win_rate);
+    let avg_edge = params.avg_edge.unwrap_or(measured.avg_edge);
+    let avg_price = params.avg_price.unwrap_or(measured.avg_price);
+
+    if params.win_rate.is_none() && measured.settled_trades == 0 {
+        bail!("No settled trades to measure a win rate from; provide one explicitly");
+    }
+    if !(0.0..=1.0).contains(&win_rate) {
+        bail!("Win rate must be between 0 and 1");
+    }
+    if avg_price <= 0.0 || avg_price >= 1.0 {
+        bail!("Average entry price must be between 0 and 1");
+    }
+    if starting_balance <= 0.0 {
+        bail!("Starting balance must be greater than 0");
+    }
+    if params.paths == 0 || params.paths > MAX_PATHS {
+        bail!("Paths must be between 1 and {}", MAX_PATHS);
+    }
+    if params.trades_per_path == 0 || params.trades_per_path > MAX_TRADES_PER_PATH {
+        bail!("Trades per path must be between 1 and {}", MAX_TRADES_PER_PATH);
+    }
+
+    let stake_fraction = ((avg_edge / (1.0 - avg_price)).clamp(0.0, 1.0) * params.kelly_fraction).clamp(0.0, 1.0);
+    let ruin_level = starting_balance * params.ruin_fraction;
+    let payoff = 1.0 / avg_price - 1.0;
+
+    let mut rng = match params.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let mut finals = Vec::with_capacity(params.paths as usize);
+    let mut drawdowns = Vec::with_capacity(params.paths as usize);
+    let mut ruined = 0u32;
+
+    for _ in 0..params.paths {
+        let mut balance = starting_balance;
+        let mut peak = starting_balance;
+        let mut max_drawdown: f64 = 0.0;
+        let mut is_ruined = false;
+
+        for _ in 0..params.trades_per_path {
+            let stake = (balance * stake_fraction).min(max_bet);
+            if stake < 1.0 {
+                break;
+            }
+            if rng.gen::<f64>() < win_rate {
+                balance += stake * payoff;
+            } else {
+                balance -= stake;
+            }
+            peak = peak.max(balance);
+            max_drawdown = max_drawdown.max((peak - balance) / peak);
+            if balance <= ruin_level {
+                is_ruined = true;
+                break;
+            }
+        }
+
+        if is_ruined {
+            ruined += 1;
+        }
+        finals.push(balance);
+        drawdowns.push(max_drawdown);
+    }
+
+    let expected_max_drawdown = drawdowns.iter().sum::<f64>() / drawdowns.len() as f64;
+
+    Ok(SimulationResult {
+        paths: params.paths,
+        trades_per_path: params.trades_per_path,
+        win_rate,
+        avg_edge,
+        avg_price,
+        stake_fraction,
+        final_balance: Percentiles::of(&mut finals),
+        max_drawdown: Percentiles::of(&mut drawdowns),
+        expected_max_drawdown,
+        risk_of_ruin: ruined as f64 / params.paths as f64,
+    })
+}
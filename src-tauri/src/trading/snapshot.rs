@@ -0,0 +1,101 @@
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use super::i18n::Locale;
+use super::models::*;
+
+/// Everything a rendered view depends on besides the collection's own revision
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewKey {
+    pub revision: u64,
+    pub locale: Locale,
+    pub timezone: String,
+    pub masked: bool,
+    pub min_level: LogLevel,
+}
+
+/// Last rendered view of a collection, shared with every reader until its key changes,
+/// so repeated polls of an unchanged collection allocate nothing
+pub struct Snapshot<T> {
+    slot: Mutex<Option<(ViewKey, Arc<Vec<T>>)>>,
+}
+
+impl<T> Default for Snapshot<T> {
+    fn default() -> Self {
+        Self { slot: Mutex::new(None) }
+    }
+}
+
+impl<T> Snapshot<T> {
+    pub fn get(&self, key: ViewKey, build: impl FnOnce() -> Vec<T>) -> Arc<Vec<T>> {
+        let mut slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((ref cached, ref view)) = *slot {
+            if *cached == key {
+                return Arc::clone(view);
+            }
+        }
+        let view = Arc::new(build());
+        *slot = Some((key, Arc::clone(&view)));
+        view
+    }
+}
+
+/// Activity added after a client's cursor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityDelta {
+    pub entries: Vec<ActivityEntry>,
+    /// Pass back on the next call
+    pub cursor: u64,
+    /// The cursor was unknown (app restarted) or already trimmed; `entries` is the whole
+    /// feed and replaces what the client holds
+    pub reset: bool,
+}
+
+/// Where a client's copy of the stats and balance history stands
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsCursor {
+    /// Fingerprint of the stats last sent
+    pub stats: u64,
+    /// Balance points already held
+    pub balance_points: usize,
+}
+
+/// Stats, if they changed, and balance points added after a client's cursor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsDelta {
+    pub stats: Option<BotStats>,
+    pub balance_points: Vec<BalancePoint>,
+    pub cursor: StatsCursor,
+    /// `balance_points` is the whole history and replaces what the client holds
+    pub reset: bool,
+}
+
+/// Cheap change detector for the order list: covers every field that changes after
+/// an order is placed, without cloning anything
+pub fn orders_revision(orders: &[Order]) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    orders.len().hash(&mut hasher);
+    for order in orders {
+        order.id.hash(&mut hasher);
+        std::mem::discriminant(&order.status).hash(&mut hasher);
+        // Partial exits shrink the size and expired rests are trimmed to their filled part
+        order.size.to_bits().hash(&mut hasher);
+        order.price.to_bits().hash(&mut hasher);
+        order.filled_size.to_bits().hash(&mut hasher);
+        order.pnl.map(f64::to_bits).hash(&mut hasher);
+        order.exited_pnl.to_bits().hash(&mut hasher);
+        order.resolved_at.hash(&mut hasher);
+        order.resolution.as_ref().map(|r| std::mem::discriminant(&r.status)).hash(&mut hasher);
+        order.child_ids.len().hash(&mut hasher);
+        order.replaced_by.hash(&mut hasher);
+        order.clob_order_id.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Fingerprint of the stats as a client would receive them
+pub fn stats_revision(stats: &BotStats) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    serde_json::to_string(stats).unwrap_or_default().hash(&mut hasher);
+    hasher.finish()
+}
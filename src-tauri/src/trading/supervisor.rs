@@ -0,0 +1,104 @@
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use tokio::sync::Mutex;
+use super::engine::TradingEngine;
+use super::i18n::Message;
+use super::models::*;
+use super::notifications::NotificationKind;
+
+const MAX_RESTARTS_PER_HOUR: usize = 5;
+const INITIAL_BACKOFF_SECS: u64 = 5;
+const MAX_BACKOFF_SECS: u64 = 300;
+
+/// Run the scan loop in a child task, restarting it with exponential backoff when it panics.
+/// Gives up and stops the bot after `MAX_RESTARTS_PER_HOUR` restarts within an hour.
+pub async fn supervise(engine: Arc<Mutex<TradingEngine>>) {
+    install_panic_hook();
+
+    let mut restarts: VecDeque<chrono::DateTime<Utc>> = VecDeque::new();
+    let mut backoff = INITIAL_BACKOFF_SECS;
+
+    loop {
+        let started = Utc::now();
+        let result = tokio::spawn(cycle_loop(engine.clone())).await;
+
+        let Err(err) = result else {
+            // The loop only returns on shutdown
+            return;
+        };
+        if !err.is_panic() {
+            return;
+        }
+        let reason = panic_message(err.into_panic());
+
+        // A loop that ran cleanly for a while earns a fresh backoff
+        if (Utc::now() - started).num_seconds() as u64 > MAX_BACKOFF_SECS {
+            backoff = INITIAL_BACKOFF_SECS;
+        }
+
+        let hour_ago = Utc::now() - chrono::Duration::hours(1);
+        while restarts.front().is_some_and(|t| *t < hour_ago) {
+            restarts.pop_front();
+        }
+
+        let mut eng = engine.lock().await;
+        if restarts.len() >= MAX_RESTARTS_PER_HOUR {
+            let msg = Message::new("supervisor.halted")
+                .arg("count", restarts.len() + 1)
+                .arg("reason", &reason);
+            eng.notifications.notify(NotificationKind::RiskHalt, "Trading stopped", &msg.render(eng.locale));
+            eng.add_message(msg, ActivityType::Error);
+            eng.stop();
+            return;
+        }
+
+        restarts.push_back(Utc::now());
+        let msg = Message::new("supervisor.restarting")
+            .arg("reason", &reason)
+            .arg("backoff", backoff)
+            .arg("restarts", restarts.len())
+            .arg("max", MAX_RESTARTS_PER_HOUR);
+        eng.add_message(msg, ActivityType::Error);
+        drop(eng);
+
+        tokio::time::sleep(Duration::from_secs(backoff)).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF_SECS);
+    }
+}
+
+/// Run a cycle every `scan_interval_secs` while the bot is running
+async fn cycle_loop(engine: Arc<Mutex<TradingEngine>>) {
+    loop {
+        let interval = {
+            let mut eng = engine.lock().await;
+            if eng.is_running {
+                if let Err(e) = eng.run_cycle().await {
+                    eng.add_message(Message::new("cycle.error").arg("error", e), ActivityType::Error);
+                }
+            }
+            eng.config.scan_interval_secs.max(1)
+        };
+        tokio::time::sleep(Duration::from_secs(interval as u64)).await;
+    }
+}
+
+/// Log every panic with its backtrace before the default hook runs
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log::error!("{}\n{}", info, Backtrace::force_capture());
+        default_hook(info);
+    }));
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer};
+
+/// Resolve a configured IANA timezone name, falling back to UTC
+pub fn display_tz(name: &str) -> Tz {
+    name.trim().parse::<Tz>().unwrap_or(Tz::UTC)
+}
+
+/// Format a UTC timestamp as wall-clock time in the display timezone
+pub fn format_display(ts: &DateTime<Utc>, tz_name: &str) -> String {
+    ts.with_timezone(&display_tz(tz_name)).format("%H:%M:%S").to_string()
+}
+
+/// Parse a persisted timestamp, accepting RFC3339 as well as the legacy
+/// "%H:%M:%S" / "[%H:%M:%S]" strings, which are assumed to be from today (UTC)
+pub fn parse_timestamp(raw: &str) -> Option<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(raw) {
+        return Some(ts.with_timezone(&Utc));
+    }
+
+    let legacy = raw.trim().trim_start_matches('[').trim_end_matches(']');
+    NaiveTime::parse_from_str(legacy, "%H:%M:%S")
+        .ok()
+        .map(|t| Utc::now().date_naive().and_time(t).and_utc())
+}
+
+/// Serde deserializer for `DateTime<Utc>` fields that migrates legacy strings
+pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = String::deserialize(deserializer)?;
+    parse_timestamp(&raw)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid timestamp: {}", raw)))
+}
+
+/// Serde deserializer for `Option<DateTime<Utc>>` fields that migrates legacy strings
+pub fn deserialize_option<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(raw) => parse_timestamp(&raw)
+            .map(Some)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid timestamp: {}", raw))),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::chain::{self, ChainClient, COLLATERAL_DECIMALS};
+use super::network::Network;
+
+/// `allowance(address,address)`
+const ALLOWANCE_SELECTOR: &str = "dd62ed3e";
+/// `approve(address,uint256)`
+const APPROVE_SELECTOR: &str = "095ea7b3";
+/// `isApprovedForAll(address,address)`
+const IS_APPROVED_FOR_ALL_SELECTOR: &str = "e985e9c5";
+/// `setApprovalForAll(address,bool)`
+const SET_APPROVAL_FOR_ALL_SELECTOR: &str = "a22cb465";
+/// Collateral allowance, in dollars, treated as unlimited; the approvals built here grant uint256 max
+const UNLIMITED_ALLOWANCE: f64 = 1e12;
+/// Headroom added to the node's gas estimate
+const GAS_BUFFER: f64 = 1.2;
+
+/// Which token an approval is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenKind {
+    /// USDC (ERC-20), spent when buying
+    Collateral,
+    /// Outcome shares on the Conditional Tokens contract (ERC-1155), moved when selling
+    ConditionalTokens,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AllowanceState {
+    Approved,
+    /// A finite collateral allowance that live orders will eventually exhaust
+    Limited,
+    Missing,
+}
+
+/// One token approval the exchange contracts need before live orders can settle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Allowance {
+    pub token: TokenKind,
+    pub token_address: String,
+    pub spender: String,
+    pub spender_name: String,
+    pub state: AllowanceState,
+    /// Collateral allowance in dollars; `None` for the all-or-nothing ERC-1155 approval
+    pub amount: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowanceStatus {
+    pub wallet: String,
+    pub chain_id: u64,
+    pub allowances: Vec<Allowance>,
+    /// Every approval is in place
+    pub ready: bool,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Approval transaction for one missing allowance; `tx_hash` is set once a signer sent it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalTx {
+    pub token: TokenKind,
+    pub spender_name: String,
+    pub from: String,
+    pub to: String,
+    pub chain_id: u64,
+    /// Hex calldata, 0x-prefixed
+    pub data: String,
+    pub nonce: u64,
+    pub gas: u64,
+    pub gas_price_wei: u128,
+    pub tx_hash: Option<String>,
+}
+
+impl ApprovalTx {
+    /// Worst-case fee in the chain's native token (POL on Polygon)
+    pub fn max_fee(&self) -> f64 {
+        self.gas as f64 * self.gas_price_wei as f64 / 1e18
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "from": self.from,
+            "to": self.to,
+            "data": self.data,
+            "nonce": format!("0x{:x}", self.nonce),
+            "gas": format!("0x{:x}", self.gas),
+            "gasPrice": format!("0x{:x}", self.gas_price_wei),
+            "chainId": format!("0x{:x}", self.chain_id),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalBatch {
+    pub transactions: Vec<ApprovalTx>,
+    /// Sent through the signer RPC; otherwise the transactions are unsigned, for an external wallet
+    pub sent: bool,
+}
+
+/// Spenders that move the wallet's tokens when orders settle: both exchanges and the neg-risk adapter
+fn spenders(network: &Network) -> [(&'static str, &str); 3] {
+    [
+        ("CTF Exchange", &network.exchange_address),
+        ("Neg Risk Exchange", &network.neg_risk_exchange_address),
+        ("Neg Risk Adapter", &network.neg_risk_adapter),
+    ]
+}
+
+fn address_word(address: &str) -> String {
+    chain::address_topic(address).trim_start_matches("0x").to_string()
+}
+
+fn approval_data(token: TokenKind, spender: &str) -> String {
+    match token {
+        TokenKind::Collateral => format!("0x{}{}{}", APPROVE_SELECTOR, address_word(spender), "f".repeat(64)),
+        TokenKind::ConditionalTokens => format!("0x{}{}{:064x}", SET_APPROVAL_FOR_ALL_SELECTOR, address_word(spender), 1),
+    }
+}
+
+/// Read every collateral allowance and CTF operator approval the exchanges need
+pub async fn check_allowances(chain: &ChainClient, wallet: &str) -> Result<AllowanceStatus> {
+    let network = chain.network();
+    let mut allowances = Vec::new();
+
+    for (name, spender) in spenders(network) {
+        let data = format!("0x{}{}{}", ALLOWANCE_SELECTOR, address_word(wallet), address_word(spender));
+        let raw = chain.eth_call(&network.collateral_token, &data).await
+            .with_context(|| format!("Reading USDC allowance for {}", name))?;
+        let amount = chain::parse_hex_f64(&raw).context("Invalid allowance")? / 10f64.powi(COLLATERAL_DECIMALS as i32);
+        allowances.push(Allowance {
+            token: TokenKind::Collateral,
+            token_address: network.collateral_token.clone(),
+            spender: spender.to_string(),
+            spender_name: name.to_string(),
+            state: if amount >= UNLIMITED_ALLOWANCE {
+                AllowanceState::Approved
+            } else if amount > 0.0 {
+                AllowanceState::Limited
+            } else {
+                AllowanceState::Missing
+            },
+            amount: Some(amount),
+        });
+    }
+
+    for (name, spender) in spenders(network) {
+        let data = format!("0x{}{}{}", IS_APPROVED_FOR_ALL_SELECTOR, address_word(wallet), address_word(spender));
+        let raw = chain.eth_call(&network.conditional_tokens, &data).await
+            .with_context(|| format!("Reading CTF approval for {}", name))?;
+        let approved = chain::parse_hex_f64(&raw).context("Invalid approval flag")? > 0.0;
+        allowances.push(Allowance {
+            token: TokenKind::ConditionalTokens,
+            token_address: network.conditional_tokens.clone(),
+            spender: spender.to_string(),
+            spender_name: name.to_string(),
+            state: if approved { AllowanceState::Approved } else { AllowanceState::Missing },
+            amount: None,
+        });
+    }
+
+    Ok(AllowanceStatus {
+        wallet: wallet.to_string(),
+        chain_id: network.chain_id,
+        ready: allowances.iter().all(|a| a.state == AllowanceState::Approved),
+        allowances,
+        checked_at: Utc::now(),
+    })
+}
+
+/// Build an approval for every allowance not yet granted, with consecutive nonces and
+/// buffered gas estimates from the node
+pub async fn build_approvals(chain: &ChainClient, status: &AllowanceStatus) -> Result<Vec<ApprovalTx>> {
+    let missing: Vec<&Allowance> = status.allowances.iter().filter(|a| a.state != AllowanceState::Approved).collect();
+    if missing.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let gas_price_wei = chain.gas_price().await?;
+    let first_nonce = chain.transaction_count(&status.wallet).await?;
+    let mut txs = Vec::new();
+    for (i, allowance) in missing.into_iter().enumerate() {
+        let data = approval_data(allowance.token, &allowance.spender);
+        let estimate = chain.estimate_gas(&status.wallet, &allowance.token_address, &data).await
+            .with_context(|| format!("Estimating gas to approve {}", allowance.spender_name))?;
+        txs.push(ApprovalTx {
+            token: allowance.token,
+            spender_name: allowance.spender_name.clone(),
+            from: status.wallet.clone(),
+            to: allowance.token_address.clone(),
+            chain_id: status.chain_id,
+            data,
+            nonce: first_nonce + i as u64,
+            gas: (estimate as f64 * GAS_BUFFER).ceil() as u64,
+            gas_price_wei,
+            tx_hash: None,
+        });
+    }
+    Ok(txs)
+}
+
+/// Send the approvals in nonce order through the signer, stopping at the first failure so
+/// no later nonce is left waiting on a gap
+pub async fn send_approvals(signer: &ChainClient, txs: &mut [ApprovalTx]) -> Result<()> {
+    for tx in txs.iter_mut() {
+        let hash = signer.send_transaction(tx.to_json()).await
+            .with_context(|| format!("Sending approval for {}", tx.spender_name))?;
+        tx.tx_hash = Some(hash);
+    }
+    Ok(())
+}